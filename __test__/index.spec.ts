@@ -1,27 +1,219 @@
+import { randomBytes } from 'node:crypto'
+import { createSocket } from 'node:dgram'
+import { readFileSync, writeFileSync } from 'node:fs'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+import { fileURLToPath } from 'node:url'
+import { Worker } from 'node:worker_threads'
+
 import test from 'ava'
 
-import { MicroserviceCache } from '../index'
+import {
+  CacheMiss,
+  ChainDemotion,
+  ChangeRecord,
+  ClockMode,
+  Compression,
+  EvictionPolicy,
+  KeyHash,
+  MemoryPressure,
+  MicroserviceCache,
+  TransactionCommand,
+} from '../index'
+
+const fakeRedis = `
+  const { createServer } = require('node:net')
+  const { parentPort } = require('node:worker_threads')
+  const store = new Map()
+  const sockets = new Set()
+  const subscribers = new Map()
+  const bulk = (value) => '$' + value.length + '\\r\\n' + value.toString('latin1') + '\\r\\n'
+  const reply = (value) => (value === undefined ? '$-1\\r\\n' : typeof value === 'number' ? ':' + value + '\\r\\n' : bulk(value))
+  const run = (socket, [command, key, ...args]) => {
+    switch (command.toString().toUpperCase()) {
+      case 'GET': return reply(store.get(key.toString()))
+      case 'PTTL': return reply(store.has(key.toString()) ? -1 : -2)
+      case 'SET': case 'PSETEX': store.set(key.toString(), args.at(-1)); return '+OK\\r\\n'
+      case 'DEL': return reply(store.delete(key.toString()) ? 1 : 0)
+      case 'SUBSCRIBE': subscribers.set(socket, key.toString()); return '*3\\r\\n' + bulk('subscribe') + bulk(key) + ':1\\r\\n'
+      case 'PUBLISH': {
+        const targets = [...subscribers].filter(([, channel]) => channel === key.toString())
+        for (const [target] of targets) target.write(Buffer.from('*3\\r\\n' + bulk('message') + bulk(key) + bulk(args[0]), 'latin1'))
+        return reply(targets.length)
+      }
+      default: return '+OK\\r\\n'
+    }
+  }
+  const server = createServer((socket) => {
+    sockets.add(socket)
+    socket.on('close', () => (sockets.delete(socket), subscribers.delete(socket)))
+    let buffer = Buffer.alloc(0)
+    socket.on('data', (chunk) => {
+      buffer = Buffer.concat([buffer, chunk])
+      for (;;) {
+        const header = buffer.indexOf('\\r\\n')
+        if (buffer[0] !== 42 || header < 0) return
+        const parts = []
+        let cursor = header + 2
+        for (let i = Number(buffer.subarray(1, header)); i > 0; i--) {
+          const end = buffer.indexOf('\\r\\n', cursor)
+          const length = Number(buffer.subarray(cursor + 1, end))
+          if (end < 0 || buffer.length < end + length + 4) return
+          parts.push(buffer.subarray(end + 2, end + 2 + length))
+          cursor = end + length + 4
+        }
+        buffer = buffer.subarray(cursor)
+        socket.write(Buffer.from(run(socket, parts), 'latin1'))
+      }
+    })
+  })
+  parentPort.on('message', () => sockets.forEach((socket) => socket.destroy()))
+  server.listen(0, '127.0.0.1', () => parentPort.postMessage(server.address().port))
+`
+
+const startFakeRedis = async () => {
+  const server = new Worker(fakeRedis, { eval: true })
+  const port = await new Promise<number>((resolve) => server.once('message', resolve))
+  return { server, url: `redis://127.0.0.1:${port}` }
+}
 
 test('MicroserviceCache basic functionality', (t) => {
   const cache = new MicroserviceCache()
 
-  const success = cache.set('test-key', 'test-value')
-  t.true(success)
+  const version = cache.set('test-key', 'test-value')
+  t.true(version > 0)
 
   const value = cache.get('test-key')
   t.is(value, 'test-value')
 })
 
 test('MicroserviceCache with TTL and tags', (t) => {
-  const cache = new MicroserviceCache(1000, 3600)
+  const cache = new MicroserviceCache({ maxSize: 1000, defaultTtlSeconds: 3600 })
 
-  const success = cache.set('tagged-key', 'tagged-value', 60, ['tag1', 'tag2'])
-  t.true(success)
+  const version = cache.set('tagged-key', 'tagged-value', 60, ['tag1', 'tag2'])
+  t.true(version > 0)
 
   const value = cache.get('tagged-key')
   t.is(value, 'tagged-value')
 })
 
+test('MicroserviceCache conditional writes', (t) => {
+  const cache = new MicroserviceCache()
+
+  t.false(cache.setIfPresent('nx-key', 'ignored'))
+  t.is(cache.get('nx-key'), null)
+
+  t.true(cache.setIfAbsent('nx-key', 'first'))
+  t.false(cache.setIfAbsent('nx-key', 'second'))
+  t.is(cache.get('nx-key'), 'first')
+
+  t.true(cache.setIfPresent('nx-key', 'updated'))
+  t.is(cache.get('nx-key'), 'updated')
+})
+
+test('MicroserviceCache has and peek do not affect stats', (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('peek-key', 'peek-value')
+
+  t.true(cache.has('peek-key'))
+  t.false(cache.has('missing'))
+  t.is(cache.peek('peek-key'), 'peek-value')
+  t.is(cache.peek('missing'), null)
+
+  const stats = cache.getStats()
+  t.is(stats.hits, 0)
+  t.is(stats.misses, 0)
+})
+
+test('MicroserviceCache compare-and-swap with versions', (t) => {
+  const cache = new MicroserviceCache()
+
+  const first = cache.set('cas-key', 'v1')
+  const second = cache.set('other-key', 'v1')
+  t.true(second > first)
+  t.is(cache.getVersion('cas-key'), first)
+
+  const swapped = cache.compareAndSwap('cas-key', first, 'v2')
+  t.true(swapped !== null && swapped > second)
+  t.is(cache.get('cas-key'), 'v2')
+
+  t.is(cache.compareAndSwap('cas-key', first, 'v3'), null)
+  t.is(cache.get('cas-key'), 'v2')
+})
+
+test('MicroserviceCache getOrSet loads on miss and caches the result', async (t) => {
+  const cache = new MicroserviceCache()
+  let calls = 0
+
+  const loader = async () => {
+    calls++
+    return 'loaded-value'
+  }
+
+  t.is(await cache.getOrSet('lazy-key', loader, 60), 'loaded-value')
+  t.is(await cache.getOrSet('lazy-key', loader, 60), 'loaded-value')
+  t.is(calls, 1)
+  t.is(cache.get('lazy-key'), 'loaded-value')
+})
+
+test('MicroserviceCache getOrSet propagates loader errors', async (t) => {
+  const cache = new MicroserviceCache()
+
+  await t.throwsAsync(
+    cache.getOrSet('failing-key', async () => {
+      throw new Error('upstream down')
+    }),
+    { message: 'upstream down' },
+  )
+  t.is(cache.get('failing-key'), null)
+})
+
+test('MicroserviceCache getOrSet coalesces concurrent misses', async (t) => {
+  const cache = new MicroserviceCache()
+  let calls = 0
+
+  const loader = () =>
+    new Promise<string>((resolve) => {
+      calls++
+      setTimeout(() => resolve('shared-value'), 20)
+    })
+
+  const results = await Promise.all(Array.from({ length: 10 }, () => cache.getOrSet('hot-key', loader)))
+
+  t.is(calls, 1)
+  t.true(results.every((value) => value === 'shared-value'))
+})
+
+test('MicroserviceCache atomic counters', (t) => {
+  const cache = new MicroserviceCache()
+
+  t.is(cache.increment('counter'), 1)
+  t.is(cache.increment('counter', 10), 11)
+  t.is(cache.decrement('counter', 3), 8)
+  t.is(cache.get('counter'), '8')
+  t.true(cache.expireAt('counter', 0))
+  t.is(cache.increment('counter', 2), 2)
+
+  cache.set('not-a-number', 'abc')
+  t.throws(() => cache.increment('not-a-number'))
+})
+
+test('MicroserviceCache tag lookup and invalidation', (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('order:123:items', 'items', 60, ['order:123'])
+  cache.set('order:123:total', 'total', 60, ['order:123', 'totals'])
+  cache.set('order:456:total', 'total', 60, ['order:456', 'totals'])
+
+  t.deepEqual(cache.getKeysByTag('order:123').sort(), ['order:123:items', 'order:123:total'])
+
+  t.is(cache.deleteByTag('order:123'), 2)
+  t.is(cache.get('order:123:items'), null)
+  t.deepEqual(cache.getKeysByTag('order:123'), [])
+  t.deepEqual(cache.getKeysByTag('totals'), ['order:456:total'])
+})
+
 test('MicroserviceCache delete functionality', (t) => {
   const cache = new MicroserviceCache()
 
@@ -33,6 +225,144 @@ test('MicroserviceCache delete functionality', (t) => {
   t.is(value, null)
 })
 
+test('MicroserviceCache pop and getSet', (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('job', 'pending')
+  t.is(cache.getSet('job', 'running'), 'pending')
+  t.is(cache.get('job'), 'running')
+  t.is(cache.getSet('new-job', 'queued'), null)
+
+  t.is(cache.pop('job'), 'running')
+  t.is(cache.pop('job'), null)
+  t.false(cache.has('job'))
+})
+
+test('MicroserviceCache take and deleteIfEquals', (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('user:1', 'alice')
+  cache.setWithDependencies('view:1', 'profile', ['user:1'])
+  t.is(cache.take('user:1'), 'alice')
+  t.is(cache.take('user:1'), null)
+  t.false(cache.has('view:1'))
+
+  cache.set('lock:report', 'owner-a')
+  t.false(cache.deleteIfEquals('lock:report', 'owner-b'))
+  t.is(cache.get('lock:report'), 'owner-a')
+  t.true(cache.deleteIfEquals('lock:report', 'owner-a'))
+  t.false(cache.has('lock:report'))
+  t.false(cache.deleteIfEquals('lock:report', 'owner-a'))
+})
+
+test('MicroserviceCache renames and copies entries with their metadata', (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('session:old', 'data', 60, ['sessions'])
+  cache.get('session:old')
+  const original = cache.getEntryInfo('session:old')!
+
+  t.true(cache.rename('session:old', 'session:new'))
+  t.false(cache.has('session:old'))
+  const renamed = cache.getEntryInfo('session:new')!
+  t.is(renamed.createdAt, original.createdAt)
+  t.is(renamed.expiresAt, original.expiresAt)
+  t.is(renamed.accessCount, original.accessCount)
+  t.true(renamed.version > original.version)
+  t.deepEqual(cache.getKeysByTag('sessions'), ['session:new'])
+
+  t.true(cache.copy('session:new', 'session:copy', 5))
+  t.is(cache.get('session:copy'), 'data')
+  t.is(cache.getTtl('session:copy'), 5)
+  t.is(cache.getTtl('session:new'), 60)
+  t.deepEqual(cache.getKeysByTag('sessions').sort(), ['session:copy', 'session:new'])
+
+  t.false(cache.rename('missing', 'other'))
+  t.false(cache.copy('missing', 'other'))
+  t.throws(() => cache.rename('session:new', 'session:new'), {
+    message: "Source and destination keys are both 'session:new'",
+  })
+})
+
+test('MicroserviceCache batch operations', (t) => {
+  const cache = new MicroserviceCache()
+
+  const written = cache.setMany([
+    { key: 'batch1', value: 'value1' },
+    { key: 'batch2', value: 'value2', ttlSeconds: 60, tags: ['batch'] },
+  ])
+  t.is(written, 2)
+
+  t.deepEqual(cache.getMany(['batch1', 'batch2', 'missing']), ['value1', 'value2', null])
+
+  t.is(cache.deleteMany(['batch1', 'missing']), 1)
+  t.is(cache.get('batch1'), null)
+  t.is(cache.get('batch2'), 'value2')
+})
+
+test('MicroserviceCache evicts the least recently used entry at capacity', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 3 })
+
+  cache.set('lru-a', 'a')
+  cache.set('lru-b', 'b')
+  cache.set('lru-c', 'c')
+  cache.get('lru-a')
+  cache.set('lru-d', 'd')
+
+  t.deepEqual(cache.keys().sort(), ['lru-a', 'lru-c', 'lru-d'])
+})
+
+test('MicroserviceCache supports LFU and FIFO eviction policies', (t) => {
+  const lfu = new MicroserviceCache({ maxSize: 2, evictionPolicy: EvictionPolicy.Lfu })
+  lfu.set('popular', 'p')
+  lfu.set('rare', 'r')
+  lfu.get('popular')
+  lfu.get('popular')
+  lfu.get('rare')
+  lfu.set('newcomer', 'n')
+  t.deepEqual(lfu.keys().sort(), ['newcomer', 'popular'])
+
+  const fifo = new MicroserviceCache({ maxSize: 2, evictionPolicy: EvictionPolicy.Fifo })
+  fifo.set('first', '1')
+  fifo.set('second', '2')
+  fifo.get('first')
+  fifo.set('third', '3')
+  t.deepEqual(fifo.keys().sort(), ['second', 'third'])
+})
+
+test('MicroserviceCache TinyLFU keeps hot keys through scans', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 100, evictionPolicy: EvictionPolicy.TinyLfu })
+  let hits = 0
+  let lookups = 0
+
+  for (let round = 0; round < 50; round++) {
+    for (let i = 0; i < 50; i++) {
+      lookups++
+      if (cache.get(`hot-${i}`) !== null) hits++
+      else cache.set(`hot-${i}`, 'hot')
+    }
+    for (let i = 0; i < 100; i++) {
+      cache.set(`scan-${round}-${i}`, 'cold')
+    }
+  }
+
+  t.true(hits / lookups > 0.9)
+})
+
+test('MicroserviceCache enforces a byte budget', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 1000, maxBytes: 100 })
+
+  for (let i = 0; i < 10; i++) {
+    cache.set(`blob-${i}`, 'x'.repeat(20))
+  }
+
+  const { memory } = cache.getStats()
+  t.true(memory.totalBytes <= 100)
+  t.is(memory.maxBytes, 100)
+  t.true(cache.has('blob-9'))
+  t.false(cache.has('blob-0'))
+
+  t.throws(() => cache.set('too-big', 'x'.repeat(200)))
+})
+
 test('MicroserviceCache get all keys', (t) => {
   const cache = new MicroserviceCache()
 
@@ -47,14 +377,2162 @@ test('MicroserviceCache get all keys', (t) => {
   t.true(keys.includes('key3'))
 })
 
-test('MicroserviceCache stats and flush', (t) => {
+test('MicroserviceCache setPx expires with millisecond precision', async (t) => {
   const cache = new MicroserviceCache()
 
-  cache.set('stat-key1', 'value1')
-  cache.set('stat-key2', 'value2')
+  cache.setPx('flag', 'on', 100)
+  t.is(cache.get('flag'), 'on')
+
+  await new Promise((resolve) => setTimeout(resolve, 200))
+  t.is(cache.get('flag'), null)
+})
+
+test('MicroserviceCache spreads expirations with TTL jitter', async (t) => {
+  const cache = new MicroserviceCache({ ttlJitterPercent: 50 })
+
+  for (let i = 0; i < 200; i++) {
+    cache.setPx(`jitter-${i}`, 'value', 1000)
+  }
+
+  await new Promise((resolve) => setTimeout(resolve, 750))
+  const alive = cache.keys().length
+  t.true(alive > 0 && alive < 200)
+})
+
+test('MicroserviceCache sliding expiration extends idle entries on read', async (t) => {
+  const cache = new MicroserviceCache()
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms))
+
+  cache.set('session', 'data', null, null, { ttiSeconds: 1 })
+
+  for (let i = 0; i < 3; i++) {
+    await sleep(800)
+    t.is(cache.get('session'), 'data')
+  }
+
+  await sleep(2200)
+  t.is(cache.get('session'), null)
+})
+
+test('MicroserviceCache serves stale values while revalidating', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 100, staleWhileRevalidateSeconds: 2 })
+  let loads = 0
+  cache.registerLoader(async (key) => `${key}-fresh-${++loads}`)
+
+  cache.setPx('swr', 'stale', 50)
+  await new Promise((resolve) => setTimeout(resolve, 100))
+
+  t.is(cache.get('swr'), 'stale')
+  t.is(cache.get('swr'), 'stale')
+
+  await new Promise((resolve) => setTimeout(resolve, 50))
+  t.is(cache.get('swr'), 'swr-fresh-1')
+  t.is(loads, 1)
+
+  const stats = cache.getStats()
+  t.is(stats.counters.stale_hits, 2)
+  t.is(stats.operations.refreshes, 1)
+  t.true(cache.unregisterLoader())
+})
+
+test('MicroserviceCache refreshes hot keys ahead of expiry', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 100, refreshAheadPercent: 50 })
+  let loads = 0
+  cache.registerLoader(async (key) => `${key}-fresh-${++loads}`)
+
+  cache.setPx('hot', 'initial', 200)
+  await new Promise((resolve) => setTimeout(resolve, 120))
+
+  t.is(cache.get('hot'), 'initial')
+  await new Promise((resolve) => setTimeout(resolve, 50))
+
+  t.is(cache.get('hot'), 'hot-fresh-1')
+  t.is(loads, 1)
+  t.is(cache.getStats().operations.refreshes, 1)
+})
+
+test('MicroserviceCache stores binary values', (t) => {
+  const cache = new MicroserviceCache()
+  const payload = Buffer.from([0x00, 0xff, 0x10, 0x80])
+
+  cache.setBuffer('binary', payload)
+  cache.set('text', 'héllo')
+
+  t.deepEqual(cache.getBuffer('binary'), payload)
+  t.is(cache.getBuffer('text')?.toString('utf8'), 'héllo')
+  t.is(cache.getBuffer('missing'), null)
+})
+
+test('MicroserviceCache stores JSON documents', (t) => {
+  const cache = new MicroserviceCache()
+  const profile = { user: { id: 42, roles: ['admin', 'ops'] } }
+
+  cache.setJson('profile', profile)
+  cache.set('raw-json', '{"count":3}')
+
+  t.deepEqual(cache.getJson('profile'), profile)
+  t.is(cache.get('profile'), JSON.stringify(profile))
+  t.is(cache.getJsonPath('profile', '$.user.id'), 42)
+  t.is(cache.getJsonPath('profile', '$.user.roles[1]'), 'ops')
+  t.is(cache.getJsonPath('profile', '$.user.email'), null)
+  t.is(cache.getJsonPath('raw-json', '$.count'), 3)
+  t.throws(() => cache.getJsonPath('profile', 'user.id'))
+})
+
+test('MicroserviceCache compresses large values transparently', (t) => {
+  const cache = new MicroserviceCache({ compression: Compression.Zstd, compressionThresholdBytes: 1024 })
+  const fragment = '<li class="item">fragment</li>'.repeat(2000)
+
+  cache.set('fragment', fragment)
+  cache.set('small', 'tiny')
+
+  t.is(cache.get('fragment'), fragment)
+  t.is(cache.get('small'), 'tiny')
+
+  const { memory } = cache.getStats()
+  t.is(memory.compressedValues, 1)
+  t.true(memory.compressionRatio! < 0.1)
+  t.true(memory.totalBytes < fragment.length)
+})
+
+test('MicroserviceCache treats corrupt compressed values as misses', (t) => {
+  const cache = new MicroserviceCache({ compression: Compression.Gzip, compressionThresholdBytes: 16 })
+  cache.set('doc', 'hello world '.repeat(100))
+  cache.set('ok', 'fine')
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}.corrupt`)
+  cache.saveSnapshot(path)
+
+  const snapshot = readFileSync(path)
+  const gzipValue = snapshot.indexOf(Buffer.from([3, 2, 0, 0x1f, 0x8b]))
+  t.true(gzipValue > 0)
+  snapshot[gzipValue + 20] ^= 0xff
+  writeFileSync(path, snapshot)
+
+  const restored = new MicroserviceCache()
+  restored.loadSnapshot(path)
+  t.is(restored.get('doc'), null)
+  t.is(restored.get('ok'), 'fine')
+  t.is(restored.getStats().counters.corrupt_values, 1)
+})
+
+test('MicroserviceCache encrypts values and rotates keys', (t) => {
+  const cache = new MicroserviceCache({ encryptionKey: randomBytes(32) })
+
+  cache.set('pii', 'jane@example.com')
+  t.is(cache.get('pii'), 'jane@example.com')
+
+  t.is(cache.rotateEncryptionKey(randomBytes(32)), 1)
+  t.is(cache.get('pii'), 'jane@example.com')
+  t.is(cache.get('pii'), 'jane@example.com')
+  t.is(cache.getStats().counters.reencrypted_values, 1)
+
+  t.throws(() => cache.rotateEncryptionKey(Buffer.alloc(8)))
+  t.throws(() => new MicroserviceCache().rotateEncryptionKey(randomBytes(32)))
+})
+
+test('MicroserviceCache skips values it cannot decrypt', (t) => {
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}.sealed`)
+  const cache = new MicroserviceCache({ encryptionKey: randomBytes(32) })
+  cache.set('pii', 'jane@example.com')
+  cache.saveSnapshot(path)
+
+  const restored = new MicroserviceCache({ encryptionKey: randomBytes(32) })
+  t.is(restored.loadSnapshot(path), 0)
+  t.is(restored.get('pii'), null)
+  t.is(restored.getStats().counters.corrupt_values, 1)
+  t.is(restored.increment('pii', 1), 1)
+})
+
+test('MicroserviceCache caches not-found results', async (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.setNegative('user:404')
+
+  t.true(cache.get('user:404') instanceof CacheMiss)
+  t.is(cache.get('user:unknown'), null)
+  t.is(cache.peek('user:404'), null)
+  t.true(cache.has('user:404'))
+  await t.throwsAsync(cache.getOrSet('user:404', async () => 'loaded'))
+
+  const { counters } = cache.getStats()
+  t.is(counters.negative_sets, 1)
+  t.is(counters.negative_hits, 2)
+})
+
+test('MicroserviceCache manages TTLs after set', async (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('ttl-key', 'value')
+  t.is(cache.getTtl('ttl-key'), -1)
+  t.is(cache.getTtl('missing'), null)
+
+  t.true(cache.expire('ttl-key', 10))
+  t.is(cache.getTtl('ttl-key'), 10)
+
+  t.true(cache.persist('ttl-key'))
+  t.false(cache.persist('ttl-key'))
+  t.is(cache.getTtl('ttl-key'), -1)
+
+  t.true(cache.expireAt('ttl-key', Math.floor(Date.now() / 1000) - 1))
+  t.is(cache.get('ttl-key'), null)
+  t.false(cache.expire('ttl-key', 10))
+})
+
+test('MicroserviceCache commits multi-key transactions atomically', (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('user:42', 'old@example.com')
+  cache.set('email:old@example.com', '42')
+  const version = cache.getVersion('user:42')
+
+  const tx = cache.transaction()
+  tx.expectVersion('user:42', version)
+  tx.expectVersion('email:new@example.com', 0)
+  tx.set('user:42', 'new@example.com')
+  tx.delete('email:old@example.com')
+  tx.set('email:new@example.com', '42')
+  tx.get('user:42')
+  const result = tx.commit()
+  t.true(result.committed)
+  t.deepEqual(result.conflicts, [])
+  t.deepEqual(result.values, ['new@example.com'])
+  t.is(result.versions.length, 2)
+  t.is(cache.get('user:42'), 'new@example.com')
+  t.false(cache.has('email:old@example.com'))
+  t.is(cache.get('email:new@example.com'), '42')
+
+  tx.expectVersion('user:42', version)
+  tx.set('user:42', 'stale@example.com')
+  tx.delete('email:new@example.com')
+  const conflict = tx.commit()
+  t.false(conflict.committed)
+  t.deepEqual(conflict.conflicts, ['user:42'])
+  t.is(cache.get('user:42'), 'new@example.com')
+  t.true(cache.has('email:new@example.com'))
+  t.is(cache.getStats().counters.cas_conflicts, 1)
+})
+
+test('MicroserviceCache keeps transaction keys out of eviction', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2 })
+  cache.set('user:1', 'Ana')
+  cache.set('user:2', 'Luis')
+
+  const tx = cache.transaction()
+  tx.expectVersion('user:1', cache.getVersion('user:1'))
+  tx.set('user:3', 'Eva')
+  t.true(tx.commit().committed)
+  t.is(cache.get('user:1'), 'Ana')
+  t.false(cache.has('user:2'))
+  t.is(cache.get('user:3'), 'Eva')
+})
+
+test('MicroserviceCache aborts watched transactions when a key changes', (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('stock:42', '5')
+
+  const tx = cache.watch(['stock:42', 'order:7'])
+  const ops = [
+    { op: TransactionCommand.Set, key: 'stock:42', value: '4' },
+    { op: TransactionCommand.Set, key: 'order:7', value: 'reserved' },
+    { op: TransactionCommand.Get, key: 'stock:42' },
+  ]
+  const result = tx.execIfUnchanged(ops)
+  t.true(result.committed)
+  t.deepEqual(result.values, ['4'])
+
+  const stale = cache.watch(['stock:42'])
+  cache.set('stock:42', '3')
+  const aborted = stale.execIfUnchanged([{ op: TransactionCommand.Delete, key: 'order:7' }])
+  t.false(aborted.committed)
+  t.deepEqual(aborted.conflicts, ['stock:42'])
+  t.is(cache.get('order:7'), 'reserved')
+
+  t.throws(() => cache.watch([]).execIfUnchanged([{ op: TransactionCommand.Set, key: 'order:7' }]), {
+    message: "Transaction set for key 'order:7' requires a value",
+  })
+})
+
+test('MicroserviceCache computes new values atomically', (t) => {
+  const cache = new MicroserviceCache()
+  const increment = (current: string | null) => String(Number(current ?? 0) + 1)
+  t.is(cache.compute('hits', increment), '1')
+  t.is(cache.compute('hits', increment), '2')
+
+  const append = (current: string | null) => JSON.stringify([...(current ? JSON.parse(current) : []), 'a'])
+  cache.compute('list', append)
+  t.deepEqual(JSON.parse(cache.compute('list', append)!), ['a', 'a'])
+
+  t.throws(() =>
+    cache.compute('hits', () => {
+      throw new Error('transform failed')
+    }),
+  )
+  t.is(cache.get('hits'), '2')
+  t.is(cache.compute('hits', () => null), null)
+  t.false(cache.has('hits'))
+
+  let calls = 0
+  const racing = (current: string | null) => {
+    calls += 1
+    if (calls === 1) {
+      cache.set('race', 'written')
+    }
+    return `${current}!`
+  }
+  t.is(cache.compute('race', racing), 'written!')
+  t.is(calls, 2)
+  t.is(cache.get('race'), 'written!')
+
+  let attempts = 0
+  t.throws(
+    () =>
+      cache.compute('self', (current) => {
+        attempts += 1
+        cache.set('self', String(attempts))
+        return `${current}!`
+      }),
+    { code: 'ERR_VERSION_CONFLICT' },
+  )
+  t.is(attempts, 8)
+  t.is(cache.get('self'), '8')
+  t.is(cache.getStats().counters.cas_conflicts, 1)
+})
+
+test('MicroserviceCache queues async computations per key', async (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('counter', '0')
+  const increment = async (current: string | null) => {
+    await new Promise((resolve) => setTimeout(resolve, 1))
+    return String(Number(current) + 1)
+  }
+  await Promise.all(Array.from({ length: 10 }, () => cache.computeAsync('counter', increment)))
+  t.is(cache.get('counter'), '10')
+
+  const slow = cache.computeAsync('counter', async (current) => {
+    await new Promise((resolve) => setTimeout(resolve, 20))
+    return String(Number(current) * 2)
+  })
+  setTimeout(() => cache.set('counter', '21'), 5)
+  t.is(await slow, '42')
+})
+
+test('MicroserviceCache keeps versions increasing after a restore', (t) => {
+  const base = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}-versions`)
+  const cache = new MicroserviceCache()
+  cache.enableAppendLog(`${base}.log`)
+  for (let i = 0; i < 5; i++) {
+    cache.set('views', `${i}`)
+  }
+  cache.set('draft', 'x')
+  cache.delete('draft')
+  const version = cache.getVersion('views')!
+  cache.saveSnapshot(`${base}.snapshot`)
+  cache.disableAppendLog()
+
+  const restored = new MicroserviceCache()
+  restored.loadSnapshot(`${base}.snapshot`)
+  t.is(restored.getVersion('views'), version)
+  t.true(restored.set('views', 'next') > version)
+
+  const replayed = new MicroserviceCache()
+  replayed.enableAppendLog(`${base}.log`)
+  t.is(replayed.getVersion('views'), version)
+  t.true(replayed.set('other', 'x') > version + 1)
+  replayed.disableAppendLog()
+})
+
+test('MicroserviceCache writes conditionally on the version read', (t) => {
+  const cache = new MicroserviceCache()
+  t.is(cache.getVersioned('views'), null)
+
+  cache.setIfVersion('views', '1', 0)
+  const read = cache.getVersioned('views')!
+  t.is(read.value, '1')
+  t.is(read.version, cache.getVersion('views'))
+
+  const version = cache.setIfVersion('views', '2', read.version)
+  t.true(version > read.version)
+  t.throws(() => cache.setIfVersion('views', '3', read.version), {
+    code: 'ERR_VERSION_CONFLICT',
+    message: `Version conflict for key 'views': expected ${read.version}, found ${version}`,
+  })
+  t.throws(() => cache.setIfVersion('views', '3', 0), { code: 'ERR_VERSION_CONFLICT' })
+  t.is(cache.get('views'), '2')
+
+  t.throws(
+    () =>
+      cache.setMany([
+        { key: 'fresh', value: 'a', expectedVersion: 0 },
+        { key: 'views', value: '4', expectedVersion: read.version },
+      ]),
+    { code: 'ERR_VERSION_CONFLICT' },
+  )
+  t.is(cache.get('fresh'), 'a')
+  t.is(cache.getStats().counters.cas_conflicts, 3)
+})
+
+test('MicroserviceCache cascades invalidation to dependent keys', (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('user:1', 'alice')
+  cache.set('post:9', 'hello')
+  cache.setWithDependencies('view:feed', 'feed', ['user:1', 'post:9'])
+  cache.setWithDependencies('view:page', 'page', ['view:feed'])
+  cache.setWithDependencies('view:post', 'post', ['post:9'])
+
+  cache.set('user:1', 'bob')
+  t.false(cache.has('view:feed'))
+  t.false(cache.has('view:page'))
+  t.true(cache.has('view:post'))
+
+  cache.delete('post:9')
+  t.false(cache.has('view:post'))
+  t.is(cache.getStats().counters.dependency_invalidations, 3)
+
+  cache.setWithDependencies('view:detached', 'x', ['user:1'])
+  cache.set('view:detached', 'y')
+  cache.delete('user:1')
+  t.is(cache.get('view:detached'), 'y')
+})
+
+test('MicroserviceCache applies per-tag default TTLs', (t) => {
+  const cache = new MicroserviceCache({ defaultTtlSeconds: 3600, tagTtlSeconds: { 'user-profile': 300 } })
+  cache.setTagTtl('feed', 60)
+
+  cache.set('profile', 'a', undefined, ['user-profile'])
+  cache.set('timeline', 'b', undefined, ['user-profile', 'feed'])
+  cache.set('explicit', 'c', 10, ['user-profile'])
+  cache.set('untagged', 'd')
+
+  t.is(cache.getTtl('profile'), 300)
+  t.is(cache.getTtl('timeline'), 60)
+  t.is(cache.getTtl('explicit'), 10)
+  t.is(cache.getTtl('untagged'), 3600)
+  t.deepEqual(cache.getTagTtls(), { 'user-profile': 300, feed: 60 })
+
+  cache.setTagTtl('feed', 0)
+  cache.set('timeline', 'b', undefined, ['user-profile', 'feed'])
+  t.is(cache.getTtl('timeline'), 300)
+})
+
+test('MicroserviceCache shrinks under memory pressure', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 100, clock: ClockMode.Manual })
+  for (let i = 0; i < 100; i++) {
+    cache.set(`key:${String(i).padStart(2, '0')}`, 'x'.repeat(100))
+  }
+
+  t.is(cache.reportMemoryPressure(MemoryPressure.Moderate), 25)
+  t.is(cache.reportMemoryPressure(MemoryPressure.Moderate), 0)
+  t.is(cache.memoryUsage().pressure, MemoryPressure.Moderate)
+  cache.set('fresh', 'x'.repeat(100))
+  t.is(cache.memoryUsage().liveEntries, 75)
+  t.true(cache.has('fresh'))
+
+  t.is(cache.reportMemoryPressure(MemoryPressure.Critical), 25)
+  t.is(cache.getStats().counters.pressure_evictions, 50)
+
+  cache.advanceTime(30_000)
+  t.is(cache.memoryUsage().pressure, MemoryPressure.Moderate)
+  cache.advanceTime(30_000)
+  t.is(cache.memoryUsage().pressure, MemoryPressure.None)
+
+  cache.reportMemoryPressure(MemoryPressure.Critical)
+  cache.reportMemoryPressure(MemoryPressure.None)
+  cache.set('more', 'x'.repeat(100))
+  t.is(cache.memoryUsage().liveEntries, 51)
+
+  const empty = new MicroserviceCache({ maxSize: 100 })
+  t.is(empty.reportMemoryPressure(MemoryPressure.Critical), 0)
+  for (let i = 0; i < 10; i++) {
+    empty.set(`key:${i}`, 'x')
+  }
+  t.is(empty.memoryUsage().liveEntries, 10)
+})
+
+test('MicroserviceCache evicts the coldest entries on demand', (t) => {
+  const cache = new MicroserviceCache({ evictionPolicy: EvictionPolicy.Lru })
+  for (const key of ['a', 'b', 'c', 'd']) {
+    cache.set(key, key)
+  }
+  cache.get('a')
+  cache.pin('b')
+
+  t.is(cache.evict(2), 2)
+  t.deepEqual(cache.keys().sort(), ['a', 'b'])
+  t.is(cache.evict(5), 1)
+  t.deepEqual(cache.keys(), ['b'])
+  t.is(cache.getStats().counters.evictions, 3)
+})
+
+test('MicroserviceCache expires entries on a controllable clock', (t) => {
+  const start = Date.now()
+  const cache = new MicroserviceCache({ clock: ClockMode.Manual })
+  const real = new MicroserviceCache()
+  cache.set('short', 'a', 60)
+  cache.set('long', 'b', 3600)
+  real.set('short', 'a', 60)
+
+  t.true(cache.advanceTime(59_000) >= start + 59_000)
+  t.is(cache.get('short'), 'a')
+
+  cache.advanceTime(2_000)
+  t.is(cache.pruneExpired(), 1)
+  t.is(cache.get('short'), null)
+  t.is(cache.get('long'), 'b')
+  t.is(real.get('short'), 'a')
+
+  cache.setTime(start + 7_200_000)
+  t.is(cache.get('long'), null)
+  t.throws(() => cache.setTime(start), { message: /Cannot move the clock back/ })
+  t.throws(() => real.advanceTime(1_000), { message: /not manual/ })
+})
+
+test('MicroserviceCache reports entry times in wall-clock milliseconds', (t) => {
+  const cache = new MicroserviceCache()
+  const before = Date.now()
+  cache.set('clock', 'value', 60)
+  const info = cache.getEntryInfo('clock')!
+
+  t.true(Math.abs(info.createdAt - before) < 1000)
+  t.true(Math.abs(info.expiresAt! - (before + 60_000)) < 1000)
+})
+
+test('MicroserviceCache touch keeps entries alive without reading them', (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('touch-key', 'payload', 5)
+  t.true(cache.touch('touch-key'))
+  t.is(cache.getTtl('touch-key'), 5)
+
+  t.true(cache.touch('touch-key', 60))
+  t.is(cache.getTtl('touch-key'), 60)
+  t.false(cache.touch('missing'))
+})
+
+test('MicroserviceCache delivers keyspace events', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2 })
+  const events: string[] = []
+  const orders: string[] = []
+
+  const subscription = cache.onEvent((event) => events.push(`${event.type}:${event.key}`))
+  cache.onEvent((event) => orders.push(event.key), 'order:*')
+
+  cache.set('order:1', 'created')
+  cache.set('session:1', 'active')
+  cache.set('order:2', 'created')
+  cache.delete('order:2')
+  await new Promise((resolve) => setTimeout(resolve, 50))
+
+  t.deepEqual(events, ['set:order:1', 'set:session:1', 'evict:order:1', 'set:order:2', 'delete:order:2'])
+  t.deepEqual(orders, ['order:1', 'order:1', 'order:2', 'order:2'])
+  t.true(cache.offEvent(subscription))
+  t.false(cache.offEvent(subscription))
+})
+
+test('MicroserviceCache reports evicted entries with a reason', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2 })
+  const evicted: string[] = []
+
+  cache.onEvicted((entry) => evicted.push(`${entry.reason}:${entry.key}=${entry.value}`))
+  cache.set('result:1', 'a')
+  cache.set('result:2', 'b')
+  cache.set('result:3', 'c')
+  cache.delete('result:2')
+  cache.flush()
+  await new Promise((resolve) => setTimeout(resolve, 50))
+
+  t.deepEqual(evicted, ['capacity:result:1=a', 'delete:result:2=b', 'flush:result:3=c'])
+  t.true(cache.offEvicted())
+  t.false(cache.offEvicted())
+})
+
+test('MicroserviceCache streams ordered change records', async (t) => {
+  const cache = new MicroserviceCache()
+  const stream = cache.createChangeStream()
+
+  cache.set('metric:1', '10')
+  cache.increment('metric:2')
+  cache.delete('metric:1')
+
+  const changes = []
+  for await (const change of stream) {
+    changes.push(change)
+    if (changes.length === 3) {
+      break
+    }
+  }
+
+  t.deepEqual(
+    changes.map((change) => [change.sequence, change.op, change.key, change.value]),
+    [
+      [1, 'set', 'metric:1', '10'],
+      [2, 'set', 'metric:2', '1'],
+      [3, 'delete', 'metric:1', undefined],
+    ],
+  )
+})
+
+test('MicroserviceCache drops change records for lagging streams', async (t) => {
+  const cache = new MicroserviceCache()
+  const stream = cache.createChangeStream(2)
+
+  cache.set('metric:1', '1')
+  cache.set('metric:2', '2')
+  cache.set('metric:3', '3')
+  t.is(cache.getStats().counters.change_stream_dropped, 1)
+
+  const sequences = []
+  for await (const change of stream) {
+    sequences.push(change.sequence)
+    if (sequences.length === 2) {
+      cache.set('metric:4', '4')
+    }
+    if (sequences.length === 3) {
+      break
+    }
+  }
+  t.deepEqual(sequences, [1, 2, 4])
+})
+
+test('MicroserviceCache background sweeper removes expired entries', async (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('short-lived', 'value', 1, ['ephemeral'])
+  cache.set('long-lived', 'value')
+
+  t.true(cache.startSweeper(100))
+  t.false(cache.startSweeper(100))
+
+  await new Promise((resolve) => setTimeout(resolve, 2500))
+
+  const stats = cache.getStats()
+  t.is(stats.entries, 1)
+  t.is(stats.counters.swept_entries, 1)
+  t.deepEqual(cache.getKeysByTag('ephemeral'), [])
+
+  t.true(cache.stopSweeper())
+  t.false(cache.stopSweeper())
+})
+
+test('MicroserviceCache restores entries from a snapshot', (t) => {
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}.snapshot`)
+  const source = new MicroserviceCache()
+
+  source.set('user:1', 'Ada', 60, ['users'])
+  source.setJson('config', { retries: 3 })
+  t.is(source.saveSnapshot(path), 2)
+
+  const restored = new MicroserviceCache()
+  t.is(restored.loadSnapshot(path), 2)
+  t.is(restored.get('user:1'), 'Ada')
+  t.deepEqual(restored.getJson('config'), { retries: 3 })
+  t.deepEqual(restored.getKeysByTag('users'), ['user:1'])
+  t.true(restored.getTtl('user:1')! > 0)
+
+  t.throws(() => restored.loadSnapshot(join(tmpdir(), 'missing.snapshot')))
+})
+
+test('MicroserviceCache autosaves snapshots periodically', async (t) => {
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}.autosave`)
+  const cache = new MicroserviceCache()
+
+  cache.set('session', 'abc')
+  t.true(cache.enableAutosave(1, path))
+  t.false(cache.enableAutosave(1, path))
+
+  await new Promise((resolve) => setTimeout(resolve, 1500))
+  t.true(cache.disableAutosave())
+
+  const { counters } = cache.getStats()
+  t.true(counters.snapshots >= 1)
+  t.true(counters.last_snapshot_bytes > 0)
+  t.is(counters.last_snapshot_entries, 1)
+
+  const restored = new MicroserviceCache()
+  t.is(restored.loadSnapshot(path), 1)
+})
+
+test('MicroserviceCache replays the append log on startup', (t) => {
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}.aof`)
+  const cache = new MicroserviceCache()
+
+  t.is(cache.enableAppendLog(path), 0)
+  cache.set('idempotency:1', 'processed', 300)
+  cache.set('idempotency:2', 'processed')
+  cache.delete('idempotency:2')
+  cache.increment('dedupe-window', 2)
+  t.true(cache.disableAppendLog())
+
+  const restarted = new MicroserviceCache()
+  t.true(restarted.enableAppendLog(path) > 0)
+  t.deepEqual(restarted.keys().sort(), ['dedupe-window', 'idempotency:1'])
+  t.is(restarted.get('dedupe-window'), '2')
+  t.true(restarted.getTtl('idempotency:1')! > 0)
+  t.is(restarted.compactAppendLog(), 2)
+  t.throws(() => restarted.enableAppendLog(path))
+  t.true(restarted.disableAppendLog())
+
+  writeFileSync(path, Buffer.from([0, 0, 0, 0, 9, 9, 9]), { flag: 'a' })
+  const truncated = new MicroserviceCache()
+  truncated.enableAppendLog(path)
+  t.is(truncated.get('dedupe-window'), '2')
+  t.is(truncated.getStats().counters.append_log_truncated_bytes, 7)
+  t.true(truncated.disableAppendLog())
+})
+
+test('MicroserviceCache replays deletes logged under another key hash', (t) => {
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}-rehash.aof`)
+  const cache = new MicroserviceCache({ keyHash: KeyHash.Xxh3 })
+  cache.enableAppendLog(path)
+  cache.set('order:1', 'open')
+  cache.set('order:2', 'open')
+  cache.delete('order:1')
+  cache.disableAppendLog()
+
+  const restarted = new MicroserviceCache({ keyHash: KeyHash.Sha256 })
+  t.is(restarted.enableAppendLog(path), 2)
+  t.deepEqual(restarted.keys(), ['order:2'])
+  restarted.disableAppendLog()
+})
+
+test('MicroserviceCache rejects a disk tier written with another key hash', async (t) => {
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}-hasher.sled`)
+  const cache = new MicroserviceCache({ keyHash: KeyHash.Xxh64 })
+  t.true(cache.enableDiskTier(path))
+  t.true(cache.disableDiskTier())
+  await new Promise((resolve) => setTimeout(resolve, 1000))
+
+  const other = new MicroserviceCache()
+  t.throws(() => other.enableDiskTier(path), { message: /key hash 'xxh64'/ })
+  const same = new MicroserviceCache({ keyHash: KeyHash.Xxh64 })
+  t.true(same.enableDiskTier(path))
+  same.disableDiskTier()
+})
+
+test('MicroserviceCache spills evicted entries to the disk tier', async (t) => {
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}.sled`)
+  const cache = new MicroserviceCache({ maxSize: 2 })
+
+  t.true(cache.enableDiskTier(path))
+  t.false(cache.enableDiskTier(path))
+  cache.set('invoice:1', 'paid', undefined, ['invoices'])
+  cache.set('invoice:2', 'pending', undefined, ['invoices'])
+  cache.set('invoice:3', 'void', undefined, ['invoices'])
+
+  t.is(cache.keys().length, 3)
+  t.true(['invoice:1', 'invoice:2', 'invoice:3'].every((key) => cache.has(key)))
+  t.is(cache.peek('invoice:1'), 'paid')
+  t.is(cache.getTtl('invoice:1'), -1)
+  t.is(cache.getEntryInfo('invoice:1')?.tags[0], 'invoices')
+  const streamed = []
+  for await (const record of cache.entries('invoice:*', 1)) {
+    streamed.push(record.key)
+  }
+  t.deepEqual(streamed.sort(), ['invoice:1', 'invoice:2', 'invoice:3'])
+  t.falsy(cache.getStats().counters.disk_promotions)
+  t.is(cache.get('invoice:1'), 'paid')
+  t.is(cache.getKeysByTag('invoices').length, 3)
+
+  const { counters } = cache.getStats()
+  t.true(counters.disk_spills >= 2)
+  t.true(counters.disk_promotions >= 1)
+
+  t.is(cache.deleteByTag('invoices'), 3)
+  t.is(cache.keys().length, 0)
+  t.true(cache.disableDiskTier())
+})
+
+test('MicroserviceCache reports unreachable Redis tiers', (t) => {
+  const cache = new MicroserviceCache()
+
+  t.throws(() => cache.enableRedisTier('redis://127.0.0.1:1'), {
+    message: /Failed to connect to Redis/,
+  })
+  t.false(cache.disableRedisTier())
+})
+
+test('MicroserviceCache round-trips typed values through the Redis tier', async (t) => {
+  const { server, url } = await startFakeRedis()
+  const encryptionKey = randomBytes(32)
+
+  const writer = new MicroserviceCache({ encryptionKey })
+  writer.enableRedisTier(url)
+  writer.set('greeting', 'hello')
+  writer.setJson('profile', { id: 7 })
+  writer.setBuffer('blob', Buffer.from([0, 255, 1]))
+  await new Promise((resolve) => setTimeout(resolve, 50))
+  t.is(writer.getStats().counters.remote_writes, 3)
+
+  const reader = new MicroserviceCache({ encryptionKey })
+  reader.enableRedisTier(url)
+  t.is(await reader.getOrSet('greeting', async () => 'loaded'), 'hello')
+  t.is(reader.getJson('profile'), null)
+  t.is(reader.getBuffer('blob'), null)
+  await new Promise((resolve) => setTimeout(resolve, 100))
+  t.deepEqual(reader.getJson('profile'), { id: 7 })
+  t.deepEqual(reader.getBuffer('blob'), Buffer.from([0, 255, 1]))
+
+  const stranger = new MicroserviceCache({ encryptionKey: randomBytes(32) })
+  stranger.enableRedisTier(url)
+  t.is(await stranger.getOrSet('greeting', async () => 'loaded'), 'loaded')
+  t.is(stranger.getStats().counters.corrupt_values, 1)
+
+  writer.delete('greeting')
+  t.is(await writer.getOrSet('greeting', async () => 'reloaded'), 'reloaded')
+
+  await server.terminate()
+})
+
+test('MicroserviceCache reports unreachable invalidation buses', (t) => {
+  const cache = new MicroserviceCache()
+
+  t.throws(() => cache.enableInvalidation('redis://127.0.0.1:1'), {
+    message: /Failed to subscribe to invalidations/,
+  })
+  t.throws(() => cache.enableInvalidation('nats://127.0.0.1:1'), {
+    message: /Failed to subscribe to invalidations/,
+  })
+  t.throws(() => cache.enableInvalidation('kafka://127.0.0.1:9092'), {
+    message: /unsupported invalidation transport/,
+  })
+  t.false(cache.disableInvalidation())
+  t.false(cache.delete('missing'))
+})
+
+test('MicroserviceCache resubscribes to invalidations after losing Redis', async (t) => {
+  const { server, url } = await startFakeRedis()
+  const publisher = new MicroserviceCache()
+  const subscriber = new MicroserviceCache()
+  t.true(publisher.enableInvalidation(url))
+  t.true(subscriber.enableInvalidation(url))
+
+  server.postMessage('drop')
+  await new Promise((resolve) => setTimeout(resolve, 1000))
+  const { counters } = subscriber.getStats()
+  t.true(counters.invalidation_failures >= 1)
+  t.true(counters.invalidation_reconnects >= 1)
+
+  subscriber.set('session', 'stale')
+  publisher.delete('session')
+  await new Promise((resolve) => setTimeout(resolve, 300))
+  t.is(subscriber.get('session'), null)
+
+  subscriber.disableInvalidation()
+  publisher.disableInvalidation()
+  await server.terminate()
+})
+
+test('MicroserviceCache exposes Prometheus metrics', (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('metric-key', 'value')
+  cache.get('metric-key')
+  cache.get('missing-key')
+
+  const metrics = cache.getPrometheusMetrics()
+  t.true(metrics.includes('# TYPE microservice_cache_hits_total counter'))
+  t.true(metrics.includes('microservice_cache_hits_total 1'))
+  t.true(metrics.includes('microservice_cache_hit_ratio 0.5'))
+  t.true(metrics.includes('microservice_cache_operation_duration_seconds_count{operation="get"} 2'))
+})
+
+test('MicroserviceCache reports latency percentiles', async (t) => {
+  const cache = new MicroserviceCache()
+
+  for (let i = 0; i < 100; i++) {
+    cache.set(`latency-${i}`, 'value')
+    cache.get(`latency-${i}`)
+  }
+  await cache.getOrSet('latency-loaded', () => new Promise((resolve) => setTimeout(() => resolve('value'), 20)))
+
+  const { latency } = cache.getStats()
+  t.is(latency.get.count, 101)
+  t.is(latency.set.count, 101)
+  t.true(latency.get.p50Us <= latency.get.p99Us)
+  t.true(latency.get.p99Us <= latency.get.maxUs)
+  t.is(latency.loader.count, 1)
+  t.true(latency.loader.p50Us >= 15000)
+})
+
+test('MicroserviceCache surfaces failure reasons as error codes', (t) => {
+  const cache = new MicroserviceCache({ maxBytes: 64 })
+  t.throws(() => cache.set('report', 'x'.repeat(100)), {
+    code: 'ERR_CAPACITY_EXCEEDED',
+    message: 'Entry of 106 bytes exceeds maxBytes of 64',
+  })
+
+  cache.set('broken', '{not json')
+  t.throws(() => cache.getJson('broken'), { code: 'ERR_SERIALIZATION_FAILED' })
+  t.throws(() => cache.getJsonPath('broken', '$.id'), { code: 'ERR_SERIALIZATION_FAILED' })
+
+  cache.acquireLock('job', 1000)
+  t.throws(() => cache.acquireLock('job', 1000), { code: 'ERR_LOCK_HELD' })
+})
+
+test('MicroserviceCache rejects oversized values and keys with error codes', (t) => {
+  const cache = new MicroserviceCache({ maxValueBytes: 10, maxKeyLength: 8 })
+  t.throws(() => cache.set('report', 'x'.repeat(11)), {
+    code: 'ERR_VALUE_TOO_LARGE',
+    message: "Value of 11 bytes for key 'report' exceeds maxValueBytes of 10",
+  })
+  t.throws(() => cache.set('a-very-long-key', 'x'), {
+    code: 'ERR_KEY_TOO_LONG',
+    message: 'Key of 15 bytes exceeds maxKeyLength of 8',
+  })
+  t.throws(() => cache.setMany([{ key: 'bulk', value: 'x'.repeat(20) }]), { code: 'ERR_VALUE_TOO_LARGE' })
+  t.false(cache.has('report'))
+
+  t.is(cache.set('report', 'x'.repeat(10)), 1)
+  cache.configure({ maxValueBytes: 0 })
+  t.is(cache.set('report', 'x'.repeat(100)), 2)
+})
+
+test('MicroserviceCache reports the memory usage of a key', (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('small', 'x')
+  cache.set('large', 'x'.repeat(10000), null, ['report'])
+
+  const small = cache.memoryUsageOf('small')!
+  const large = cache.memoryUsageOf('large')!
+  t.true(small > 'small'.length + 1)
+  t.is(large - small, 'large'.length - 'small'.length + 9999 + 'report'.length)
+  t.is(cache.memoryUsageOf('missing'), null)
+  t.is(small + large, cache.memoryUsage().totalBytes)
+})
+
+test('MicroserviceCache reports live entries and memory usage', async (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('short', 'gone', 1)
+  cache.set('long', 'kept')
+  await new Promise((resolve) => setTimeout(resolve, 1100))
+
+  const usage = cache.memoryUsage()
+  t.is(usage.liveEntries, 1)
+  t.is(usage.expiredEntries, 1)
+  t.is(usage.dataBytes, cache.getStats().memory.totalBytes)
+  t.true(usage.metadataBytes > 0)
+  t.is(usage.totalBytes, usage.dataBytes + usage.metadataBytes)
+
+  const stats = cache.getStats()
+  t.is(stats.entries, 2)
+  t.is(stats.liveEntries, 1)
+})
+
+test('MicroserviceCache accepts a shard count and initial capacity', (t) => {
+  const cache = new MicroserviceCache({ shardCount: 64, initialCapacity: 10000 })
+  for (let i = 0; i < 1000; i++) {
+    cache.set(`user:${i}`, `value-${i}`)
+  }
+  t.is(cache.get('user:999'), 'value-999')
+  t.is(cache.getStats().entries, 1000)
+
+  t.throws(() => new MicroserviceCache({ shardCount: 3 }), {
+    message: 'shardCount must be a power of two greater than 1, got 3',
+  })
+})
+
+test('MicroserviceCache serves large values from shared storage', (t) => {
+  const cache = new MicroserviceCache()
+  const value = 'x'.repeat(1 << 20)
+  cache.set('report', value)
+  t.is(cache.get('report'), value)
+  t.is(cache.get('report'), value)
+
+  const ns = cache.namespace('reports')
+  ns.set('weekly', value)
+  t.is(ns.get('weekly'), value)
+  t.is(cache.getBuffer('report')?.length, 1 << 20)
+
+  cache.setBuffer('blob', Buffer.from([1, 2, 3]))
+  const first = cache.getBuffer('blob')!
+  first[0] = 9
+  t.deepEqual(Array.from(cache.getBuffer('blob') ?? []), [1, 2, 3])
+  t.not(cache.getBuffer('blob')?.buffer, first.buffer)
+})
+
+test('MicroserviceCache indexes keys with the configured hash', (t) => {
+  for (const keyHash of [KeyHash.Fnv, KeyHash.Xxh64, KeyHash.Xxh3, KeyHash.Sha256]) {
+    const cache = new MicroserviceCache({ keyHash })
+    for (let i = 0; i < 150; i++) {
+      cache.set(`user:${i}`, `value-${i}`)
+    }
+    t.is(cache.get('user:42'), 'value-42')
+
+    let cursor = '0'
+    let scanned = 0
+    do {
+      const page = cache.scan(cursor, null, 100)
+      scanned += page.keys.length
+      cursor = page.cursor
+    } while (cursor !== '0')
+    t.is(scanned, 150)
+  }
+})
+
+test('MicroserviceCache is reconfigured at runtime', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 10 })
+  for (let i = 0; i < 10; i++) {
+    cache.set(`user:${i}`, `value-${i}`)
+  }
+
+  t.is(cache.configure({ maxSize: 4 }), 6)
+  t.is(cache.keys().length, 4)
+  t.is(cache.getStats().maxSize, 4)
+  cache.set('user:10', 'value-10')
+  t.is(cache.keys().length, 4)
+
+  cache.configure({ defaultTtlSeconds: 100 })
+  cache.set('session:1', 'Ana')
+  t.is(cache.getTtl('session:1'), 100)
+  cache.configure({ defaultTtlSeconds: 0 })
+  cache.set('session:2', 'Luis')
+  t.is(cache.getTtl('session:2'), -1)
+})
+
+test('MicroserviceCache reports recent windows and resets stats', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2 })
+
+  cache.set('window-1', 'value')
+  cache.set('window-2', 'value')
+  cache.set('window-3', 'value')
+  cache.get('window-3')
+  cache.get('window-missing')
+
+  const { windows } = cache.getStats()
+  t.is(windows.oneMinute.hits, 1)
+  t.is(windows.oneMinute.hitRate, 0.5)
+  t.is(windows.fiveMinutes.sets, 3)
+  t.is(windows.fifteenMinutes.evictions, 1)
+  t.true(windows.oneMinute.operationsPerSecond > 0)
+
+  cache.resetStats()
+  const stats = cache.getStats()
+  t.is(stats.hits, 0)
+  t.is(stats.latency.get.count, 0)
+  t.is(stats.windows.oneMinute.sets, 0)
+  t.deepEqual(stats.counters, {})
+  t.is(stats.entries, 2)
+})
+
+test('MicroserviceCache namespaces have their own limits and stats', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 100 })
+  cache.set('users:legacy', 'value')
+
+  const users = cache.namespace('users', 2)
+  const sessions = cache.namespace('sessions', null, 1)
+  t.is(users.name, 'users')
+  t.is(users.getStats().entries, 1)
+
+  users.set('a', '1')
+  users.set('b', '2')
+  users.get('a')
+  users.set('c', '3')
+  sessions.set('a', 'session')
+
+  t.deepEqual(users.keys().sort(), ['a', 'c'])
+  t.is(cache.get('users:a'), '1')
+  t.is(sessions.get('a'), 'session')
+
+  const stats = users.getStats()
+  t.is(stats.entries, 2)
+  t.is(stats.maxSize, 2)
+  t.is(stats.evictions, 2)
+  t.is(stats.sets, 3)
+
+  cache.set('users:d', '4')
+  t.deepEqual(users.keys().sort(), ['a', 'd'])
+
+  t.true(users.delete('a'))
+  t.false(users.has('a'))
+  t.is(users.flush(), 1)
+  t.deepEqual(cache.keys(), ['sessions:a'])
+
+  await new Promise((resolve) => setTimeout(resolve, 1100))
+  t.is(sessions.get('a'), null)
+  t.throws(() => cache.namespace('bad:name'))
+})
+
+test('MicroserviceCache invalidates a namespace by bumping its generation', async (t) => {
+  const cache = new MicroserviceCache()
+  for (let i = 0; i < 100; i++) {
+    cache.set(`catalog:${i}`, `v${i}`)
+  }
+  cache.set('other:1', 'kept')
+
+  t.is(cache.bumpGeneration('catalog'), 1)
+  t.is(cache.get('catalog:1'), null)
+  t.false(cache.has('catalog:2'))
+  t.deepEqual(cache.keys(), ['other:1'])
+
+  const catalog = cache.namespace('catalog')
+  catalog.set('1', 'fresh')
+  t.is(cache.get('catalog:1'), 'fresh')
+  t.deepEqual(catalog.keys(), ['1'])
+
+  t.true(cache.startSweeper(10))
+  await new Promise((resolve) => setTimeout(resolve, 100))
+  t.true(cache.stopSweeper())
+  t.is(cache.getStats().entries, 2)
+  t.is(cache.getStats().counters.stale_generation_entries, 100)
+
+  t.is(catalog.bumpGeneration(), 2)
+  t.is(catalog.get('1'), null)
+  t.throws(() => cache.bumpGeneration('bad:name'))
+})
+
+test('MicroserviceCache keeps namespace generations across restarts', (t) => {
+  const base = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}-generations`)
+  const cache = new MicroserviceCache()
+  cache.enableAppendLog(`${base}.log`)
+  cache.set('catalog:1', 'old')
+  t.is(cache.bumpGeneration('catalog'), 1)
+  cache.set('catalog:2', 'new')
+  cache.saveSnapshot(`${base}.snapshot`)
+  cache.disableAppendLog()
+
+  const replayed = new MicroserviceCache()
+  t.is(replayed.enableAppendLog(`${base}.log`), 2)
+  t.is(replayed.get('catalog:1'), null)
+  t.is(replayed.get('catalog:2'), 'new')
+  t.is(replayed.bumpGeneration('catalog'), 2)
+  replayed.disableAppendLog()
+
+  const restored = new MicroserviceCache()
+  t.is(restored.loadSnapshot(`${base}.snapshot`), 1)
+  t.is(restored.get('catalog:1'), null)
+  t.is(restored.bumpGeneration('catalog'), 2)
+})
+
+test('MicroserviceCache enforces per-tenant quotas', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 100 })
+  cache.setTenantQuota('noisy', 3, 200)
+
+  cache.setForTenant('quiet', 'quiet-1', 'value')
+  for (let i = 0; i < 20; i++) {
+    cache.setForTenant('noisy', `noisy-${i}`, 'x'.repeat(40))
+  }
+
+  t.true(cache.has('quiet-1'))
+  t.deepEqual(cache.keys().sort(), ['noisy-17', 'noisy-18', 'noisy-19', 'quiet-1'])
+  t.throws(() => cache.setForTenant('noisy', 'too-big', 'x'.repeat(300)), { message: /quota of 200 bytes/ })
+
+  const { noisy, quiet } = cache.getTenantStats()
+  t.is(noisy.entries, 3)
+  t.is(noisy.maxEntries, 3)
+  t.is(noisy.evictions, 17)
+  t.true(noisy.bytes <= 200)
+  t.is(quiet.entries, 1)
+  t.is(quiet.maxBytes, undefined)
+})
+
+test('MicroserviceCache breaks stats down by tag and namespace', (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('users:1', 'alice', null, ['profile'])
+  cache.set('users:2', 'bob')
+  cache.set('orders:1', 'order', null, ['profile', 'billing'])
+  cache.set('plain', 'value')
+  cache.get('users:1')
+  cache.get('users:404')
+  cache.get('orders:1')
+
+  const namespaces = cache.getNamespaceStats()
+  t.deepEqual(Object.keys(namespaces).sort(), ['orders', 'users'])
+  t.is(namespaces.users.entries, 2)
+  t.is(namespaces.users.hitRate, 0.5)
+  t.true(namespaces.users.bytes > 0)
+
+  const tags = cache.getTagStats()
+  t.is(tags.profile.hits, 2)
+  t.is(tags.profile.entries, 2)
+  t.is(tags.billing.entries, 1)
+})
+
+test('MicroserviceCache folds untracked namespaces into one bucket', (t) => {
+  const cache = new MicroserviceCache()
+
+  for (let i = 0; i < 1100; i++) {
+    cache.get(`missing${i}:key`)
+  }
+
+  const namespaces = cache.getNamespaceStats()
+  t.is(Object.keys(namespaces).length, 1025)
+  t.is(namespaces['(other)'].misses, 76)
+})
+
+test('MicroserviceCache reports hot keys', (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('hot', 'value')
+  for (let i = 0; i < 1000; i++) {
+    cache.get('hot')
+    if (i % 4 === 0) {
+      cache.get('warm')
+    }
+    cache.get(`cold-${i}`)
+  }
+
+  const [first, second] = cache.getHotKeys(2)
+  t.is(first.key, 'hot')
+  t.true(first.count >= 1001)
+  t.true(first.share > 0.4)
+  t.is(second.key, 'warm')
+  t.is(cache.getHotKeys(500).length, 128)
+
+  cache.resetStats()
+  t.deepEqual(cache.getHotKeys(), [])
+})
+
+test('MicroserviceCache pushes counters to StatsD', async (t) => {
+  const socket = createSocket('udp4')
+  const packet = new Promise<string>((resolve) => socket.once('message', (message) => resolve(message.toString())))
+  await new Promise<void>((resolve) => socket.bind(0, '127.0.0.1', resolve))
+
+  const cache = new MicroserviceCache()
+  cache.set('statsd-key', 'value')
+  t.true(cache.startStatsdEmitter(`127.0.0.1:${socket.address().port}`, 20, 'svc.', ['env:test']))
+  t.false(cache.startStatsdEmitter('127.0.0.1:8125'))
+
+  t.true((await packet).split('\n').includes('svc.sets:1|c|#env:test'))
+  t.true(cache.stopStatsdEmitter())
+  socket.close()
+})
+
+test('MicroserviceCache requires the otel feature for OpenTelemetry', (t) => {
+  const cache = new MicroserviceCache()
+
+  const error = t.throws(() => cache.enableOpenTelemetry('http://127.0.0.1:4318'))
+  t.true(error?.message.includes('otel'))
+  t.false(cache.disableOpenTelemetry())
+})
+
+test('MicroserviceCache runs operations under a W3C trace context', (t) => {
+  const cache = new MicroserviceCache()
+  const traceparent = '00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01'
+
+  t.is(
+    cache.withTraceContext(traceparent, () => {
+      cache.set('user:1', 'alice')
+      return cache.get('user:1')
+    }),
+    'alice',
+  )
+  const untraced = '00-00000000000000000000000000000000-00f067aa0ba902b7-01'
+  t.throws(() => cache.withTraceContext(untraced, () => 1), { message: /Invalid traceparent/ })
+
+  const failing = () => {
+    throw new Error('handler failed')
+  }
+  t.throws(() => cache.withTraceContext(traceparent, failing), { message: 'handler failed' })
+})
+
+test('MicroserviceCache scans keys with a cursor', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 10000 })
+  for (let i = 0; i < 1000; i++) {
+    cache.set(`${i % 2 ? 'user' : 'order'}:${i}`, 'value')
+  }
+
+  const seen: string[] = []
+  let cursor = '0'
+  let pages = 0
+  do {
+    const page = cache.scan(cursor, null, 150)
+    t.true(page.keys.length <= 150)
+    seen.push(...page.keys)
+    cursor = page.cursor
+    pages++
+  } while (cursor !== '0')
+
+  t.is(pages, 7)
+  t.is(new Set(seen).size, 1000)
+
+  const users = cache.scan('0', 'user:*', 10000)
+  t.is(users.keys.length, 500)
+  t.is(users.cursor, '0')
+  t.throws(() => cache.scan('not-a-cursor'), { message: /Invalid scan cursor/ })
+})
+
+test('MicroserviceCache ranks members of sorted sets', (t) => {
+  const cache = new MicroserviceCache()
+  t.true(cache.zadd('leaderboard', 'ana', 10, 60))
+  t.true(cache.zadd('leaderboard', 'bob', 30))
+  t.true(cache.zadd('leaderboard', 'eva', 20))
+  t.false(cache.zadd('leaderboard', 'ana', 5))
+  t.is(cache.zincrby('leaderboard', 'ana', 40), 45)
+  t.is(cache.zincrby('leaderboard', 'leo', 1), 1)
+
+  t.deepEqual(
+    cache.zrange('leaderboard', 0, -1).map((entry) => entry.member),
+    ['leo', 'eva', 'bob', 'ana'],
+  )
+  t.deepEqual(cache.zrange('leaderboard', 0, 1, true), [
+    { member: 'ana', score: 45 },
+    { member: 'bob', score: 30 },
+  ])
+  t.is(cache.zrank('leaderboard', 'ana'), 3)
+  t.is(cache.zrank('leaderboard', 'ana', true), 0)
+  t.is(cache.zrank('leaderboard', 'zoe'), null)
+  t.deepEqual(cache.getJson('leaderboard'), [
+    ['leo', 1],
+    ['eva', 20],
+    ['bob', 30],
+    ['ana', 45],
+  ])
+
+  t.throws(() => cache.zadd('leaderboard', 'zoe', NaN), { message: /Invalid score/ })
+  cache.rpush('queue', 'a')
+  t.throws(() => cache.zrange('queue', 0, -1), { message: /not a sorted set/ })
+})
+
+test('MicroserviceCache combines sets across keys', (t) => {
+  const cache = new MicroserviceCache()
+  t.is(cache.sadd('perms:1', ['read', 'write', 'read'], 60), 2)
+  t.is(cache.sadd('perms:1', 'read'), 0)
+  t.is(cache.sadd('perms:2', ['read', 'admin']), 2)
+
+  t.deepEqual(cache.smembers('perms:1'), ['read', 'write'])
+  t.true(cache.sismember('perms:1', 'write'))
+  t.false(cache.sismember('perms:2', 'write'))
+  t.deepEqual(cache.sinter(['perms:1', 'perms:2']), ['read'])
+  t.deepEqual(cache.sunion(['perms:1', 'perms:2', 'perms:3']), ['admin', 'read', 'write'])
+  t.deepEqual(cache.sinter(['perms:1', 'perms:3']), [])
+
+  t.is(cache.srem('perms:2', ['admin', 'unknown']), 1)
+  t.is(cache.srem('perms:2', 'read'), 1)
+  t.false(cache.has('perms:2'))
+
+  cache.hset('profile', 'name', 'Ana')
+  t.throws(() => cache.smembers('profile'), { message: /not a set/ })
+  cache.setJson('flags', { read: true })
+  t.throws(() => cache.sadd('flags', 'write'), { message: /not a set/ })
+  t.deepEqual(cache.getJson('perms:1'), { read: true, write: true })
+})
+
+test('MicroserviceCache keeps bounded lists', (t) => {
+  const cache = new MicroserviceCache()
+  t.is(cache.rpush('queue', 'a', 60), 1)
+  t.is(cache.rpush('queue', ['b', 'c']), 3)
+  t.is(cache.lpush('queue', ['x', 'y']), 5)
+  t.deepEqual(cache.lrange('queue', 0, -1), ['y', 'x', 'a', 'b', 'c'])
+  t.deepEqual(cache.lrange('queue', -2, -1), ['b', 'c'])
+  t.deepEqual(cache.lrange('queue', 5, 9), [])
+
+  t.is(cache.lpop('queue'), 'y')
+  t.is(cache.rpop('queue'), 'c')
+  t.true(cache.getTtl('queue')! > 0)
+
+  for (let i = 0; i < 10; i++) {
+    cache.rpush('recent', `event:${i}`, undefined, 3)
+  }
+  t.deepEqual(cache.lrange('recent', 0, -1), ['event:7', 'event:8', 'event:9'])
+
+  cache.rpush('single', 'only')
+  t.is(cache.rpop('single'), 'only')
+  t.false(cache.has('single'))
+  t.is(cache.rpop('single'), null)
+
+  cache.set('plain', 'value')
+  t.throws(() => cache.rpush('plain', 'a'), { message: /not a list/ })
+  cache.setJson('array', ['a', 'b'])
+  t.throws(() => cache.lpop('array'), { message: /not a list/ })
+  t.is(cache.get('queue'), '["x","a","b"]')
+})
+
+test('MicroserviceCache updates hash fields in place', (t) => {
+  const cache = new MicroserviceCache()
+  t.true(cache.hset('user:1', 'name', 'Ana', 60))
+  t.false(cache.hset('user:1', 'name', 'Eva'))
+  t.true(cache.hset('user:1', 'plan', 'pro'))
+
+  t.is(cache.hget('user:1', 'name'), 'Eva')
+  t.is(cache.hget('user:1', 'email'), null)
+  t.is(cache.hget('user:2', 'name'), null)
+  t.deepEqual(cache.hgetAll('user:1'), { name: 'Eva', plan: 'pro' })
+  t.deepEqual(cache.getJson('user:1'), { name: 'Eva', plan: 'pro' })
+  t.true(cache.getTtl('user:1')! > 0)
+
+  t.true(cache.hdel('user:1', 'name'))
+  t.false(cache.hdel('user:1', 'name'))
+  t.true(cache.hdel('user:1', 'plan'))
+  t.false(cache.has('user:1'))
+
+  cache.set('plain', 'value')
+  t.throws(() => cache.hset('plain', 'name', 'Ana'), { message: /not a hash/ })
+  cache.setJson('document', { name: 'Ana' })
+  t.throws(() => cache.hget('document', 'name'), { message: /not a hash/ })
+})
+
+test('MicroserviceCache keeps compressed and encrypted hashes', (t) => {
+  const compressed = new MicroserviceCache({ compression: Compression.Gzip, compressionThresholdBytes: 64 })
+  const encrypted = new MicroserviceCache({ encryptionKey: randomBytes(32) })
+  for (const cache of [compressed, encrypted]) {
+    for (let i = 0; i < 20; i++) {
+      cache.hset('wide', `field:${i}`, 'x'.repeat(10))
+    }
+    t.is(cache.hget('wide', 'field:7'), 'x'.repeat(10))
+    t.is(Object.keys(cache.hgetAll('wide')!).length, 20)
+    t.is(cache.getStats().memory.compressedValues > 0, cache === compressed)
+    for (let i = 0; i < 20; i++) {
+      t.true(cache.hdel('wide', `field:${i}`))
+    }
+    t.false(cache.has('wide'))
+  }
+})
+
+test('MicroserviceCache accepts an options object', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2, hashKeys: false, sweeperIntervalMs: 50 })
+  t.false(cache.startSweeper())
+  t.true(cache.stopSweeper())
+
+  cache.set('user:1', 'Ana')
+  cache.set('user:2', 'Luis')
+  cache.set('user:3', 'Eva')
+  t.deepEqual(cache.keys().sort(), ['user:2', 'user:3'])
+  t.deepEqual(cache.scan('0').keys.sort(), ['user:2', 'user:3'])
+})
+
+test('MicroserviceCache.getOrCreate returns one cache per name', (t) => {
+  const name = `registry-${process.pid}-${Date.now()}`
+  const first = MicroserviceCache.getOrCreate(name, { maxSize: 2 })
+  first.set('user:1', 'Ana')
+
+  const second = MicroserviceCache.getOrCreate(name, { maxSize: 100 })
+  t.is(second.get('user:1'), 'Ana')
+  second.set('user:2', 'Luis')
+  second.set('user:3', 'Eva')
+  t.is(first.keys().length, 2)
+
+  t.is(MicroserviceCache.getOrCreate(`${name}-other`).get('user:1'), null)
+  t.true(first.unshare(name))
+  t.is(MicroserviceCache.getOrCreate(name).get('user:2'), null)
+})
+
+test('MicroserviceCache is shared across worker threads', async (t) => {
+  const cache = new MicroserviceCache()
+  const token = cache.share()
+  cache.set('config:region', 'eu-west-1')
+
+  const worker = new Worker(
+    `
+      const { parentPort, workerData } = require('node:worker_threads')
+      const { MicroserviceCache } = require(workerData.binding)
+      const cache = MicroserviceCache.attach(workerData.token)
+      cache.set('config:workers', '4')
+      parentPort.postMessage(cache.get('config:region'))
+    `,
+    { eval: true, workerData: { token, binding: fileURLToPath(new URL('../index.js', import.meta.url)) } },
+  )
+  const seen = await new Promise((resolve, reject) => {
+    worker.once('message', resolve)
+    worker.once('error', reject)
+  })
+  await worker.terminate()
+
+  t.is(seen, 'eu-west-1')
+  t.is(cache.get('config:workers'), '4')
+  t.is(MicroserviceCache.attach(token).get('config:workers'), '4')
+  t.throws(() => new MicroserviceCache().share(token), { message: /already shared/ })
+
+  t.true(cache.unshare(token))
+  t.false(cache.unshare(token))
+  t.throws(() => MicroserviceCache.attach(token), { message: /No shared cache/ })
+})
+
+test('CacheChain reads through L1, L2 and the loader', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 10 })
+  const path = join(tmpdir(), `microservice-cache-chain-${process.pid}-${Date.now()}.sled`)
+  let loads = 0
+  const chain = cache.createChain({ diskPath: path, promoteAfterHits: 2 }, async (key) => {
+    loads++
+    return `loaded:${key}`
+  })
+
+  t.is(await chain.get('user:1'), 'loaded:user:1')
+  t.is(await chain.get('user:1'), 'loaded:user:1')
+  t.is(loads, 1)
+
+  chain.set('user:2', 'Ana')
+  cache.delete('user:2')
+  t.is(await chain.get('user:2'), 'Ana')
+  t.false(cache.has('user:2'))
+  t.is(await chain.get('user:2'), 'Ana')
+  t.true(cache.has('user:2'))
+
+  t.true(chain.delete('user:2'))
+  t.is(await chain.get('user:2'), 'loaded:user:2')
+
+  const stats = chain.getStats()
+  t.is(stats.l1.hits, 1)
+  t.is(stats.l2.hits, 2)
+  t.is(stats.loader.hits, 2)
+  t.is(stats.promotions, 1)
+  t.throws(() => cache.createChain({ diskPath: path, redisUrl: 'redis://localhost' }), {
+    message: /either redisUrl or diskPath/,
+  })
+})
+
+test('CacheChain demotes L1 evictions to L2', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 1 })
+  const path = join(tmpdir(), `microservice-cache-demotion-${process.pid}-${Date.now()}.sled`)
+  const chain = cache.createChain({ diskPath: path, demotion: ChainDemotion.Evict, promoteAfterHits: 0 })
+
+  chain.set('user:1', 'Ana')
+  chain.set('user:2', 'Luis')
+  t.false(cache.has('user:1'))
+  await new Promise((resolve) => setTimeout(resolve, 50))
+  t.is(await chain.get('user:1'), 'Ana')
+  t.is(chain.getStats().demotions, 1)
+
+  cache.set('plain:1', 'a')
+  cache.set('plain:2', 'b')
+  await new Promise((resolve) => setTimeout(resolve, 50))
+  t.is(chain.getStats().demotions, 2)
+  t.is(await chain.get('plain:1'), null)
+})
+
+test('MicroserviceCache drains on shutdown', async (t) => {
+  const cache = new MicroserviceCache()
+  const written: string[] = []
+  cache.enableWriteBehind(
+    async (batch: ChangeRecord[]) => {
+      written.push(...batch.map((record) => record.key))
+    },
+    100,
+    60_000,
+  )
+  cache.startSweeper(10)
+  cache.set('user:1', 'Ana')
+  cache.set('user:2', 'Luis')
+
+  t.false(cache.isDraining())
+  const report = await cache.shutdown()
+  t.is(report.flushed, 2)
+  t.is(report.snapshotEntries, undefined)
+  t.deepEqual(written, ['user:1', 'user:2'])
+  t.true(cache.isDraining())
+  t.false(cache.stopSweeper())
+
+  t.is(cache.get('user:1'), 'Ana')
+  t.throws(() => cache.set('user:3', 'Eva'), { message: /draining/ })
+  t.throws(() => cache.increment('visits'), { message: /draining/ })
+  t.is(await cache.getOrSet('user:4', async () => 'Sara'), 'Sara')
+  t.false(cache.has('user:4'))
+  t.throws(() => cache.delete('user:2'), { message: /draining/ })
+  t.throws(() => cache.take('user:2'), { message: /draining/ })
+  t.throws(() => cache.deleteByTag('users'), { message: /draining/ })
+  t.throws(() => cache.deleteIfEquals('user:2', 'Luis'), { message: /draining/ })
+  for (const write of [
+    () => cache.setIfPresent('user:2', 'Eva'),
+    () => cache.setIfAbsent('user:5', 'Eva'),
+    () => cache.expire('user:2', 1),
+    () => cache.expireAt('user:2', 1),
+    () => cache.persist('user:2'),
+    () => cache.touch('user:2', 1),
+    () => cache.acquireLock('jobs', 1000),
+  ]) {
+    t.throws(write, { message: /draining/ })
+  }
+  t.is(cache.get('user:2'), 'Luis')
+  t.is(cache.getTtl('user:2'), -1)
+  t.false(cache.has('user:5'))
+})
+
+test('MicroserviceCache saves the shutdown snapshot when the write-behind flush fails', async (t) => {
+  const cache = new MicroserviceCache()
+  const path = join(tmpdir(), `microservice-cache-${process.pid}-${Date.now()}.shutdown`)
+  cache.enableWriteBehind(
+    async () => {
+      throw new Error('database unavailable')
+    },
+    100,
+    60_000,
+  )
+  cache.set('user:1', 'Ana')
+
+  await t.throwsAsync(cache.shutdown(path), { message: /database unavailable/ })
+  const restored = new MicroserviceCache()
+  t.is(restored.loadSnapshot(path), 1)
+  t.is(restored.get('user:1'), 'Ana')
+})
+
+test('MicroserviceCache persists writes through before caching them', async (t) => {
+  const cache = new MicroserviceCache()
+  const records: string[] = []
+  const persist = async (record: ChangeRecord) => {
+    if (record.key === 'orders:broken') {
+      throw new Error('database unavailable')
+    }
+    if (record.value === 'slow') {
+      await new Promise((resolve) => setTimeout(resolve, 30))
+    }
+    records.push(`${record.op}:${record.key}:${record.value ?? ''}`)
+  }
+
+  t.true(cache.enableWriteThrough('orders', persist))
+  t.false(cache.enableWriteThrough('orders', persist))
+  t.throws(() => cache.set('orders:1', 'pending'), { message: /write-through namespace 'orders'/ })
+  for (const write of [
+    () => cache.setJson('orders:1', {}),
+    () => cache.increment('orders:1'),
+    () => cache.hset('orders:1', 'status', 'pending'),
+    () => cache.setIfAbsent('orders:1', 'pending'),
+    () => cache.setIfPresent('orders:1', 'pending'),
+    () => cache.setHttpResponse('orders:1', 200, { 'cache-control': 'max-age=60' }, '{}'),
+    () => cache.setHttpVariant('orders:1', {}, 200, { 'cache-control': 'max-age=60', vary: 'accept' }, '{}'),
+    () => cache.setForTenant('acme', 'orders:1', 'pending'),
+    () => cache.setGraphqlResponse('orders:1', { data: {} }),
+    () => cache.setMany([{ key: 'orders:1', value: 'pending' }]),
+  ]) {
+    t.throws(write, { message: /write-through namespace 'orders'/ })
+  }
+
+  t.false(cache.has('orders:1'))
+
+  t.true((await cache.setWriteThrough('orders:1', 'paid')) > 0)
+  t.is(cache.get('orders:1'), 'paid')
+  t.throws(() => cache.setIfPresent('orders:1', 'direct'), { message: /write-through namespace 'orders'/ })
+  t.is(cache.get('orders:1'), 'paid')
+  await t.throwsAsync(cache.setWriteThrough('orders:broken', 'paid'), { message: /database unavailable/ })
+  t.false(cache.has('orders:broken'))
+
+  t.true(await cache.deleteWriteThrough('orders:1'))
+  t.false(cache.has('orders:1'))
+  t.deepEqual(records, ['set:orders:1:paid', 'delete:orders:1:'])
+
+  await Promise.all([cache.setWriteThrough('orders:3', 'slow'), cache.setWriteThrough('orders:3', 'fast')])
+  t.is(cache.get('orders:3'), 'fast')
+  t.deepEqual(records.slice(-2), ['set:orders:3:slow', 'set:orders:3:fast'])
+
+  t.true(cache.disableWriteThrough('orders'))
+  cache.set('orders:2', 'pending')
+  t.is(cache.get('orders:2'), 'pending')
+})
+
+test('MicroserviceCache persists writes behind in batches', async (t) => {
+  const cache = new MicroserviceCache()
+  const batches: string[][] = []
+  const writer = async (batch: ChangeRecord[]) => {
+    batches.push(batch.map((record) => `${record.op}:${record.key}`))
+  }
+  t.true(cache.enableWriteBehind(writer, 2, 50))
+  t.false(cache.enableWriteBehind(writer))
+
+  cache.set('user:1', 'Ana')
+  cache.set('user:2', 'Eva')
+  cache.delete('user:1')
+  await new Promise((resolve) => setTimeout(resolve, 100))
+  t.deepEqual(batches, [['set:user:1', 'set:user:2'], ['delete:user:1']])
+
+  t.true(cache.disableWriteBehind())
+  let release = () => {}
+  const blocked = new Promise<void>((resolve) => {
+    release = resolve
+  })
+  cache.enableWriteBehind(() => blocked, 2, 1000, 4)
+  for (let i = 0; i < 7; i++) {
+    cache.set(`order:${i}`, 'pending')
+  }
+  t.is(cache.getStats().counters.write_behind_dropped, 1)
+  release()
+  t.is(await cache.flushWriteBehind(), 4)
+})
+
+test('MicroserviceCache retries loaders and falls back to a supplier', async (t) => {
+  const cache = new MicroserviceCache()
+  t.true(cache.enableLoaderRetries(3, 5))
+  t.false(cache.enableLoaderRetries(1))
+  t.throws(() => cache.enableLoaderRetries(0), { message: /Invalid loader retries/ })
+
+  let attempts = 0
+  const flaky = async () => {
+    attempts++
+    if (attempts < 3) {
+      throw new Error('transient')
+    }
+    return 'loaded'
+  }
+  t.is(await cache.getOrSet('user:1', flaky), 'loaded')
+  t.is(attempts, 3)
+  t.is(cache.getStats().counters.loader_retries, 2)
+
+  const failing = async (): Promise<string> => {
+    throw new Error('downstream unavailable')
+  }
+  await t.throwsAsync(cache.getOrSet('user:2', failing), { message: /downstream unavailable/ })
+
+  cache.registerFallback(async (key) => `fallback:${key}`)
+  t.is(await cache.getOrSet('user:2', failing), 'fallback:user:2')
+  t.is(cache.get('user:2'), null)
+  t.true(cache.unregisterFallback())
+  t.true(cache.disableLoaderRetries())
+})
+
+test('MicroserviceCache opens a circuit after repeated loader failures', async (t) => {
+  const cache = new MicroserviceCache()
+  t.true(cache.enableCircuitBreaker(2, 50, ':'))
+  t.false(cache.enableCircuitBreaker(2, 50))
+  t.throws(() => cache.enableCircuitBreaker(0, 50), { message: /Invalid circuit breaker/ })
+
+  const failing = async (): Promise<string> => {
+    throw new Error('downstream unavailable')
+  }
+  let calls = 0
+  const healthy = async () => {
+    calls++
+    return 'loaded'
+  }
+  await t.throwsAsync(cache.getOrSet('user:1', failing), { message: /downstream unavailable/ })
+  await t.throwsAsync(cache.getOrSet('user:2', failing), { message: /downstream unavailable/ })
+  await t.throwsAsync(cache.getOrSet('user:3', healthy), { message: /Circuit open/ })
+  t.is(calls, 0)
+  t.is(await cache.getOrSet('order:1', healthy), 'loaded')
+  t.is(cache.getCircuitStates().user.status, 'open')
+  t.is(cache.getStats().openCircuits, 1)
+
+  await new Promise((resolve) => setTimeout(resolve, 70))
+  t.is(cache.getCircuitStates().user.status, 'half-open')
+  t.is(await cache.getOrSet('user:3', healthy), 'loaded')
+  t.deepEqual(cache.getCircuitStates(), {})
+  t.is(cache.getStats().counters.circuits_opened, 1)
+
+  t.true(cache.disableCircuitBreaker())
+  cache.enableCircuitBreaker(1, 1000, null, 'fallback')
+  await t.throwsAsync(cache.getOrSet('report', failing))
+  t.is(await cache.getOrSet('report', healthy), 'fallback')
+  t.is(cache.get('report'), null)
+
+  t.true(cache.disableCircuitBreaker())
+  cache.enableCircuitBreaker(1, 1000, ':')
+  cache.setPx('session:1', 'previous', 20)
+  await t.throwsAsync(cache.getOrSet('session:2', failing))
+  await new Promise((resolve) => setTimeout(resolve, 40))
+  const loads = calls
+  t.is(await cache.getOrSet('session:1', healthy), 'previous')
+  t.is(calls, loads)
+})
+
+test('MicroserviceCache expires expensive entries early with XFetch', async (t) => {
+  const cache = new MicroserviceCache()
+  const slowLoader = async () => {
+    await new Promise((resolve) => setTimeout(resolve, 20))
+    return 'computed'
+  }
+  await cache.getOrSet('report', slowLoader, 60)
+  cache.set('plain', 'value', 60)
+  t.is(cache.get('report'), 'computed')
+
+  t.true(cache.enableEarlyExpiration(1e6))
+  t.false(cache.enableEarlyExpiration())
+  t.throws(() => cache.enableEarlyExpiration(-1), { message: /Invalid early expiration/ })
+
+  const reads = Array.from({ length: 10 }, () => cache.get('report'))
+  t.true(reads.includes(null))
+  t.is(cache.get('plain'), 'value')
+  t.true(cache.getStats().counters.early_expirations > 0)
+
+  t.true(cache.disableEarlyExpiration())
+  t.is(cache.get('report'), 'computed')
+})
+
+test('MicroserviceCache counts unique items with HyperLogLog', (t) => {
+  const cache = new MicroserviceCache()
+  t.true(cache.pfAdd('visitors:mon', 'alice'))
+  t.false(cache.pfAdd('visitors:mon', 'alice'))
+  t.true(cache.pfAdd('visitors:mon', ['bob', 'carol'], 60))
+  t.is(cache.pfCount('visitors:mon'), 3)
+  t.is(cache.pfCount('visitors:none'), 0)
+
+  for (let i = 0; i < 5000; i++) {
+    cache.pfAdd('visitors:tue', `user:${i}`)
+  }
+  const estimate = cache.pfCount('visitors:tue')
+  t.true(Math.abs(estimate - 5000) < 250)
+  t.true(cache.pfCount(['visitors:mon', 'visitors:tue']) >= estimate)
+
+  t.true(cache.expireAt('visitors:mon', 0))
+  t.true(cache.pfAdd('visitors:mon', 'dave'))
+  t.is(cache.pfCount('visitors:mon'), 1)
+
+  cache.set('plain', 'value')
+  t.throws(() => cache.pfAdd('plain', 'alice'), { message: /not a HyperLogLog/ })
+  t.throws(() => cache.pfCount('plain'), { message: /not a HyperLogLog/ })
+})
+
+test('MicroserviceCache short-circuits known missing keys with a Bloom filter', async (t) => {
+  const cache = new MicroserviceCache()
+  t.false(cache.markMissing('user:404'))
+  t.true(cache.enableBloomFilter(1000))
+  t.false(cache.enableBloomFilter(1000))
+  t.throws(() => cache.enableBloomFilter(0), { message: /Invalid Bloom filter/ })
+
+  t.true(cache.markMissing('user:404'))
+  t.true(cache.mightContain('user:404'))
+  t.false(cache.mightContain('user:1'))
+  t.true(cache.get('user:404') instanceof CacheMiss)
+  await t.throwsAsync(cache.getOrSet('user:404', async () => 'loaded'), { message: /cached as not found/ })
+
+  cache.setNegative('user:405', 1)
+  t.true(cache.mightContain('user:405'))
+
+  cache.setIfAbsent('user:404', 'found')
+  t.false(cache.mightContain('user:404'))
+  t.is(cache.get('user:404'), 'found')
+  for (let i = 0; i < 2000; i++) {
+    cache.set(`other:${i}`, 'value')
+  }
+  t.true(cache.mightContain('user:405'))
+  t.is(cache.getStats().counters.bloom_hits, 2)
+  t.true(cache.disableBloomFilter())
+  t.false(cache.mightContain('user:405'))
+})
+
+test('MicroserviceCache invalidates GraphQL responses by entity', (t) => {
+  const cache = new MicroserviceCache()
+  const profile = {
+    data: {
+      user: {
+        __typename: 'User',
+        id: 42,
+        posts: [
+          { __typename: 'Post', id: '7' },
+          { __typename: 'Post', id: '8' },
+        ],
+      },
+    },
+  }
+  cache.setGraphqlResponse('query:profile', profile, 60, ['feed'])
+  cache.setGraphqlResponse('query:post', { data: { post: { __typename: 'Post', id: '7' } } })
+  cache.setGraphqlResponse('query:stats', { data: { stats: { __typename: 'Stats', total: 3 } } })
+
+  t.deepEqual(cache.getEntryInfo('query:profile').tags, ['feed', 'Post', 'Post:7', 'Post:8', 'User', 'User:42'])
+  t.deepEqual(cache.getJson('query:profile'), profile)
+  t.is(cache.invalidateEntity('Post:7'), 2)
+  t.deepEqual(cache.keys(), ['query:stats'])
+  t.is(cache.invalidateEntity('Stats'), 1)
+  t.is(cache.invalidateEntity('User:42'), 0)
+})
+
+test('MicroserviceCache stores HTTP variants by Vary headers', (t) => {
+  const cache = new MicroserviceCache()
+  const response = { 'Cache-Control': 'max-age=60', Vary: 'Accept-Encoding, Accept-Language' }
+  cache.setHttpVariant('GET /a', { 'accept-encoding': 'gzip', 'Accept-Language': 'en' }, 200, response, 'gzip')
+  cache.setHttpVariant('GET /a', { 'accept-encoding': 'br', 'Accept-Language': 'en' }, 200, response, 'br')
+  cache.setHttpVariant('GET /ab', {}, 200, { 'Cache-Control': 'max-age=60' }, 'ab')
+
+  t.is(cache.getHttpVariant('GET /a', { 'Accept-Encoding': 'gzip', 'accept-language': 'en' }).body, 'gzip')
+  t.is(cache.getHttpVariant('GET /a', { 'accept-encoding': 'zstd' }), null)
+  t.is(cache.getHttpVariant('GET /missing', {}), null)
+  t.is(
+    cache.httpVariantKey('GET /a', { 'Accept-Encoding': 'gzip' }, ['Accept-Language', 'accept-encoding']),
+    'GET /a accept-encoding="gzip" accept-language=""',
+  )
+  t.is(cache.setHttpVariant('GET /any', {}, 200, { 'Cache-Control': 'max-age=60', Vary: '*' }, 'x'), null)
+
+  t.true(cache.deleteHttpVariant('GET /a', { 'accept-encoding': 'br', 'Accept-Language': 'en' }))
+  t.is(cache.getHttpVariant('GET /a', { 'accept-encoding': 'br', 'Accept-Language': 'en' }), null)
+  t.is(cache.deleteHttpResource('GET /a'), 1)
+  t.deepEqual(cache.keys().sort(), ['GET /ab', 'GET /ab vary'])
+  t.is(cache.getTtl('GET /ab vary'), 60)
+})
+
+test('MicroserviceCache serves conditional reads by ETag', (t) => {
+  const cache = new MicroserviceCache()
+  cache.set('page', 'hello')
+  const { etag } = cache.getEntryInfo('page')
+  t.regex(etag, /^"[0-9a-f]{16}"$/)
+
+  t.deepEqual(cache.getIfNoneMatch('page', etag), { notModified: true, etag })
+  t.true(cache.getIfNoneMatch('page', `"other", W/${etag}`).notModified)
+  t.true(cache.getIfNoneMatch('page', '*').notModified)
+  t.deepEqual(cache.getIfNoneMatch('page', '"other"'), { notModified: false, etag, value: 'hello' })
+  t.is(cache.getIfNoneMatch('missing', '*'), null)
+
+  cache.set('copy', 'hello')
+  t.is(cache.getEntryInfo('copy').etag, etag)
+  cache.set('page', 'changed')
+  const changed = cache.getIfNoneMatch('page', etag)
+  t.false(changed.notModified)
+  t.not(changed.etag, etag)
+  t.is(changed.value, 'changed')
+})
+
+test('MicroserviceCache derives HTTP response TTLs from cache headers', async (t) => {
+  const cache = new MicroserviceCache()
+  const headers = {
+    'Cache-Control': 'public, max-age=60, s-maxage=1, stale-while-revalidate=1',
+    'Content-Type': 'text/plain',
+  }
+  t.is(typeof cache.setHttpResponse('GET /a', 200, headers, 'hello'), 'number')
+  t.is(cache.getTtl('GET /a'), 2)
+  t.deepEqual(cache.getHttpResponse('GET /a'), { status: 200, headers, body: 'hello', ageSeconds: 0, stale: false })
+
+  t.is(cache.setHttpResponse('GET /b', 200, { 'cache-control': 'no-store, max-age=60' }, 'x'), null)
+  t.is(cache.setHttpResponse('GET /c', 200, {}, 'x'), null)
+  t.is(cache.setHttpResponse('GET /session', 200, { 'Cache-Control': 'max-age=60', 'Set-Cookie': 'id=1' }, 'x'), null)
+  t.false(cache.has('GET /b'))
+
+  const expires = new Date(Date.now() + 30_000).toUTCString()
+  cache.setHttpResponse('GET /d', 200, { Expires: expires }, 'x')
+  t.true(cache.getTtl('GET /d') >= 28 && cache.getTtl('GET /d') <= 30)
+
+  cache.setHttpResponse('GET /e', 200, { 'Cache-Control': 'max-age=10', Age: '8' }, 'x')
+  t.is(cache.getTtl('GET /e'), 2)
+  t.is(cache.getHttpResponse('GET /e').ageSeconds, 8)
+
+  await new Promise((resolve) => setTimeout(resolve, 1200))
+  t.true(cache.getHttpResponse('GET /a').stale)
+  t.is(cache.getHttpResponse('GET /e').ageSeconds, 9)
+})
+
+test('MicroserviceCache ages HTTP responses on the manual clock', (t) => {
+  const cache = new MicroserviceCache({ clock: ClockMode.Manual })
+  const start = Date.parse('2030-01-01T00:00:00Z')
+  cache.setTime(start)
+
+  cache.setHttpResponse('GET /expires', 200, { Expires: new Date(start + 30_000).toUTCString() }, 'x')
+  t.is(cache.getTtl('GET /expires'), 30)
+  cache.setHttpResponse('GET /swr', 200, { 'Cache-Control': 'max-age=10, stale-while-revalidate=10' }, 'x')
+
+  cache.advanceTime(11_000)
+  t.is(cache.getTtl('GET /expires'), 19)
+  t.true(cache.getHttpResponse('GET /swr')?.stale)
+  t.is(cache.getHttpResponse('GET /swr')?.ageSeconds, 11)
+
+  cache.advanceTime(20_000)
+  t.is(cache.getHttpResponse('GET /expires'), null)
+})
+
+test('MicroserviceCache claims idempotency keys once', (t) => {
+  const cache = new MicroserviceCache()
+  t.deepEqual(cache.beginIdempotent('payment:1', 60), { status: 'claimed' })
+  t.deepEqual(cache.beginIdempotent('payment:1', 60), { status: 'in-progress' })
+  t.true(cache.completeIdempotent('payment:1', '{"id":42}'))
+  t.deepEqual(cache.beginIdempotent('payment:1', 60), { status: 'completed', response: '{"id":42}' })
+  t.false(cache.completeIdempotent('payment:1', 'other'))
+  t.false(cache.releaseIdempotent('payment:1'))
+
+  t.is(cache.beginIdempotent('payment:2', 60).status, 'claimed')
+  t.true(cache.releaseIdempotent('payment:2'))
+  t.is(cache.beginIdempotent('payment:2', 60).status, 'claimed')
+  t.false(cache.completeIdempotent('payment:3', 'response'))
+  t.deepEqual(cache.keys(), [])
+})
+
+test('MicroserviceCache bounds idempotency records and counts responses toward maxBytes', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2, maxBytes: 100 })
+  cache.set('report', 'x'.repeat(60))
+  cache.beginIdempotent('payment:1', 60)
+  t.true(cache.completeIdempotent('payment:1', 'y'.repeat(60)))
+  t.false(cache.has('report'))
+
+  cache.beginIdempotent('payment:2', 60)
+  t.throws(() => cache.beginIdempotent('payment:3', 60), { code: 'ERR_CAPACITY_EXCEEDED' })
+  t.throws(() => cache.completeIdempotent('payment:2', 'z'.repeat(101)), { code: 'ERR_CAPACITY_EXCEEDED' })
+})
+
+test('MicroserviceCache enforces token bucket rate limits', async (t) => {
+  const cache = new MicroserviceCache()
+  const results = Array.from({ length: 4 }, () => cache.checkRateLimit('user:1', 3, 300))
+  t.deepEqual(
+    results.map((result) => [result.allowed, result.remaining]),
+    [
+      [true, 2],
+      [true, 1],
+      [true, 0],
+      [false, 0],
+    ],
+  )
+  t.true(results[3].retryAfterMs > 0 && results[3].retryAfterMs <= 100)
+  t.true(cache.checkRateLimit('user:2', 3, 300).allowed)
+  t.false(cache.checkRateLimit('user:3', 2, 1000, 5).allowed)
+  t.throws(() => cache.checkRateLimit('user:4', 0, 1000), { message: /must be positive/ })
+
+  await new Promise((resolve) => setTimeout(resolve, 120))
+  t.true(cache.checkRateLimit('user:1', 3, 300).allowed)
+  t.true(cache.resetRateLimit('user:1'))
+  t.is(cache.checkRateLimit('user:1', 3, 300).remaining, 2)
+})
+
+test('MicroserviceCache bounds the number of rate limit buckets', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2 })
+  cache.checkRateLimit('user:1', 10, 50)
+  cache.checkRateLimit('user:2', 10, 1000)
+  t.throws(() => cache.checkRateLimit('user:3', 10, 1000), { code: 'ERR_CAPACITY_EXCEEDED' })
+
+  await new Promise((resolve) => setTimeout(resolve, 20))
+  t.true(cache.checkRateLimit('user:3', 10, 1000).allowed)
+})
+
+test('MicroserviceCache leases repopulation of missing or stale keys', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 100, staleWhileRevalidateSeconds: 10 })
+  const first = cache.getWithLease('user:1', 1000)
+  t.is(typeof first.token, 'number')
+  t.false(first.wait)
+  const second = cache.getWithLease('user:1', 1000)
+  t.is(second.token, undefined)
+  t.true(second.wait)
+
+  t.is(cache.setWithLease('user:1', first.token! + 1, 'ignored'), null)
+  t.is(typeof cache.setWithLease('user:1', first.token!, 'fresh'), 'number')
+  t.is(cache.setWithLease('user:1', first.token!, 'again'), null)
+  t.deepEqual(cache.getWithLease('user:1', 1000), { value: 'fresh', stale: false, wait: false })
+
+  cache.setPx('user:2', 'old', 20)
+  await new Promise((resolve) => setTimeout(resolve, 40))
+  const refresher = cache.getWithLease('user:2', 1000)
+  t.is(refresher.value, 'old')
+  t.true(refresher.stale)
+  t.is(typeof refresher.token, 'number')
+  const reader = cache.getWithLease('user:2', 1000)
+  t.is(reader.value, 'old')
+  t.is(reader.token, undefined)
+  t.false(reader.wait)
+})
+
+test('MicroserviceCache hands out leases with fencing tokens', async (t) => {
+  const cache = new MicroserviceCache()
+  const token = cache.acquireLock('job', 30)
+  t.is(typeof token, 'number')
+  t.throws(() => cache.acquireLock('job', 30), { code: 'ERR_LOCK_HELD', message: "Lock 'job' is held by another owner" })
+  t.false(cache.releaseLock('job', token + 1))
+  t.true(cache.releaseLock('job', token))
+  t.false(cache.releaseLock('job', token))
+
+  const expiring = cache.acquireLock('job', 20)
+  t.true(expiring > token)
+  await new Promise((resolve) => setTimeout(resolve, 40))
+  const next = cache.acquireLock('job', 1000)
+  t.true(next > expiring)
+  t.false(cache.releaseLock('job', expiring))
+  t.true(cache.releaseLock('job', next))
+})
+
+test('MicroserviceCache bounds the number of held locks', async (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2 })
+  cache.acquireLock('a', 20)
+  cache.acquireLock('b', 1000)
+  t.throws(() => cache.acquireLock('c', 1000), { code: 'ERR_CAPACITY_EXCEEDED' })
+
+  await new Promise((resolve) => setTimeout(resolve, 40))
+  t.is(typeof cache.acquireLock('c', 1000), 'number')
+})
+
+test('MicroserviceCache evicts low priority entries first', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 3 })
+  cache.set('report', 'expensive', undefined, undefined, { priority: 'high' })
+  cache.set('page', 'normal')
+  cache.set('thumbnail', 'cheap', undefined, undefined, { priority: 'low' })
+
+  cache.set('other', 'value')
+  t.deepEqual(cache.keys().sort(), ['other', 'page', 'report'])
+
+  for (let i = 0; i < 5; i++) {
+    cache.set(`item:${i}`, 'value')
+  }
+  t.true(cache.has('report'))
+  t.is(cache.getEntryInfo('report').priority, 'high')
+  t.is(cache.getEntryInfo('item:4').priority, 'normal')
+
+  cache.setMany([{ key: 'batch', value: 'value', priority: 'low' }])
+  t.is(cache.getEntryInfo('batch').priority, 'low')
+})
+
+test('MicroserviceCache never evicts pinned entries for capacity', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 4 })
+  cache.set('config', 'critical')
+  t.true(cache.pin('config'))
+  t.false(cache.pin('missing'))
+
+  for (let i = 0; i < 20; i++) {
+    cache.set(`item:${i}`, 'value')
+  }
+  t.is(cache.get('config'), 'critical')
+  t.true(cache.isPinned('config'))
+  t.is(cache.getStats().pinned, 1)
+
+  t.true(cache.pin('item:19'))
+  t.throws(() => cache.pin('item:18'), { message: /already pinned/ })
+
+  t.true(cache.unpin('config'))
+  t.false(cache.unpin('config'))
+  for (let i = 0; i < 20; i++) {
+    cache.set(`other:${i}`, 'value')
+  }
+  t.false(cache.has('config'))
+  t.true(cache.has('item:19'))
+
+  cache.delete('item:19')
+  t.is(cache.getStats().pinned, 0)
+})
+
+test('MicroserviceCache inspects entry metadata without touching eviction order', (t) => {
+  const cache = new MicroserviceCache({ maxSize: 2 })
+  cache.set('a', 'hello', 60, ['greeting'])
+  cache.get('a')
+  cache.set('b', 'world')
+
+  const info = cache.getEntryInfo('a')
+  t.is(info.accessCount, 1)
+  t.deepEqual(info.tags, ['greeting'])
+  t.is(info.expiresAt - info.createdAt, 60_000)
+  t.true(info.bytes >= 'a'.length + 'hello'.length)
+  t.is(cache.getEntryInfo('b').expiresAt, undefined)
+  t.is(cache.getEntryInfo('missing'), null)
+
+  cache.getEntryInfo('a')
+  cache.set('c', '!')
+  t.deepEqual(cache.keys().sort(), ['b', 'c'])
+})
+
+test('MicroserviceCache streams entries in batches', async (t) => {
+  const cache = new MicroserviceCache()
+  for (let i = 0; i < 25; i++) {
+    cache.set(`item:${i}`, `value:${i}`, 60, i % 2 ? ['odd'] : [])
+  }
+  cache.set('other', 'value')
+
+  const records = []
+  for await (const record of cache.entries('item:*', 4)) {
+    records.push(record)
+  }
+
+  t.is(records.length, 25)
+  t.is(new Set(records.map((record) => record.key)).size, 25)
+  const record = records.find((record) => record.key === 'item:3')
+  t.is(record.value, 'value:3')
+  t.deepEqual(record.metadata.tags, ['odd'])
+  t.true(record.metadata.expiresAt > record.metadata.createdAt)
+  t.is(record.metadata.accessCount, 0)
+})
+
+test('MicroserviceCache queries and deletes keys by prefix or glob', (t) => {
+  const cache = new MicroserviceCache()
+  for (const key of ['user:1', 'user:2', 'user:10', 'order:1', 'users']) {
+    cache.set(key, 'value')
+  }
+
+  t.deepEqual(cache.keysByPrefix('user:').sort(), ['user:1', 'user:10', 'user:2'])
+  t.deepEqual(cache.keysMatching('user:?').sort(), ['user:1', 'user:2'])
+  t.deepEqual(cache.keysMatching('*1*').sort(), ['order:1', 'user:1', 'user:10'])
+
+  t.is(cache.deleteByPrefix('user:'), 3)
+  t.deepEqual(cache.keys().sort(), ['order:1', 'users'])
+  t.is(cache.deleteByPrefix('user:'), 0)
+})
+
+test('MicroserviceCache stats and flush', (t) => {
+  const cache = new MicroserviceCache()
+
+  cache.set('stat-key1', 'value1')
+  cache.set('stat-key2', 'value2')
+
+  cache.get('stat-key1')
+  cache.get('stat-missing')
 
   const stats = cache.getStats()
-  t.true(typeof stats === 'string')
+  t.is(stats.entries, 2)
+  t.is(stats.hitRate, 0.5)
+  t.is(stats.operations.gets, 2)
+  t.is(stats.operations.sets, 2)
+  t.true(stats.memory.averageEntryBytes > 0)
 
   const flushedCount = cache.flush()
   t.true(typeof flushedCount === 'number')
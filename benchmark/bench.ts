@@ -1,7 +1,7 @@
 import { Bench } from 'tinybench'
 import { MicroserviceCache } from '../index.js'
 
-const rustCache = new MicroserviceCache(10000, 3600)
+const rustCache = new MicroserviceCache({ maxSize: 10000, defaultTtlSeconds: 3600 })
 
 class SimpleJSCache {
   private cache = new Map<string, { value: string; timestamp: number; ttl: number }>()
@@ -1,16 +1,716 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+export declare class CacheChain {
+  get(key: string): Promise<string | null>
+  set(key: string, value: string, ttlSeconds?: number | undefined | null): number
+  delete(key: string): boolean
+  getStats(): ChainStats
+}
+
+export declare class CacheMiss {}
+
+export declare class CacheNamespace {
+  get name(): string
+  set(key: string, value: string, ttlSeconds?: number | undefined | null, tags?: Array<string> | undefined | null): number
+  get(key: string): string | CacheMiss | null
+  has(key: string): boolean
+  delete(key: string): boolean
+  keys(): Array<string>
+  flush(): number
+  bumpGeneration(): number
+  getStats(): NamespaceStats
+}
+
+export declare class CacheTransaction {
+  expectVersion(key: string, version: number): void
+  watch(keys: Array<string>): void
+  get(key: string): void
+  set(key: string, value: string, ttlSeconds?: number | undefined | null, tags?: Array<string> | undefined | null): void
+  delete(key: string): void
+  execIfUnchanged(ops: Array<TransactionOperation>): TransactionResult
+  commit(): TransactionResult
+}
+
+/**
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
+export declare class ChangeStream {
+  [Symbol.asyncIterator](): AsyncGenerator<ChangeRecord, void, undefined>
+}
+
+/**
+ * This type implements JavaScript's async iterable protocol.
+ * It can be used with `for await...of` loops.
+ *
+ * @see https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Iteration_protocols#the_async_iterator_and_async_iterable_protocols
+ */
+export declare class EntryStream {
+  [Symbol.asyncIterator](): AsyncGenerator<EntryRecord, void, undefined>
+}
+
 export declare class MicroserviceCache {
-  constructor(maxSize?: number | undefined | null, defaultTtlSeconds?: number | undefined | null)
+  constructor(options?: CacheOptions | undefined | null)
+  static attach(token: string): MicroserviceCache
+  static getOrCreate(name: string, options?: CacheOptions | undefined | null): MicroserviceCache
+  share(name?: string | undefined | null): string
+  unshare(token: string): boolean
+  advanceTime(ms: number): number
+  setTime(unixMs: number): void
   set(
     key: string,
     value: string,
     ttlSeconds?: number | undefined | null,
     tags?: Array<string> | undefined | null,
+    options?: SetOptions | undefined | null,
+  ): number
+  setWithDependencies(
+    key: string,
+    value: string,
+    dependsOn: Array<string>,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): number
+  setWriteThrough(
+    key: string,
+    value: string,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): Promise<number>
+  deleteWriteThrough(key: string): Promise<boolean>
+  setPx(key: string, value: string, ttlMs: number, tags?: Array<string> | undefined | null): number
+  setBuffer(
+    key: string,
+    value: Buffer,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): number
+  setJson(
+    key: string,
+    value: any,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): number
+  setIfAbsent(
+    key: string,
+    value: string,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
   ): boolean
-  get(key: string): string | null
+  setIfPresent(
+    key: string,
+    value: string,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): boolean
+  setIfVersion(
+    key: string,
+    value: string,
+    expectedVersion: number,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): number
+  compute(
+    key: string,
+    transform: (current: string | null) => string | null,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): string | null
+  computeAsync(
+    key: string,
+    transform: (current: string | null) => Promise<string | null>,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): Promise<string | null>
+  getVersioned(key: string): VersionedValue | null
+  get(key: string): string | CacheMiss | null
+  getBuffer(key: string): Buffer | null
+  getJson(key: string): any
+  getJsonPath(key: string, path: string): any
+  setHttpResponse(
+    key: string,
+    status: number,
+    headers: Record<string, string>,
+    body: string,
+    tags?: Array<string> | undefined | null,
+  ): number | null
+  getHttpResponse(key: string): HttpResponse | null
+  httpVariantKey(url: string, requestHeaders: Record<string, string>, vary: Array<string>): string
+  setHttpVariant(
+    url: string,
+    requestHeaders: Record<string, string>,
+    status: number,
+    headers: Record<string, string>,
+    body: string,
+    tags?: Array<string> | undefined | null,
+  ): number | null
+  getHttpVariant(url: string, requestHeaders: Record<string, string>): HttpResponse | null
+  deleteHttpVariant(url: string, requestHeaders: Record<string, string>): boolean
+  deleteHttpResource(url: string): number
+  getIfNoneMatch(key: string, etag: string): ConditionalValue | null
+  setNegative(key: string, ttlSeconds?: number | undefined | null): number
+  has(key: string): boolean
+  peek(key: string): string | null
+  getEntryInfo(key: string): EntryMetadata | null
+  expire(key: string, ttlSeconds: number): boolean
+  expireAt(key: string, unixTimestamp: number): boolean
+  persist(key: string): boolean
+  pin(key: string): boolean
+  unpin(key: string): boolean
+  isPinned(key: string): boolean
+  touch(key: string, ttlSeconds?: number | undefined | null): boolean
+  getTtl(key: string): number | null
+  getVersion(key: string): number | null
+  compareAndSwap(key: string, expectedVersion: number, newValue: string): number | null
+  getOrSet(
+    key: string,
+    loader: () => Promise<string>,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): Promise<string>
+  rotateEncryptionKey(key: Buffer): number
+  registerLoader(loader: (key: string) => Promise<string>): void
+  onEvent(callback: (event: CacheEvent) => void, pattern?: string | undefined | null): number
+  offEvent(subscriptionId: number): boolean
+  createChangeStream(capacity?: number | undefined | null): ChangeStream
+  onEvicted(callback: (entry: EvictedEntry) => void): void
+  offEvicted(): boolean
+  unregisterLoader(): boolean
+  registerFallback(fallback: (key: string) => Promise<string>): void
+  unregisterFallback(): boolean
+  enableLoaderRetries(maxRetries: number, baseDelayMs?: number | undefined | null, maxDelayMs?: number | undefined | null): boolean
+  disableLoaderRetries(): boolean
+  enableCircuitBreaker(
+    failureThreshold: number,
+    cooldownMs: number,
+    prefixDelimiter?: string | undefined | null,
+    fallback?: string | undefined | null,
+  ): boolean
+  disableCircuitBreaker(): boolean
+  getCircuitStates(): Record<string, CircuitState>
   delete(key: string): boolean
-  getStats(): string
+  deleteIfEquals(key: string, expectedValue: string): boolean
+  increment(key: string, delta?: number | undefined | null, ttlSeconds?: number | undefined | null): number
+  hset(key: string, field: string, value: string, ttlSeconds?: number | undefined | null): boolean
+  hget(key: string, field: string): string | null
+  hgetAll(key: string): Record<string, string> | null
+  hdel(key: string, field: string): boolean
+  lpush(key: string, values: string | Array<string>, ttlSeconds?: number | undefined | null, maxLength?: number | undefined | null): number
+  rpush(key: string, values: string | Array<string>, ttlSeconds?: number | undefined | null, maxLength?: number | undefined | null): number
+  lpop(key: string): string | null
+  rpop(key: string): string | null
+  lrange(key: string, start: number, stop: number): Array<string>
+  sadd(key: string, members: string | Array<string>, ttlSeconds?: number | undefined | null): number
+  srem(key: string, members: string | Array<string>): number
+  smembers(key: string): Array<string>
+  sismember(key: string, member: string): boolean
+  sinter(keys: Array<string>): Array<string>
+  sunion(keys: Array<string>): Array<string>
+  zadd(key: string, member: string, score: number, ttlSeconds?: number | undefined | null): boolean
+  zincrby(key: string, member: string, increment: number, ttlSeconds?: number | undefined | null): number
+  zrange(key: string, start: number, stop: number, reverse?: boolean | undefined | null): Array<ScoredMember>
+  zrank(key: string, member: string, reverse?: boolean | undefined | null): number | null
+  decrement(key: string, delta?: number | undefined | null): number
+  acquireLock(name: string, ttlMs: number): number
+  releaseLock(name: string, token: number): boolean
+  getWithLease(key: string, leaseMs: number): LeasedValue
+  setWithLease(
+    key: string,
+    token: number,
+    value: string,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): number | null
+  checkRateLimit(key: string, limit: number, windowMs: number, cost?: number | undefined | null): RateLimitResult
+  resetRateLimit(key: string): boolean
+  beginIdempotent(key: string, ttlSeconds: number): IdempotencyClaim
+  completeIdempotent(key: string, response: string, ttlSeconds?: number | undefined | null): boolean
+  releaseIdempotent(key: string): boolean
+  getKeysByTag(tag: string): Array<string>
+  deleteByTag(tag: string): number
+  setGraphqlResponse(
+    key: string,
+    response: any,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): number
+  invalidateEntity(entity: string): number
+  take(key: string): string | null
+  pop(key: string): string | null
+  rename(oldKey: string, newKey: string): boolean
+  copy(srcKey: string, dstKey: string, ttlSeconds?: number | undefined | null): boolean
+  getSet(
+    key: string,
+    value: string,
+    ttlSeconds?: number | undefined | null,
+    tags?: Array<string> | undefined | null,
+  ): string | null
+  getMany(keys: Array<string>): Array<string | undefined | null>
+  setMany(entries: Array<SetManyEntry>): number
+  deleteMany(keys: Array<string>): number
+  getStats(): CacheStats
+  memoryUsage(): MemoryUsage
+  memoryUsageOf(key: string): number | null
+  reportMemoryPressure(level: MemoryPressure): number
+  resetStats(): void
+  configure(settings: CacheSettings): number
+  getHotKeys(limit?: number | undefined | null): Array<HotKey>
+  namespace(name: string, maxSize?: number | undefined | null, defaultTtlSeconds?: number | undefined | null): CacheNamespace
+  transaction(): CacheTransaction
+  watch(keys: Array<string>): CacheTransaction
+  setTenantQuota(tenant: string, maxEntries?: number | undefined | null, maxBytes?: number | undefined | null): void
+  setForTenant(tenant: string, key: string, value: string, ttlSeconds?: number | undefined | null, tags?: Array<string> | undefined | null): number
+  getTenantStats(): Record<string, TenantStats>
+  bumpGeneration(namespace: string): number
+  setTagTtl(tag: string, ttlSeconds: number): void
+  getTagTtls(): Record<string, number>
+  getTagStats(): Record<string, GroupStats>
+  getNamespaceStats(): Record<string, GroupStats>
+  getPrometheusMetrics(): string
+  scan(cursor: string, pattern?: string | undefined | null, limit?: number | undefined | null): ScanPage
+  entries(pattern?: string | undefined | null, batchSize?: number | undefined | null): EntryStream
   keys(): Array<string>
+  keysByPrefix(prefix: string): Array<string>
+  keysMatching(pattern: string): Array<string>
+  deleteByPrefix(prefix: string): number
+  startSweeper(intervalMs?: number | undefined | null): boolean
+  pruneExpired(): number
+  evict(count: number): number
+  stopSweeper(): boolean
+  saveSnapshot(path: string): number
+  loadSnapshot(path: string): number
+  enableAutosave(intervalSeconds: number, path: string): boolean
+  disableAutosave(): boolean
+  startStatsdEmitter(address: string, intervalMs?: number | undefined | null, prefix?: string | undefined | null, tags?: Array<string> | undefined | null): boolean
+  stopStatsdEmitter(): boolean
+  enableWriteThrough(namespace: string, persist: (record: ChangeRecord) => Promise<void>): boolean
+  disableWriteThrough(namespace: string): boolean
+  enableWriteBehind(
+    writer: (batch: Array<ChangeRecord>) => Promise<void>,
+    batchSize?: number | undefined | null,
+    flushIntervalMs?: number | undefined | null,
+    maxQueue?: number | undefined | null,
+  ): boolean
+  flushWriteBehind(): Promise<number>
+  disableWriteBehind(): boolean
+  shutdown(snapshotPath?: string | undefined | null): Promise<ShutdownReport>
+  isDraining(): boolean
+  enableOpenTelemetry(endpoint: string, serviceName?: string | undefined | null, traces?: boolean | undefined | null): boolean
+  disableOpenTelemetry(): boolean
+  withTraceContext<T>(traceparent: string, callback: () => T): T
+  enableAppendLog(path: string, compactIntervalSeconds?: number | undefined | null): number
+  compactAppendLog(): number
+  disableAppendLog(): boolean
+  enableEarlyExpiration(beta?: number | undefined | null): boolean
+  disableEarlyExpiration(): boolean
+  enableBloomFilter(expectedItems: number, falsePositiveRate?: number | undefined | null): boolean
+  disableBloomFilter(): boolean
+  markMissing(key: string): boolean
+  mightContain(key: string): boolean
+  pfAdd(key: string, items: string | Array<string>, ttlSeconds?: number | undefined | null): boolean
+  pfCount(keys: string | Array<string>): number
+  enableDiskTier(path: string): boolean
+  disableDiskTier(): boolean
+  /**
+   * Writes and deletes reach Redis from a background queue. Read-modify-write calls
+   * (`setIfAbsent`, `increment`, `hset`, ...) on keys missing from memory still block the
+   * JavaScript thread on a Redis round-trip of up to 1 second.
+   */
+  enableRedisTier(url: string, keyPrefix?: string | undefined | null): boolean
+  disableRedisTier(): boolean
+  createChain(
+    options?: ChainOptions | undefined | null,
+    loader?: ((key: string) => Promise<string>) | undefined | null,
+  ): CacheChain
+  enableInvalidation(url: string, channel?: string | undefined | null): boolean
+  disableInvalidation(): boolean
   flush(): number
 }
+
+export interface CacheEvent {
+  type: CacheEventType
+  key: string
+  timestamp: number
+}
+
+export interface CacheOptions {
+  maxSize?: number
+  maxBytes?: number
+  defaultTtlSeconds?: number
+  defaultTtiSeconds?: number
+  evictionPolicy?: EvictionPolicy
+  ttlJitterPercent?: number
+  staleWhileRevalidateSeconds?: number
+  refreshAheadPercent?: number
+  negativeTtlSeconds?: number
+  compression?: Compression
+  compressionThresholdBytes?: number
+  encryptionKey?: Buffer
+  sweeperIntervalMs?: number
+  hashKeys?: boolean
+  keyHash?: KeyHash
+  shardCount?: number
+  initialCapacity?: number
+  maxValueBytes?: number
+  maxKeyLength?: number
+  tagTtlSeconds?: Record<string, number>
+  clock?: ClockMode
+}
+
+export interface CacheSettings {
+  maxSize?: number
+  maxBytes?: number
+  defaultTtlSeconds?: number
+  defaultTtiSeconds?: number
+  ttlJitterPercent?: number
+  staleWhileRevalidateSeconds?: number
+  refreshAheadPercent?: number
+  negativeTtlSeconds?: number
+  maxValueBytes?: number
+  maxKeyLength?: number
+}
+
+export interface CacheStats {
+  entries: number
+  liveEntries: number
+  maxSize: number
+  pinned: number
+  openCircuits: number
+  hits: number
+  misses: number
+  hitRate: number
+  operations: OperationStats
+  memory: MemoryStats
+  latency: OperationLatencies
+  windows: RollingWindows
+  counters: Record<string, number>
+}
+
+export declare enum CacheEventType {
+  Set = 'set',
+  Delete = 'delete',
+  Expire = 'expire',
+  Evict = 'evict',
+}
+
+export declare enum ChainDemotion {
+  WriteThrough = 'write-through',
+  Evict = 'evict'
+}
+
+export interface ChainLevelStats {
+  hits: number
+  misses: number
+  writes: number
+  errors: number
+}
+
+export interface ChainOptions {
+  redisUrl?: string
+  redisPrefix?: string
+  diskPath?: string
+  ttlSeconds?: number
+  l1TtlSeconds?: number
+  promoteAfterHits?: number
+  demotion?: ChainDemotion
+}
+
+export interface ChainStats {
+  l1: ChainLevelStats
+  l2: ChainLevelStats
+  loader: ChainLevelStats
+  promotions: number
+  demotions: number
+}
+
+export declare enum ChangeOperation {
+  Set = 'set',
+  Delete = 'delete',
+  Expire = 'expire',
+  Evict = 'evict',
+  Flush = 'flush',
+}
+
+export interface ChangeRecord {
+  sequence: number
+  op: ChangeOperation
+  key: string
+  value?: string
+  timestamp: number
+}
+
+export interface CircuitState {
+  status: CircuitStatus
+  failures: number
+  openedAt?: number
+}
+
+export declare enum CircuitStatus {
+  Closed = 'closed',
+  Open = 'open',
+  HalfOpen = 'half-open'
+}
+
+export declare enum ClockMode {
+  System = 'system',
+  Manual = 'manual'
+}
+
+export declare enum Compression {
+  Lz4 = 'lz4',
+  Zstd = 'zstd',
+  Gzip = 'gzip',
+}
+
+export interface ConditionalValue {
+  notModified: boolean
+  value?: string
+  etag: string
+}
+
+export interface EntryMetadata {
+  createdAt: number
+  lastAccessed: number
+  expiresAt?: number
+  accessCount: number
+  version: number
+  tags: Array<string>
+  bytes: number
+  priority: Priority
+  etag: string
+}
+
+export interface EntryRecord {
+  key: string
+  value?: string
+  metadata: EntryMetadata
+}
+
+export interface EvictedEntry {
+  key: string
+  value?: string
+  reason: EvictionReason
+}
+
+export declare enum EvictionPolicy {
+  Lru = 'lru',
+  Lfu = 'lfu',
+  Fifo = 'fifo',
+  Random = 'random',
+  TinyLfu = 'tinylfu',
+}
+
+export declare enum EvictionReason {
+  Capacity = 'capacity',
+  Ttl = 'ttl',
+  Delete = 'delete',
+  Flush = 'flush',
+}
+
+export interface GroupStats {
+  hits: number
+  misses: number
+  hitRate: number
+  entries: number
+  bytes: number
+}
+
+export interface HotKey {
+  key: string
+  count: number
+  share: number
+}
+
+export interface HttpResponse {
+  status: number
+  headers: Record<string, string>
+  body: string
+  ageSeconds: number
+  stale: boolean
+}
+
+export interface IdempotencyClaim {
+  status: IdempotencyStatus
+  response?: string
+}
+
+export declare enum IdempotencyStatus {
+  Claimed = 'claimed',
+  InProgress = 'in-progress',
+  Completed = 'completed'
+}
+
+export declare enum KeyHash {
+  Fnv = 'fnv',
+  Xxh64 = 'xxh64',
+  Xxh3 = 'xxh3',
+  Sha256 = 'sha256'
+}
+
+export interface LatencyStats {
+  count: number
+  meanUs: number
+  p50Us: number
+  p95Us: number
+  p99Us: number
+  maxUs: number
+}
+
+export interface LeasedValue {
+  value?: string
+  stale: boolean
+  token?: number
+  wait: boolean
+}
+
+export interface MemoryStats {
+  totalBytes: number
+  metadataBytes: number
+  maxBytes?: number
+  averageEntryBytes: number
+  compressedValues: number
+  compressionInputBytes: number
+  compressionOutputBytes: number
+  compressionRatio?: number
+}
+
+export declare enum MemoryPressure {
+  None = 'none',
+  Moderate = 'moderate',
+  Critical = 'critical',
+}
+
+export interface MemoryUsage {
+  liveEntries: number
+  expiredEntries: number
+  dataBytes: number
+  metadataBytes: number
+  totalBytes: number
+  maxBytes?: number
+  pressure: MemoryPressure
+}
+
+export interface NamespaceStats {
+  entries: number
+  maxSize?: number
+  hits: number
+  misses: number
+  hitRate: number
+  sets: number
+  deletes: number
+  evictions: number
+  bytes: number
+}
+
+export interface OperationLatencies {
+  get: LatencyStats
+  set: LatencyStats
+  delete: LatencyStats
+  loader: LatencyStats
+}
+
+export interface OperationStats {
+  gets: number
+  sets: number
+  deletes: number
+  loads: number
+  refreshes: number
+  evictions: number
+  expirations: number
+  flushes: number
+}
+
+export declare enum Priority {
+  Low = 'low',
+  Normal = 'normal',
+  High = 'high'
+}
+
+export interface RateLimitResult {
+  allowed: boolean
+  remaining: number
+  retryAfterMs?: number
+}
+
+export interface RollingWindows {
+  oneMinute: WindowStats
+  fiveMinutes: WindowStats
+  fifteenMinutes: WindowStats
+}
+
+export interface ScanPage {
+  keys: Array<string>
+  cursor: string
+}
+
+export interface ScoredMember {
+  member: string
+  score: number
+}
+
+export interface SetManyEntry {
+  key: string
+  value: string
+  ttlSeconds?: number
+  tags?: Array<string>
+  ttiSeconds?: number
+  priority?: Priority
+  expectedVersion?: number
+}
+
+export interface SetOptions {
+  ttiSeconds?: number
+  priority?: Priority
+}
+
+export interface ShutdownReport {
+  flushed: number
+  snapshotEntries?: number
+}
+
+export interface TenantStats {
+  entries: number
+  bytes: number
+  maxEntries?: number
+  maxBytes?: number
+  evictions: number
+}
+
+export declare enum TransactionCommand {
+  Get = 'get',
+  Set = 'set',
+  Delete = 'delete'
+}
+
+export interface TransactionOperation {
+  op: TransactionCommand
+  key: string
+  value?: string
+  ttlSeconds?: number
+  tags?: Array<string>
+}
+
+export interface TransactionResult {
+  committed: boolean
+  conflicts: Array<string>
+  values: Array<string | undefined | null>
+  versions: Array<number>
+}
+
+export interface VersionedValue {
+  value: string
+  version: number
+}
+
+export interface WindowStats {
+  windowSeconds: number
+  hits: number
+  misses: number
+  hitRate: number
+  sets: number
+  deletes: number
+  evictions: number
+  operationsPerSecond: number
+}
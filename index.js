@@ -508,4 +508,23 @@ if (!nativeBinding) {
 }
 
 module.exports = nativeBinding
+module.exports.CacheChain = nativeBinding.CacheChain
+module.exports.CacheMiss = nativeBinding.CacheMiss
+module.exports.CacheNamespace = nativeBinding.CacheNamespace
+module.exports.CacheTransaction = nativeBinding.CacheTransaction
+module.exports.ChangeStream = nativeBinding.ChangeStream
+module.exports.EntryStream = nativeBinding.EntryStream
 module.exports.MicroserviceCache = nativeBinding.MicroserviceCache
+module.exports.CacheEventType = nativeBinding.CacheEventType
+module.exports.ChangeOperation = nativeBinding.ChangeOperation
+module.exports.EvictionPolicy = nativeBinding.EvictionPolicy
+module.exports.EvictionReason = nativeBinding.EvictionReason
+module.exports.Compression = nativeBinding.Compression
+module.exports.IdempotencyStatus = nativeBinding.IdempotencyStatus
+module.exports.Priority = nativeBinding.Priority
+module.exports.CircuitStatus = nativeBinding.CircuitStatus
+module.exports.ClockMode = nativeBinding.ClockMode
+module.exports.ChainDemotion = nativeBinding.ChainDemotion
+module.exports.KeyHash = nativeBinding.KeyHash
+module.exports.MemoryPressure = nativeBinding.MemoryPressure
+module.exports.TransactionCommand = nativeBinding.TransactionCommand
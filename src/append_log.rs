@@ -0,0 +1,175 @@
+use crate::CacheEntry;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"MSL2";
+const LEGACY_MAGIC: &[u8; 4] = b"MSL1";
+
+#[derive(Serialize)]
+pub(crate) enum Record<'a> {
+  Put(&'a CacheEntry),
+  Remove(&'a str, u64),
+  Clear,
+  Generation(&'a str, u64),
+}
+
+#[derive(Deserialize)]
+pub(crate) enum Replayed {
+  Put(Box<CacheEntry>),
+  Remove(String, u64),
+  Clear,
+  Generation(String, u64),
+}
+
+pub(crate) struct Replay {
+  pub(crate) records: Vec<Replayed>,
+  pub(crate) truncated_bytes: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct LogQueue(Mutex<Option<Vec<u8>>>);
+
+impl LogQueue {
+  pub(crate) fn open(&self) {
+    *self.0.lock() = Some(Vec::new());
+  }
+
+  pub(crate) fn close(&self) -> Vec<u8> {
+    self.0.lock().take().unwrap_or_default()
+  }
+
+  pub(crate) fn is_open(&self) -> bool {
+    self.0.lock().is_some()
+  }
+
+  pub(crate) fn push(&self, record: &Record) -> bincode::Result<()> {
+    if !self.is_open() {
+      return Ok(());
+    }
+    let encoded = bincode::serialize(record)?;
+    if let Some(queued) = self.0.lock().as_mut() {
+      queued.extend_from_slice(&encoded);
+    }
+    Ok(())
+  }
+
+  pub(crate) fn drain(&self) -> Vec<u8> {
+    self.0.lock().as_mut().map(mem::take).unwrap_or_default()
+  }
+}
+
+pub(crate) struct AppendLog {
+  path: PathBuf,
+  writer: BufWriter<File>,
+  pending: Option<Vec<u8>>,
+}
+
+impl AppendLog {
+  pub(crate) fn open(path: &Path) -> io::Result<Self> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = BufWriter::new(file);
+    if writer.get_ref().metadata()?.len() == 0 {
+      writer.write_all(MAGIC)?;
+      writer.flush()?;
+    }
+
+    Ok(Self {
+      path: path.to_path_buf(),
+      writer,
+      pending: None,
+    })
+  }
+
+  pub(crate) fn append(&mut self, encoded: &[u8]) -> io::Result<()> {
+    if encoded.is_empty() {
+      return Ok(());
+    }
+    if let Some(pending) = &mut self.pending {
+      pending.extend_from_slice(encoded);
+    }
+    self.writer.write_all(encoded)?;
+    self.writer.flush()
+  }
+
+  pub(crate) fn begin_compaction(&mut self) {
+    self.pending = Some(Vec::new());
+  }
+
+  pub(crate) fn finish_compaction(
+    &mut self,
+    generations: &[(String, u64)],
+    entries: &[CacheEntry],
+  ) -> io::Result<()> {
+    let pending = self.pending.take().unwrap_or_default();
+
+    let mut partial = self.path.as_os_str().to_owned();
+    partial.push(".tmp");
+    let partial = Path::new(&partial);
+
+    let mut writer = BufWriter::new(File::create(partial)?);
+    writer.write_all(MAGIC)?;
+    for (namespace, generation) in generations {
+      bincode::serialize_into(&mut writer, &Record::Generation(namespace, *generation))
+        .map_err(io::Error::other)?;
+    }
+    for entry in entries {
+      bincode::serialize_into(&mut writer, &Record::Put(entry)).map_err(io::Error::other)?;
+    }
+    writer.write_all(&pending)?;
+    writer.into_inner()?.sync_all()?;
+
+    fs::rename(partial, &self.path)?;
+    *self = Self::open(&self.path)?;
+    Ok(())
+  }
+
+  pub(crate) fn sync(&mut self) -> io::Result<()> {
+    self.writer.flush()?;
+    self.writer.get_ref().sync_data()
+  }
+}
+
+pub(crate) fn replay(path: &Path) -> io::Result<Replay> {
+  let bytes = match fs::read(path) {
+    Ok(bytes) => bytes,
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+    Err(err) => return Err(err),
+  };
+
+  let mut replay = Replay {
+    records: Vec::new(),
+    truncated_bytes: 0,
+  };
+  if bytes.is_empty() {
+    return Ok(replay);
+  }
+
+  let Some(mut remaining) = bytes.strip_prefix(MAGIC) else {
+    if bytes.starts_with(LEGACY_MAGIC) {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "append log was written by an older cache version",
+      ));
+    }
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidData,
+      "not a cache append log",
+    ));
+  };
+
+  while !remaining.is_empty() {
+    let unread = remaining.len();
+    match bincode::deserialize_from(&mut remaining) {
+      Ok(record) => replay.records.push(record),
+      Err(_) => {
+        replay.truncated_bytes = unread as u64;
+        break;
+      }
+    }
+  }
+  Ok(replay)
+}
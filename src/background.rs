@@ -0,0 +1,51 @@
+use parking_lot::{Condvar, Mutex};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+pub(crate) struct BackgroundTask {
+  shutdown: Arc<(Mutex<bool>, Condvar)>,
+  handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundTask {
+  pub(crate) fn spawn(
+    name: &str,
+    interval: Duration,
+    mut tick: impl FnMut() + Send + 'static,
+  ) -> std::io::Result<Self> {
+    let shutdown = Arc::new((Mutex::new(false), Condvar::new()));
+    let signal = shutdown.clone();
+
+    let handle = thread::Builder::new()
+      .name(name.to_string())
+      .spawn(move || {
+        let (stopped, condvar) = &*signal;
+        let mut stopped = stopped.lock();
+        loop {
+          condvar.wait_for(&mut stopped, interval);
+          if *stopped {
+            break;
+          }
+          tick();
+        }
+      })?;
+
+    Ok(Self {
+      shutdown,
+      handle: Some(handle),
+    })
+  }
+}
+
+impl Drop for BackgroundTask {
+  fn drop(&mut self) {
+    let (stopped, condvar) = &*self.shutdown;
+    *stopped.lock() = true;
+    condvar.notify_all();
+
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
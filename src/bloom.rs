@@ -0,0 +1,85 @@
+use dashmap::DashSet;
+use fnv::FnvHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+pub(crate) struct BloomFilter {
+  counters: Box<[AtomicU8]>,
+  hashes: u64,
+  capacity: usize,
+  marked: DashSet<u64>,
+}
+
+impl BloomFilter {
+  pub(crate) fn new(capacity: u64, false_positive_rate: f64) -> Self {
+    let ln2 = std::f64::consts::LN_2;
+    let width = (-(capacity as f64) * false_positive_rate.ln() / (ln2 * ln2)).ceil() as u64;
+    let width = width.max(64);
+    let hashes = ((width as f64 / capacity as f64) * ln2)
+      .round()
+      .clamp(1.0, 16.0) as u64;
+    Self {
+      counters: (0..width).map(|_| AtomicU8::new(0)).collect(),
+      hashes,
+      capacity: capacity as usize,
+      marked: DashSet::new(),
+    }
+  }
+
+  fn fingerprint(key: &str) -> u64 {
+    let mut hasher = FnvHasher::default();
+    key.hash(&mut hasher);
+    hasher.finish()
+  }
+
+  fn slots(&self, hash: u64) -> impl Iterator<Item = usize> + '_ {
+    let (first, second) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+    let width = self.counters.len() as u64;
+    (0..self.hashes)
+      .map(move |index| (first.wrapping_add(index.wrapping_mul(second)) % width) as usize)
+  }
+
+  pub(crate) fn insert(&self, key: &str) {
+    let hash = Self::fingerprint(key);
+    if self.marked.contains(&hash) {
+      return;
+    }
+    if self.marked.len() >= self.capacity {
+      self.clear();
+    }
+    if !self.marked.insert(hash) {
+      return;
+    }
+    for slot in self.slots(hash) {
+      let _ = self.counters[slot].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+        (count < u8::MAX).then_some(count + 1)
+      });
+    }
+  }
+
+  pub(crate) fn remove(&self, key: &str) -> bool {
+    let hash = Self::fingerprint(key);
+    if self.marked.remove(&hash).is_none() {
+      return false;
+    }
+    for slot in self.slots(hash) {
+      let _ = self.counters[slot].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+        (count > 0 && count < u8::MAX).then(|| count - 1)
+      });
+    }
+    true
+  }
+
+  pub(crate) fn contains(&self, key: &str) -> bool {
+    self
+      .slots(Self::fingerprint(key))
+      .all(|slot| self.counters[slot].load(Ordering::Relaxed) > 0)
+  }
+
+  pub(crate) fn clear(&self) {
+    self.marked.clear();
+    for counter in self.counters.iter() {
+      counter.store(0, Ordering::Relaxed);
+    }
+  }
+}
@@ -0,0 +1,130 @@
+use dashmap::DashMap;
+
+const MAX_CIRCUITS: usize = 4096;
+
+struct Circuit {
+  failures: u32,
+  failed_at: u64,
+  opened_at: Option<u64>,
+}
+
+pub(crate) struct CircuitBreaker {
+  failure_threshold: u32,
+  cooldown_ms: u64,
+  delimiter: Option<String>,
+  pub(crate) fallback: Option<String>,
+  circuits: DashMap<String, Circuit>,
+}
+
+pub(crate) struct CircuitSnapshot {
+  pub(crate) scope: String,
+  pub(crate) failures: u32,
+  pub(crate) opened_at: Option<u64>,
+  pub(crate) half_open: bool,
+}
+
+impl CircuitBreaker {
+  pub(crate) fn new(
+    failure_threshold: u32,
+    cooldown_ms: u64,
+    delimiter: Option<String>,
+    fallback: Option<String>,
+  ) -> Self {
+    Self {
+      failure_threshold,
+      cooldown_ms,
+      delimiter: delimiter.filter(|delimiter| !delimiter.is_empty()),
+      fallback,
+      circuits: DashMap::new(),
+    }
+  }
+
+  fn scope<'a>(&self, key: &'a str) -> &'a str {
+    match &self.delimiter {
+      Some(delimiter) => key
+        .split_once(delimiter.as_str())
+        .map_or(key, |(scope, _)| scope),
+      None => key,
+    }
+  }
+
+  pub(crate) fn admit(&self, key: &str, now: u64) -> bool {
+    let Some(mut circuit) = self.circuits.get_mut(self.scope(key)) else {
+      return true;
+    };
+    match circuit.opened_at {
+      None => true,
+      Some(opened_at) => {
+        if now < opened_at + self.cooldown_ms {
+          return false;
+        }
+        circuit.opened_at = Some(now);
+        true
+      }
+    }
+  }
+
+  pub(crate) fn is_open(&self, key: &str, now: u64) -> bool {
+    self.circuits.get(self.scope(key)).is_some_and(|circuit| {
+      circuit
+        .opened_at
+        .is_some_and(|opened_at| now < opened_at + self.cooldown_ms)
+    })
+  }
+
+  pub(crate) fn record(&self, key: &str, succeeded: bool, now: u64) -> bool {
+    let scope = self.scope(key);
+    if succeeded {
+      self.circuits.remove(scope);
+      return false;
+    }
+
+    if !self.circuits.contains_key(scope) && self.circuits.len() >= MAX_CIRCUITS {
+      self.prune(now);
+      if self.circuits.len() >= MAX_CIRCUITS {
+        return false;
+      }
+    }
+    let mut circuit = self.circuits.entry(scope.to_string()).or_insert(Circuit {
+      failures: 0,
+      failed_at: now,
+      opened_at: None,
+    });
+    circuit.failures += 1;
+    circuit.failed_at = now;
+    let was_open = circuit.opened_at.is_some();
+    if was_open || circuit.failures >= self.failure_threshold {
+      circuit.opened_at = Some(now);
+    }
+    !was_open && circuit.opened_at.is_some()
+  }
+
+  fn prune(&self, now: u64) {
+    self
+      .circuits
+      .retain(|_, circuit| now < circuit.failed_at + self.cooldown_ms);
+  }
+
+  pub(crate) fn open_count(&self) -> usize {
+    self
+      .circuits
+      .iter()
+      .filter(|circuit| circuit.opened_at.is_some())
+      .count()
+  }
+
+  pub(crate) fn snapshot(&self, now: u64) -> Vec<CircuitSnapshot> {
+    self
+      .circuits
+      .iter()
+      .map(|circuit| CircuitSnapshot {
+        scope: circuit.key().clone(),
+        failures: circuit.failures,
+        opened_at: circuit.opened_at,
+        half_open: circuit
+          .opened_at
+          .is_some_and(|opened_at| now >= opened_at + self.cooldown_ms),
+      })
+      .collect()
+  }
+}
@@ -0,0 +1,109 @@
+use crate::disk_tier::DiskTier;
+use crate::remote_tier::RedisTier;
+use crate::{ChainDemotion, KeyLoader};
+use dashmap::{DashMap, DashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const MAX_TRACKED_KEYS: usize = 65_536;
+
+pub(crate) enum SecondLevel {
+  Redis(RedisTier),
+  Disk(DiskTier),
+}
+
+#[derive(Default)]
+pub(crate) struct LevelCounters {
+  pub(crate) hits: AtomicU64,
+  pub(crate) misses: AtomicU64,
+  pub(crate) writes: AtomicU64,
+  pub(crate) errors: AtomicU64,
+}
+
+pub(crate) struct Chain {
+  pub(crate) second: Option<SecondLevel>,
+  pub(crate) loader: Option<KeyLoader>,
+  pub(crate) demotion: ChainDemotion,
+  pub(crate) ttl_ms: Option<u64>,
+  pub(crate) l1_ttl_ms: Option<u64>,
+  promote_after_hits: u32,
+  candidates: DashMap<String, u32>,
+  owned: DashSet<String>,
+  pub(crate) l1: LevelCounters,
+  pub(crate) l2: LevelCounters,
+  pub(crate) origin: LevelCounters,
+  pub(crate) promotions: AtomicU64,
+  pub(crate) demotions: AtomicU64,
+}
+
+impl Chain {
+  pub(crate) fn new(
+    second: Option<SecondLevel>,
+    loader: Option<KeyLoader>,
+    demotion: ChainDemotion,
+    ttl_ms: Option<u64>,
+    l1_ttl_ms: Option<u64>,
+    promote_after_hits: u32,
+  ) -> Self {
+    Self {
+      second,
+      loader,
+      demotion,
+      ttl_ms,
+      l1_ttl_ms,
+      promote_after_hits,
+      candidates: DashMap::new(),
+      owned: DashSet::new(),
+      l1: LevelCounters::default(),
+      l2: LevelCounters::default(),
+      origin: LevelCounters::default(),
+      promotions: AtomicU64::new(0),
+      demotions: AtomicU64::new(0),
+    }
+  }
+
+  pub(crate) fn should_promote(&self, key: &str) -> bool {
+    if self.promote_after_hits == 0 {
+      return false;
+    }
+    if self.candidates.len() >= MAX_TRACKED_KEYS && !self.candidates.contains_key(key) {
+      self.candidates.retain(|_, hits| {
+        *hits /= 2;
+        *hits > 0
+      });
+    }
+    let mut hits = self.candidates.entry(key.to_string()).or_insert(0);
+    *hits += 1;
+    if *hits < self.promote_after_hits {
+      return false;
+    }
+    drop(hits);
+    self.candidates.remove(key);
+    true
+  }
+
+  pub(crate) fn forget(&self, key: &str) {
+    self.candidates.remove(key);
+    self.owned.remove(key);
+  }
+
+  pub(crate) fn claim(&self, key: &str) {
+    if self.demotion == ChainDemotion::Evict && self.owned.len() < MAX_TRACKED_KEYS {
+      self.owned.insert(key.to_string());
+    }
+  }
+
+  pub(crate) fn release(&self, key: &str) -> bool {
+    self.owned.remove(key).is_some()
+  }
+
+  pub(crate) fn l1_ttl(&self, ttl_ms: Option<u64>) -> Option<u64> {
+    match (ttl_ms, self.l1_ttl_ms) {
+      (Some(ttl_ms), Some(l1_ttl_ms)) => Some(ttl_ms.min(l1_ttl_ms)),
+      (ttl_ms, l1_ttl_ms) => ttl_ms.or(l1_ttl_ms),
+    }
+  }
+}
+
+pub(crate) fn bump(counter: &AtomicU64) {
+  counter.fetch_add(1, Ordering::Relaxed);
+}
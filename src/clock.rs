@@ -0,0 +1,56 @@
+use crate::{now_millis, to_wall_millis, wall_millis, ClockMode};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub(crate) enum Clock {
+  System,
+  Manual(AtomicU64),
+}
+
+impl Clock {
+  pub(crate) fn new(mode: ClockMode) -> Self {
+    match mode {
+      ClockMode::System => Clock::System,
+      ClockMode::Manual => Clock::Manual(AtomicU64::new(now_millis())),
+    }
+  }
+
+  pub(crate) fn now(&self) -> u64 {
+    match self {
+      Clock::System => now_millis(),
+      Clock::Manual(now) => now.load(Ordering::Relaxed),
+    }
+  }
+
+  pub(crate) fn wall(&self) -> u64 {
+    match self {
+      Clock::System => wall_millis(),
+      Clock::Manual(now) => to_wall_millis(now.load(Ordering::Relaxed)).max(0) as u64,
+    }
+  }
+
+  pub(crate) fn advance(&self, ms: u64) -> Result<u64, String> {
+    let Clock::Manual(now) = self else {
+      return Err(not_manual());
+    };
+    now.fetch_add(ms, Ordering::Relaxed);
+    Ok(self.wall())
+  }
+
+  pub(crate) fn set_wall(&self, unix_ms: u64) -> Result<(), String> {
+    let Clock::Manual(now) = self else {
+      return Err(not_manual());
+    };
+    let current = self.wall();
+    if unix_ms < current {
+      return Err(format!(
+        "Cannot move the clock back from {current} to {unix_ms}"
+      ));
+    }
+    now.fetch_add(unix_ms - current, Ordering::Relaxed);
+    Ok(())
+  }
+}
+
+fn not_manual() -> String {
+  "The cache clock is not manual; create the cache with { clock: 'manual' }".to_string()
+}
@@ -0,0 +1,369 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::hash::Hasher;
+
+const HASH_TAG: u8 = 0;
+const LIST_TAG: u8 = 1;
+const SET_TAG: u8 = 2;
+const SORTED_SET_TAG: u8 = 3;
+
+#[derive(Clone)]
+pub(crate) enum Collection {
+  Hash(HashFields),
+  List(ListItems),
+  Set(SetMembers),
+  SortedSet(ScoredMembers),
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct HashFields {
+  fields: BTreeMap<String, String>,
+  size: usize,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct ListItems {
+  items: VecDeque<String>,
+  size: usize,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct SetMembers {
+  members: BTreeSet<String>,
+  size: usize,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct ScoredMembers {
+  scores: HashMap<String, f64>,
+  order: Vec<(f64, String)>,
+  size: usize,
+}
+
+impl Collection {
+  pub(crate) fn len(&self) -> usize {
+    match self {
+      Collection::Hash(fields) => fields.size,
+      Collection::List(items) => items.size,
+      Collection::Set(members) => members.size,
+      Collection::SortedSet(members) => members.size,
+    }
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    match self {
+      Collection::Hash(fields) => fields.fields.is_empty(),
+      Collection::List(items) => items.items.is_empty(),
+      Collection::Set(members) => members.members.is_empty(),
+      Collection::SortedSet(members) => members.order.is_empty(),
+    }
+  }
+
+  pub(crate) fn to_json(&self) -> Value {
+    match self {
+      Collection::Hash(fields) => Value::Object(
+        fields
+          .iter()
+          .map(|(field, value)| (field.clone(), Value::String(value.clone())))
+          .collect(),
+      ),
+      Collection::List(items) => Value::Array(items.iter().cloned().map(Value::String).collect()),
+      Collection::Set(members) => Value::Object(
+        members
+          .iter()
+          .map(|member| (member.clone(), Value::Bool(true)))
+          .collect(),
+      ),
+      Collection::SortedSet(members) => Value::Array(
+        members
+          .order
+          .iter()
+          .map(|(score, member)| serde_json::json!([member, score]))
+          .collect(),
+      ),
+    }
+  }
+
+  pub(crate) fn to_json_string(&self) -> String {
+    match self {
+      Collection::Hash(fields) => serde_json::to_string(&fields.fields).unwrap_or_default(),
+      Collection::List(items) => serde_json::to_string(&items.items).unwrap_or_default(),
+      Collection::Set(_) | Collection::SortedSet(_) => self.to_json().to_string(),
+    }
+  }
+
+  pub(crate) fn digest(&self, hasher: &mut impl Hasher) {
+    match self {
+      Collection::Hash(fields) => {
+        for (field, value) in fields.iter() {
+          hasher.write(field.as_bytes());
+          hasher.write_u8(0);
+          hasher.write(value.as_bytes());
+          hasher.write_u8(0);
+        }
+      }
+      Collection::List(items) => {
+        for item in items.iter() {
+          hasher.write(item.as_bytes());
+          hasher.write_u8(0);
+        }
+      }
+      Collection::Set(members) => {
+        for member in members.iter() {
+          hasher.write(member.as_bytes());
+          hasher.write_u8(0);
+        }
+      }
+      Collection::SortedSet(members) => {
+        for (score, member) in &members.order {
+          hasher.write(member.as_bytes());
+          hasher.write_u64(score.to_bits());
+        }
+      }
+    }
+  }
+
+  pub(crate) fn encode(&self, encoded: &mut Vec<u8>) {
+    match self {
+      Collection::Hash(fields) => {
+        encoded.push(HASH_TAG);
+        let _ = serde_json::to_writer(encoded, &fields.fields);
+      }
+      Collection::List(items) => {
+        encoded.push(LIST_TAG);
+        let _ = serde_json::to_writer(encoded, &items.items);
+      }
+      Collection::Set(members) => {
+        encoded.push(SET_TAG);
+        let _ = serde_json::to_writer(encoded, &members.members);
+      }
+      Collection::SortedSet(members) => {
+        encoded.push(SORTED_SET_TAG);
+        let _ = serde_json::to_writer(encoded, &members.order);
+      }
+    }
+  }
+
+  pub(crate) fn decode(encoded: &[u8]) -> Option<Self> {
+    let (&tag, body) = encoded.split_first()?;
+    match tag {
+      HASH_TAG => {
+        let fields: BTreeMap<String, String> = serde_json::from_slice(body).ok()?;
+        Some(Collection::Hash(fields.into_iter().collect()))
+      }
+      LIST_TAG => {
+        let items: Vec<String> = serde_json::from_slice(body).ok()?;
+        Some(Collection::List(items.into_iter().collect()))
+      }
+      SET_TAG => {
+        let members: Vec<String> = serde_json::from_slice(body).ok()?;
+        Some(Collection::Set(members.into_iter().collect()))
+      }
+      SORTED_SET_TAG => {
+        let order: Vec<(f64, String)> = serde_json::from_slice(body).ok()?;
+        let mut members = ScoredMembers::default();
+        for (score, member) in order {
+          members.insert(member, score);
+        }
+        Some(Collection::SortedSet(members))
+      }
+      _ => None,
+    }
+  }
+}
+
+impl HashFields {
+  pub(crate) fn get(&self, field: &str) -> Option<&String> {
+    self.fields.get(field)
+  }
+
+  pub(crate) fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+    self.fields.iter()
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.fields.is_empty()
+  }
+
+  pub(crate) fn insert(&mut self, field: String, value: String) -> bool {
+    self.size += value.len();
+    match self.fields.get_mut(&field) {
+      Some(existing) => {
+        self.size -= existing.len();
+        *existing = value;
+        false
+      }
+      None => {
+        self.size += field.len();
+        self.fields.insert(field, value);
+        true
+      }
+    }
+  }
+
+  pub(crate) fn remove(&mut self, field: &str) -> bool {
+    let Some(value) = self.fields.remove(field) else {
+      return false;
+    };
+    self.size -= field.len() + value.len();
+    true
+  }
+}
+
+impl FromIterator<(String, String)> for HashFields {
+  fn from_iter<I: IntoIterator<Item = (String, String)>>(pairs: I) -> Self {
+    let mut fields = HashFields::default();
+    for (field, value) in pairs {
+      fields.insert(field, value);
+    }
+    fields
+  }
+}
+
+impl ListItems {
+  pub(crate) fn len(&self) -> usize {
+    self.items.len()
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.items.is_empty()
+  }
+
+  pub(crate) fn iter(&self) -> impl Iterator<Item = &String> {
+    self.items.iter()
+  }
+
+  pub(crate) fn range(&self, skip: usize, take: usize) -> impl Iterator<Item = &String> {
+    self.items.range(skip..skip + take)
+  }
+
+  pub(crate) fn push(&mut self, item: String, front: bool) {
+    self.size += item.len();
+    if front {
+      self.items.push_front(item);
+    } else {
+      self.items.push_back(item);
+    }
+  }
+
+  pub(crate) fn pop(&mut self, front: bool) -> Option<String> {
+    let item = if front {
+      self.items.pop_front()
+    } else {
+      self.items.pop_back()
+    }?;
+    self.size -= item.len();
+    Some(item)
+  }
+}
+
+impl FromIterator<String> for ListItems {
+  fn from_iter<I: IntoIterator<Item = String>>(items: I) -> Self {
+    let mut list = ListItems::default();
+    for item in items {
+      list.push(item, false);
+    }
+    list
+  }
+}
+
+impl SetMembers {
+  pub(crate) fn contains(&self, member: &str) -> bool {
+    self.members.contains(member)
+  }
+
+  pub(crate) fn iter(&self) -> impl Iterator<Item = &String> {
+    self.members.iter()
+  }
+
+  pub(crate) fn len(&self) -> usize {
+    self.members.len()
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.members.is_empty()
+  }
+
+  pub(crate) fn insert(&mut self, member: String) -> bool {
+    let size = member.len();
+    let added = self.members.insert(member);
+    if added {
+      self.size += size;
+    }
+    added
+  }
+
+  pub(crate) fn remove(&mut self, member: &str) -> bool {
+    let removed = self.members.remove(member);
+    if removed {
+      self.size -= member.len();
+    }
+    removed
+  }
+}
+
+impl FromIterator<String> for SetMembers {
+  fn from_iter<I: IntoIterator<Item = String>>(members: I) -> Self {
+    let mut set = SetMembers::default();
+    for member in members {
+      set.insert(member);
+    }
+    set
+  }
+}
+
+impl ScoredMembers {
+  pub(crate) fn len(&self) -> usize {
+    self.order.len()
+  }
+
+  pub(crate) fn score(&self, member: &str) -> Option<f64> {
+    self.scores.get(member).copied()
+  }
+
+  pub(crate) fn insert(&mut self, member: String, score: f64) -> Option<f64> {
+    let previous = self.remove(&member);
+    let index = self.position(score, &member).unwrap_or_else(|index| index);
+    self.size += member.len() + 8;
+    self.order.insert(index, (score, member.clone()));
+    self.scores.insert(member, score);
+    previous
+  }
+
+  pub(crate) fn remove(&mut self, member: &str) -> Option<f64> {
+    let score = self.scores.remove(member)?;
+    if let Ok(index) = self.position(score, member) {
+      self.order.remove(index);
+    }
+    self.size -= member.len() + 8;
+    Some(score)
+  }
+
+  pub(crate) fn rank(&self, member: &str) -> Option<usize> {
+    self.position(self.score(member)?, member).ok()
+  }
+
+  pub(crate) fn range(&self, skip: usize, take: usize, reverse: bool) -> Vec<(String, f64)> {
+    let pick = |(score, member): &(f64, String)| (member.clone(), *score);
+    if reverse {
+      self
+        .order
+        .iter()
+        .rev()
+        .skip(skip)
+        .take(take)
+        .map(pick)
+        .collect()
+    } else {
+      self.order.iter().skip(skip).take(take).map(pick).collect()
+    }
+  }
+
+  fn position(&self, score: f64, member: &str) -> Result<usize, usize> {
+    self.order.binary_search_by(|(existing_score, existing)| {
+      existing_score
+        .total_cmp(&score)
+        .then_with(|| existing.as_str().cmp(member))
+    })
+  }
+}
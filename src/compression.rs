@@ -0,0 +1,50 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::io::{self, Read, Write};
+
+#[derive(Clone, Copy)]
+pub(crate) enum Codec {
+  Lz4,
+  Zstd,
+  Gzip,
+}
+
+impl Codec {
+  pub(crate) fn tag(self) -> u8 {
+    self as u8
+  }
+
+  pub(crate) fn from_tag(tag: u8) -> Option<Self> {
+    match tag {
+      0 => Some(Codec::Lz4),
+      1 => Some(Codec::Zstd),
+      2 => Some(Codec::Gzip),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+    match self {
+      Codec::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+      Codec::Zstd => zstd::encode_all(data, 3),
+      Codec::Gzip => {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data)?;
+        encoder.finish()
+      }
+    }
+  }
+
+  pub(crate) fn decompress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+    match self {
+      Codec::Lz4 => lz4_flex::decompress_size_prepended(data)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+      Codec::Zstd => zstd::decode_all(data),
+      Codec::Gzip => {
+        let mut decoded = Vec::new();
+        GzDecoder::new(data).read_to_end(&mut decoded)?;
+        Ok(decoded)
+      }
+    }
+  }
+}
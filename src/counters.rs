@@ -0,0 +1,138 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+macro_rules! stats {
+  ($($stat:ident => $name:literal,)+) => {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub(crate) enum Stat {
+      $($stat,)+
+    }
+
+    impl Stat {
+      pub(crate) const ALL: &[Stat] = &[$(Stat::$stat,)+];
+
+      pub(crate) fn name(self) -> &'static str {
+        match self {
+          $(Stat::$stat => $name,)+
+        }
+      }
+    }
+  };
+}
+
+stats! {
+  AppendLogCompactions => "append_log_compactions",
+  AppendLogFailures => "append_log_failures",
+  AppendLogTruncatedBytes => "append_log_truncated_bytes",
+  BloomHits => "bloom_hits",
+  CasConflicts => "cas_conflicts",
+  ChangeStreamDropped => "change_stream_dropped",
+  CircuitRejections => "circuit_rejections",
+  CircuitsOpened => "circuits_opened",
+  CoalescedLoads => "coalesced_loads",
+  CompressedValues => "compressed_values",
+  CompressionInputBytes => "compression_input_bytes",
+  CompressionOutputBytes => "compression_output_bytes",
+  CorruptValues => "corrupt_values",
+  Deletes => "deletes",
+  DependencyInvalidations => "dependency_invalidations",
+  DiskFailures => "disk_failures",
+  DiskPromotions => "disk_promotions",
+  DiskSpills => "disk_spills",
+  EarlyExpirations => "early_expirations",
+  Evictions => "evictions",
+  ExpiredHits => "expired_hits",
+  Fallbacks => "fallbacks",
+  FillLeases => "fill_leases",
+  FillLeasesLost => "fill_leases_lost",
+  Flushes => "flushes",
+  Hits => "hits",
+  HttpUncacheable => "http_uncacheable",
+  InvalidatedEntries => "invalidated_entries",
+  InvalidationFailures => "invalidation_failures",
+  InvalidationReconnects => "invalidation_reconnects",
+  InvalidationsReceived => "invalidations_received",
+  InvalidationsSent => "invalidations_sent",
+  LastSnapshotAt => "last_snapshot_at",
+  LastSnapshotBytes => "last_snapshot_bytes",
+  LastSnapshotEntries => "last_snapshot_entries",
+  LastSweepAt => "last_sweep_at",
+  LastSweepDurationUs => "last_sweep_duration_us",
+  LoaderRetries => "loader_retries",
+  Loads => "loads",
+  LocksAcquired => "locks_acquired",
+  LocksContended => "locks_contended",
+  Misses => "misses",
+  NegativeHits => "negative_hits",
+  NegativeSets => "negative_sets",
+  NotModified => "not_modified",
+  PressureEvictions => "pressure_evictions",
+  RateLimitAllowed => "rate_limit_allowed",
+  RateLimitRejected => "rate_limit_rejected",
+  Reconfigurations => "reconfigurations",
+  ReencryptedValues => "reencrypted_values",
+  RefreshFailures => "refresh_failures",
+  Refreshes => "refreshes",
+  RemoteFailures => "remote_failures",
+  RemoteHits => "remote_hits",
+  RemoteMisses => "remote_misses",
+  RemoteWrites => "remote_writes",
+  Sets => "sets",
+  SnapshotFailures => "snapshot_failures",
+  Snapshots => "snapshots",
+  StaleGenerationEntries => "stale_generation_entries",
+  StaleHits => "stale_hits",
+  Sweeps => "sweeps",
+  SweptEntries => "swept_entries",
+  WriteBehindBatches => "write_behind_batches",
+  WriteBehindDropped => "write_behind_dropped",
+  WriteBehindFailures => "write_behind_failures",
+  WriteBehindFlushed => "write_behind_flushed",
+  WriteBehindQueued => "write_behind_queued",
+  WriteThroughFailures => "write_through_failures",
+  WriteThroughWrites => "write_through_writes",
+}
+
+#[repr(align(64))]
+#[derive(Default)]
+struct Counter(AtomicU64);
+
+pub(crate) struct Counters {
+  values: [Counter; Stat::ALL.len()],
+}
+
+impl Default for Counters {
+  fn default() -> Self {
+    Self {
+      values: std::array::from_fn(|_| Counter::default()),
+    }
+  }
+}
+
+impl Counters {
+  pub(crate) fn add(&self, stat: Stat, amount: u64) {
+    self.values[stat as usize]
+      .0
+      .fetch_add(amount, Ordering::Relaxed);
+  }
+
+  pub(crate) fn set(&self, stat: Stat, value: u64) {
+    self.values[stat as usize].0.store(value, Ordering::Relaxed);
+  }
+
+  pub(crate) fn get(&self, stat: Stat) -> u64 {
+    self.values[stat as usize].0.load(Ordering::Relaxed)
+  }
+
+  pub(crate) fn iter(&self) -> impl Iterator<Item = (Stat, u64)> + '_ {
+    Stat::ALL
+      .iter()
+      .map(|stat| (*stat, self.get(*stat)))
+      .filter(|(_, value)| *value > 0)
+  }
+
+  pub(crate) fn reset(&self) {
+    for counter in &self.values {
+      counter.0.store(0, Ordering::Relaxed);
+    }
+  }
+}
@@ -0,0 +1,95 @@
+use crate::CacheEntry;
+use dashmap::DashMap;
+use std::path::Path;
+
+const META_TREE: &str = "meta";
+const HASHER_KEY: &str = "key_hash";
+
+struct DiskKey {
+  original_key: String,
+  tags: Vec<String>,
+  expires_at: Option<u64>,
+}
+
+pub(crate) struct DiskTier {
+  db: sled::Db,
+  index: DashMap<String, DiskKey>,
+}
+
+impl DiskTier {
+  pub(crate) fn open(path: &Path, hasher: &str) -> sled::Result<Self> {
+    let db = sled::open(path)?;
+    let meta = db.open_tree(META_TREE)?;
+    if let Some(written) = meta.get(HASHER_KEY)? {
+      if written != hasher.as_bytes() {
+        return Err(sled::Error::Unsupported(format!(
+          "written with key hash '{}', this cache uses '{hasher}'",
+          String::from_utf8_lossy(&written)
+        )));
+      }
+    }
+    db.clear()?;
+    meta.insert(HASHER_KEY, hasher.as_bytes())?;
+    Ok(Self {
+      db,
+      index: DashMap::new(),
+    })
+  }
+
+  pub(crate) fn put(&self, key_hash: &str, entry: &CacheEntry) -> sled::Result<()> {
+    let encoded =
+      bincode::serialize(entry).map_err(|err| sled::Error::Unsupported(err.to_string()))?;
+    self.db.insert(key_hash, encoded)?;
+    self.index.insert(
+      key_hash.to_string(),
+      DiskKey {
+        original_key: entry.original_key.clone(),
+        tags: entry.tags.clone(),
+        expires_at: entry.expires_at,
+      },
+    );
+    Ok(())
+  }
+
+  pub(crate) fn get(&self, key_hash: &str) -> Option<CacheEntry> {
+    let encoded = self.db.get(key_hash).ok()??;
+    bincode::deserialize(&encoded).ok()
+  }
+
+  pub(crate) fn take(&self, key_hash: &str) -> Option<CacheEntry> {
+    self.index.remove(key_hash);
+    let encoded = self.db.remove(key_hash).ok()??;
+    bincode::deserialize(&encoded).ok()
+  }
+
+  pub(crate) fn remove(&self, key_hash: &str) {
+    self.index.remove(key_hash);
+    let _ = self.db.remove(key_hash);
+  }
+
+  pub(crate) fn keys(&self, now: u64) -> Vec<(String, String)> {
+    self.live_keys(now, |_| true)
+  }
+
+  pub(crate) fn tagged(&self, tag: &str, now: u64) -> Vec<(String, String)> {
+    self.live_keys(now, |key| key.tags.iter().any(|entry_tag| entry_tag == tag))
+  }
+
+  fn live_keys(&self, now: u64, predicate: impl Fn(&DiskKey) -> bool) -> Vec<(String, String)> {
+    self
+      .index
+      .iter()
+      .filter(|key| key.expires_at.is_none_or(|expires_at| expires_at >= now) && predicate(key))
+      .map(|key| (key.key().clone(), key.original_key.clone()))
+      .collect()
+  }
+
+  pub(crate) fn clear(&self) {
+    self.index.clear();
+    let _ = self.db.clear();
+  }
+
+  pub(crate) fn flush(&self) {
+    let _ = self.db.flush();
+  }
+}
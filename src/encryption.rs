@@ -0,0 +1,54 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use parking_lot::RwLock;
+
+pub(crate) const NONCE_LEN: usize = 12;
+
+pub(crate) struct Keyring {
+  ciphers: RwLock<Vec<Aes256Gcm>>,
+}
+
+impl Keyring {
+  pub(crate) fn new(key: &[u8]) -> Result<Self, String> {
+    Ok(Self {
+      ciphers: RwLock::new(vec![cipher(key)?]),
+    })
+  }
+
+  pub(crate) fn rotate(&self, key: &[u8]) -> Result<u32, String> {
+    let cipher = cipher(key)?;
+    let mut ciphers = self.ciphers.write();
+    ciphers.push(cipher);
+    Ok((ciphers.len() - 1) as u32)
+  }
+
+  pub(crate) fn current_key_id(&self) -> u32 {
+    (self.ciphers.read().len() - 1) as u32
+  }
+
+  pub(crate) fn seal(&self, plaintext: &[u8]) -> Result<(u32, [u8; NONCE_LEN], Vec<u8>), String> {
+    let ciphers = self.ciphers.read();
+    let key_id = ciphers.len() - 1;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = ciphers[key_id]
+      .encrypt(&nonce, plaintext)
+      .map_err(|_| format!("Value of {} bytes could not be encrypted", plaintext.len()))?;
+    Ok((key_id as u32, nonce.into(), ciphertext))
+  }
+
+  pub(crate) fn open(
+    &self,
+    key_id: u32,
+    nonce: &[u8; NONCE_LEN],
+    ciphertext: &[u8],
+  ) -> Option<Vec<u8>> {
+    let ciphers = self.ciphers.read();
+    let cipher = ciphers.get(key_id as usize)?;
+    cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+  }
+}
+
+fn cipher(key: &[u8]) -> Result<Aes256Gcm, String> {
+  Aes256Gcm::new_from_slice(key)
+    .map_err(|_| format!("Encryption key must be 32 bytes, got {}", key.len()))
+}
@@ -0,0 +1,129 @@
+use crate::pattern::KeyPattern;
+use crate::{wall_millis, CacheEvent, CacheEventType, ChangeOperation, ChangeRecord, EvictedEntry};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::Status;
+use parking_lot::{Mutex, RwLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+pub(crate) type EventListener = ThreadsafeFunction<CacheEvent, (), CacheEvent, Status, false, true>;
+pub(crate) type EvictionListener =
+  ThreadsafeFunction<EvictedEntry, (), EvictedEntry, Status, false, true>;
+
+struct Subscription {
+  id: u32,
+  pattern: Option<KeyPattern>,
+  listener: Arc<EventListener>,
+}
+
+#[derive(Default)]
+struct ChangeFeed {
+  sequence: u64,
+  streams: Vec<mpsc::Sender<ChangeRecord>>,
+}
+
+#[derive(Default)]
+pub(crate) struct EventBus {
+  subscriptions: RwLock<Vec<Subscription>>,
+  next_id: AtomicU32,
+  eviction_listener: RwLock<Option<Arc<EvictionListener>>>,
+  changes: Mutex<ChangeFeed>,
+}
+
+impl EventBus {
+  pub(crate) fn subscribe(&self, listener: EventListener, pattern: Option<&str>) -> u32 {
+    let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    self.subscriptions.write().push(Subscription {
+      id,
+      pattern: pattern.map(KeyPattern::parse),
+      listener: Arc::new(listener),
+    });
+    id
+  }
+
+  pub(crate) fn unsubscribe(&self, id: u32) -> bool {
+    let mut subscriptions = self.subscriptions.write();
+    let before = subscriptions.len();
+    subscriptions.retain(|subscription| subscription.id != id);
+    subscriptions.len() != before
+  }
+
+  pub(crate) fn emit(&self, event_type: CacheEventType, key: &str) {
+    let subscriptions = self.subscriptions.read();
+    if subscriptions.is_empty() {
+      return;
+    }
+
+    let timestamp = wall_millis() as i64;
+    for subscription in subscriptions.iter() {
+      let matched = subscription
+        .pattern
+        .as_ref()
+        .is_none_or(|pattern| pattern.matches(key));
+      if matched {
+        subscription.listener.call(
+          CacheEvent {
+            event_type,
+            key: key.to_string(),
+            timestamp,
+          },
+          ThreadsafeFunctionCallMode::NonBlocking,
+        );
+      }
+    }
+  }
+
+  pub(crate) fn set_eviction_listener(&self, listener: Option<EvictionListener>) -> bool {
+    let previous = std::mem::replace(&mut *self.eviction_listener.write(), listener.map(Arc::new));
+    previous.is_some()
+  }
+
+  pub(crate) fn has_eviction_listener(&self) -> bool {
+    self.eviction_listener.read().is_some()
+  }
+
+  pub(crate) fn emit_eviction(&self, entry: EvictedEntry) {
+    if let Some(listener) = self.eviction_listener.read().as_ref() {
+      listener.call(entry, ThreadsafeFunctionCallMode::NonBlocking);
+    }
+  }
+
+  pub(crate) fn open_change_stream(&self, capacity: usize) -> mpsc::Receiver<ChangeRecord> {
+    let (sender, receiver) = mpsc::channel(capacity.max(1));
+    self.changes.lock().streams.push(sender);
+    receiver
+  }
+
+  pub(crate) fn has_change_streams(&self) -> bool {
+    !self.changes.lock().streams.is_empty()
+  }
+
+  pub(crate) fn emit_change(&self, op: ChangeOperation, key: &str, value: Option<String>) -> u64 {
+    let mut changes = self.changes.lock();
+    if changes.streams.is_empty() {
+      return 0;
+    }
+
+    changes.sequence += 1;
+    let record = ChangeRecord {
+      sequence: changes.sequence as i64,
+      op,
+      key: key.to_string(),
+      value,
+      timestamp: wall_millis() as i64,
+    };
+    let mut dropped = 0;
+    changes
+      .streams
+      .retain(|stream| match stream.try_send(record.clone()) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => {
+          dropped += 1;
+          true
+        }
+        Err(TrySendError::Closed(_)) => false,
+      });
+    dropped
+  }
+}
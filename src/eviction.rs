@@ -0,0 +1,460 @@
+use crate::sketch::CountMinSketch;
+use parking_lot::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+const NIL: usize = usize::MAX;
+
+struct Node {
+  key_hash: String,
+  prev: usize,
+  next: usize,
+}
+
+#[derive(Default)]
+struct LinkedKeys {
+  nodes: Vec<Node>,
+  free: Vec<usize>,
+  index: HashMap<String, usize>,
+  head: usize,
+  tail: usize,
+}
+
+impl LinkedKeys {
+  fn new() -> Self {
+    Self {
+      head: NIL,
+      tail: NIL,
+      ..Default::default()
+    }
+  }
+
+  fn unlink(&mut self, slot: usize) {
+    let (prev, next) = (self.nodes[slot].prev, self.nodes[slot].next);
+    match prev {
+      NIL => self.head = next,
+      prev => self.nodes[prev].next = next,
+    }
+    match next {
+      NIL => self.tail = prev,
+      next => self.nodes[next].prev = prev,
+    }
+  }
+
+  fn link_front(&mut self, slot: usize) {
+    self.nodes[slot].prev = NIL;
+    self.nodes[slot].next = self.head;
+    match self.head {
+      NIL => self.tail = slot,
+      head => self.nodes[head].prev = slot,
+    }
+    self.head = slot;
+  }
+
+  fn push_front(&mut self, key_hash: &str) {
+    if self.index.contains_key(key_hash) {
+      return;
+    }
+
+    let node = Node {
+      key_hash: key_hash.to_string(),
+      prev: NIL,
+      next: NIL,
+    };
+    let slot = match self.free.pop() {
+      Some(slot) => {
+        self.nodes[slot] = node;
+        slot
+      }
+      None => {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+      }
+    };
+    self.index.insert(key_hash.to_string(), slot);
+    self.link_front(slot);
+  }
+
+  fn move_to_front(&mut self, key_hash: &str) {
+    if let Some(&slot) = self.index.get(key_hash) {
+      self.unlink(slot);
+      self.link_front(slot);
+    }
+  }
+
+  fn remove(&mut self, key_hash: &str) {
+    if let Some(slot) = self.index.remove(key_hash) {
+      self.unlink(slot);
+      self.nodes[slot].key_hash.clear();
+      self.free.push(slot);
+    }
+  }
+
+  fn contains(&self, key_hash: &str) -> bool {
+    self.index.contains_key(key_hash)
+  }
+
+  fn len(&self) -> usize {
+    self.index.len()
+  }
+
+  fn back(&self) -> Option<&str> {
+    match self.tail {
+      NIL => None,
+      tail => Some(&self.nodes[tail].key_hash),
+    }
+  }
+
+  fn pop_back(&mut self) -> Option<String> {
+    if self.tail == NIL {
+      return None;
+    }
+    let key_hash = self.nodes[self.tail].key_hash.clone();
+    self.remove(&key_hash);
+    Some(key_hash)
+  }
+}
+
+pub(crate) trait Policy: Send + Sync {
+  fn on_insert(&self, key_hash: &str);
+  fn on_access(&self, key_hash: &str);
+  fn on_remove(&self, key_hash: &str);
+  fn victim(&self) -> Option<String>;
+  fn clear(&self);
+  fn resize(&self, _capacity: usize) {}
+}
+
+pub(crate) struct Lru {
+  keys: Mutex<LinkedKeys>,
+}
+
+impl Lru {
+  pub(crate) fn new() -> Self {
+    Self {
+      keys: Mutex::new(LinkedKeys::new()),
+    }
+  }
+}
+
+impl Policy for Lru {
+  fn on_insert(&self, key_hash: &str) {
+    let mut keys = self.keys.lock();
+    keys.push_front(key_hash);
+    keys.move_to_front(key_hash);
+  }
+
+  fn on_access(&self, key_hash: &str) {
+    self.keys.lock().move_to_front(key_hash);
+  }
+
+  fn on_remove(&self, key_hash: &str) {
+    self.keys.lock().remove(key_hash);
+  }
+
+  fn victim(&self) -> Option<String> {
+    self.keys.lock().pop_back()
+  }
+
+  fn clear(&self) {
+    *self.keys.lock() = LinkedKeys::new();
+  }
+}
+
+pub(crate) struct Fifo {
+  keys: Mutex<LinkedKeys>,
+}
+
+impl Fifo {
+  pub(crate) fn new() -> Self {
+    Self {
+      keys: Mutex::new(LinkedKeys::new()),
+    }
+  }
+}
+
+impl Policy for Fifo {
+  fn on_insert(&self, key_hash: &str) {
+    self.keys.lock().push_front(key_hash);
+  }
+
+  fn on_access(&self, _key_hash: &str) {}
+
+  fn on_remove(&self, key_hash: &str) {
+    self.keys.lock().remove(key_hash);
+  }
+
+  fn victim(&self) -> Option<String> {
+    self.keys.lock().pop_back()
+  }
+
+  fn clear(&self) {
+    *self.keys.lock() = LinkedKeys::new();
+  }
+}
+
+#[derive(Default)]
+struct Frequencies {
+  ordered: BTreeSet<(u64, u64, String)>,
+  counts: HashMap<String, (u64, u64)>,
+  tick: u64,
+}
+
+impl Frequencies {
+  fn bump(&mut self, key_hash: &str, initial: bool) {
+    self.tick += 1;
+    let tick = self.tick;
+    let frequency = match self.counts.get(key_hash) {
+      Some(&(frequency, last_tick)) => {
+        self
+          .ordered
+          .remove(&(frequency, last_tick, key_hash.to_string()));
+        frequency + 1
+      }
+      None if initial => 1,
+      None => return,
+    };
+    self.counts.insert(key_hash.to_string(), (frequency, tick));
+    self.ordered.insert((frequency, tick, key_hash.to_string()));
+  }
+}
+
+#[derive(Default)]
+pub(crate) struct Lfu {
+  frequencies: Mutex<Frequencies>,
+}
+
+impl Policy for Lfu {
+  fn on_insert(&self, key_hash: &str) {
+    self.frequencies.lock().bump(key_hash, true);
+  }
+
+  fn on_access(&self, key_hash: &str) {
+    self.frequencies.lock().bump(key_hash, false);
+  }
+
+  fn on_remove(&self, key_hash: &str) {
+    let mut frequencies = self.frequencies.lock();
+    if let Some((frequency, tick)) = frequencies.counts.remove(key_hash) {
+      frequencies
+        .ordered
+        .remove(&(frequency, tick, key_hash.to_string()));
+    }
+  }
+
+  fn victim(&self) -> Option<String> {
+    let mut frequencies = self.frequencies.lock();
+    let (_, _, key_hash) = frequencies.ordered.pop_first()?;
+    frequencies.counts.remove(&key_hash);
+    Some(key_hash)
+  }
+
+  fn clear(&self) {
+    *self.frequencies.lock() = Frequencies::default();
+  }
+}
+
+#[derive(Default)]
+struct Members {
+  keys: Vec<String>,
+  index: HashMap<String, usize>,
+}
+
+impl Members {
+  fn swap_remove(&mut self, slot: usize) -> String {
+    let key_hash = self.keys.swap_remove(slot);
+    self.index.remove(&key_hash);
+    if let Some(moved) = self.keys.get(slot) {
+      self.index.insert(moved.clone(), slot);
+    }
+    key_hash
+  }
+}
+
+#[derive(Default)]
+pub(crate) struct Random {
+  members: Mutex<Members>,
+}
+
+impl Policy for Random {
+  fn on_insert(&self, key_hash: &str) {
+    let mut members = self.members.lock();
+    if !members.index.contains_key(key_hash) {
+      let slot = members.keys.len();
+      members.keys.push(key_hash.to_string());
+      members.index.insert(key_hash.to_string(), slot);
+    }
+  }
+
+  fn on_access(&self, _key_hash: &str) {}
+
+  fn on_remove(&self, key_hash: &str) {
+    let mut members = self.members.lock();
+    if let Some(&slot) = members.index.get(key_hash) {
+      members.swap_remove(slot);
+    }
+  }
+
+  fn victim(&self) -> Option<String> {
+    let mut members = self.members.lock();
+    if members.keys.is_empty() {
+      return None;
+    }
+    let slot = fastrand::usize(..members.keys.len());
+    Some(members.swap_remove(slot))
+  }
+
+  fn clear(&self) {
+    *self.members.lock() = Members::default();
+  }
+}
+
+#[derive(Clone)]
+pub(crate) struct SharedSketch(Arc<Mutex<CountMinSketch>>);
+
+impl SharedSketch {
+  pub(crate) fn new(capacity: usize) -> Self {
+    Self(Arc::new(Mutex::new(CountMinSketch::new(capacity))))
+  }
+}
+
+struct TinyLfuState {
+  window: LinkedKeys,
+  main: LinkedKeys,
+  window_capacity: usize,
+}
+
+impl TinyLfuState {
+  fn window_capacity(capacity: usize) -> usize {
+    (capacity / 100).max(1)
+  }
+
+  fn trim_window(&mut self) {
+    while self.window.len() > self.window_capacity {
+      if let Some(promoted) = self.window.pop_back() {
+        self.main.push_front(&promoted);
+      }
+    }
+  }
+}
+
+pub(crate) struct TinyLfu {
+  state: Mutex<TinyLfuState>,
+  sketch: SharedSketch,
+}
+
+impl TinyLfu {
+  pub(crate) fn new(capacity: usize, sketch: SharedSketch) -> Self {
+    Self {
+      state: Mutex::new(TinyLfuState {
+        window: LinkedKeys::new(),
+        main: LinkedKeys::new(),
+        window_capacity: TinyLfuState::window_capacity(capacity),
+      }),
+      sketch,
+    }
+  }
+}
+
+impl Policy for TinyLfu {
+  fn on_insert(&self, key_hash: &str) {
+    let state = &mut *self.state.lock();
+    self.sketch.0.lock().increment(key_hash);
+
+    if state.main.contains(key_hash) {
+      state.main.move_to_front(key_hash);
+      return;
+    }
+    state.window.push_front(key_hash);
+    state.window.move_to_front(key_hash);
+    state.trim_window();
+  }
+
+  fn on_access(&self, key_hash: &str) {
+    let state = &mut *self.state.lock();
+    self.sketch.0.lock().increment(key_hash);
+    state.window.move_to_front(key_hash);
+    state.main.move_to_front(key_hash);
+  }
+
+  fn on_remove(&self, key_hash: &str) {
+    let state = &mut *self.state.lock();
+    state.window.remove(key_hash);
+    state.main.remove(key_hash);
+  }
+
+  fn victim(&self) -> Option<String> {
+    let state = &mut *self.state.lock();
+
+    let (Some(candidate), Some(incumbent)) = (state.window.back(), state.main.back()) else {
+      return state.window.pop_back().or_else(|| state.main.pop_back());
+    };
+
+    let sketch = self.sketch.0.lock();
+    if sketch.estimate(candidate) > sketch.estimate(incumbent) {
+      drop(sketch);
+      let promoted = state.window.pop_back()?;
+      state.main.push_front(&promoted);
+      state.main.pop_back()
+    } else {
+      state.window.pop_back()
+    }
+  }
+
+  fn clear(&self) {
+    let state = &mut *self.state.lock();
+    state.window = LinkedKeys::new();
+    state.main = LinkedKeys::new();
+  }
+
+  fn resize(&self, capacity: usize) {
+    let state = &mut *self.state.lock();
+    state.window_capacity = TinyLfuState::window_capacity(capacity);
+    state.trim_window();
+  }
+}
+
+pub(crate) struct Prioritized {
+  tiers: Vec<Box<dyn Policy>>,
+}
+
+impl Prioritized {
+  pub(crate) fn new(tiers: usize, policy: impl Fn() -> Box<dyn Policy>) -> Self {
+    Self {
+      tiers: (0..tiers).map(|_| policy()).collect(),
+    }
+  }
+
+  pub(crate) fn on_insert(&self, key_hash: &str, tier: usize) {
+    for (index, policy) in self.tiers.iter().enumerate() {
+      if index == tier {
+        policy.on_insert(key_hash);
+      } else {
+        policy.on_remove(key_hash);
+      }
+    }
+  }
+
+  pub(crate) fn on_access(&self, key_hash: &str, tier: usize) {
+    self.tiers[tier].on_access(key_hash);
+  }
+
+  pub(crate) fn on_remove(&self, key_hash: &str, tier: usize) {
+    self.tiers[tier].on_remove(key_hash);
+  }
+
+  pub(crate) fn victim(&self) -> Option<String> {
+    self.tiers.iter().find_map(|policy| policy.victim())
+  }
+
+  pub(crate) fn clear(&self) {
+    for policy in &self.tiers {
+      policy.clear();
+    }
+  }
+
+  pub(crate) fn resize(&self, capacity: usize) {
+    for policy in &self.tiers {
+      policy.resize(capacity);
+    }
+  }
+}
@@ -0,0 +1,43 @@
+use parking_lot::Mutex;
+use std::collections::BTreeSet;
+
+#[derive(Default)]
+pub(crate) struct ExpiryIndex {
+  deadlines: Mutex<BTreeSet<(u64, String)>>,
+}
+
+impl ExpiryIndex {
+  pub(crate) fn schedule(&self, expires_at: Option<u64>, key_hash: &str) {
+    if let Some(expires_at) = expires_at {
+      self
+        .deadlines
+        .lock()
+        .insert((expires_at, key_hash.to_string()));
+    }
+  }
+
+  pub(crate) fn cancel(&self, expires_at: Option<u64>, key_hash: &str) {
+    if let Some(expires_at) = expires_at {
+      self
+        .deadlines
+        .lock()
+        .remove(&(expires_at, key_hash.to_string()));
+    }
+  }
+
+  pub(crate) fn drain_due(&self, now: u64) -> Vec<(u64, String)> {
+    let mut deadlines = self.deadlines.lock();
+    let pending = deadlines.split_off(&(now, String::new()));
+    std::mem::replace(&mut *deadlines, pending)
+      .into_iter()
+      .collect()
+  }
+
+  pub(crate) fn due_count(&self, now: u64) -> usize {
+    self.deadlines.lock().range(..(now, String::new())).count()
+  }
+
+  pub(crate) fn clear(&self) {
+    self.deadlines.lock().clear();
+  }
+}
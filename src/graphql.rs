@@ -0,0 +1,30 @@
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+pub(crate) fn entity_tags(document: &Value) -> Vec<String> {
+  let mut tags = BTreeSet::new();
+  collect(document, &mut tags);
+  tags.into_iter().collect()
+}
+
+fn collect(value: &Value, tags: &mut BTreeSet<String>) {
+  match value {
+    Value::Object(object) => {
+      if let Some(Value::String(typename)) = object.get("__typename") {
+        tags.insert(typename.clone());
+        match object.get("id") {
+          Some(Value::String(id)) => {
+            tags.insert(format!("{typename}:{id}"));
+          }
+          Some(Value::Number(id)) => {
+            tags.insert(format!("{typename}:{id}"));
+          }
+          _ => {}
+        }
+      }
+      object.values().for_each(|value| collect(value, tags));
+    }
+    Value::Array(items) => items.iter().for_each(|item| collect(item, tags)),
+    _ => {}
+  }
+}
@@ -0,0 +1,89 @@
+use dashmap::DashMap;
+use std::collections::HashMap;
+
+pub(crate) const NAMESPACE_SEPARATOR: char = ':';
+const MAX_GROUPS: usize = 1024;
+const OVERFLOW_GROUP: &str = "(other)";
+
+pub(crate) fn namespace_of(key: &str) -> Option<&str> {
+  key
+    .split_once(NAMESPACE_SEPARATOR)
+    .map(|(namespace, _)| namespace)
+}
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct GroupUsage {
+  pub(crate) hits: u64,
+  pub(crate) misses: u64,
+  pub(crate) entries: u64,
+  pub(crate) bytes: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct GroupCounters {
+  tags: DashMap<String, (u64, u64)>,
+  namespaces: DashMap<String, (u64, u64)>,
+}
+
+impl GroupCounters {
+  pub(crate) fn record(&self, key: &str, tags: &[String], hit: bool) {
+    let bump = |counts: &mut (u64, u64)| {
+      if hit {
+        counts.0 += 1;
+      } else {
+        counts.1 += 1;
+      }
+    };
+    if let Some(namespace) = namespace_of(key) {
+      bump(&mut bounded(&self.namespaces, namespace));
+    }
+    for tag in tags {
+      bump(&mut bounded(&self.tags, tag));
+    }
+  }
+
+  pub(crate) fn tag_usage(&self) -> HashMap<String, GroupUsage> {
+    usage(&self.tags)
+  }
+
+  pub(crate) fn namespace_usage(&self) -> HashMap<String, GroupUsage> {
+    usage(&self.namespaces)
+  }
+
+  pub(crate) fn reset(&self) {
+    self.tags.clear();
+    self.namespaces.clear();
+  }
+}
+
+fn bounded<'a>(
+  counters: &'a DashMap<String, (u64, u64)>,
+  group: &str,
+) -> dashmap::mapref::one::RefMut<'a, String, (u64, u64)> {
+  if let Some(counts) = counters.get_mut(group) {
+    return counts;
+  }
+  let group = if counters.len() < MAX_GROUPS {
+    group
+  } else {
+    OVERFLOW_GROUP
+  };
+  counters.entry(group.to_string()).or_default()
+}
+
+fn usage(counters: &DashMap<String, (u64, u64)>) -> HashMap<String, GroupUsage> {
+  counters
+    .iter()
+    .map(|counts| {
+      let (hits, misses) = *counts.value();
+      (
+        counts.key().clone(),
+        GroupUsage {
+          hits,
+          misses,
+          ..GroupUsage::default()
+        },
+      )
+    })
+    .collect()
+}
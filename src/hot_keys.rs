@@ -0,0 +1,121 @@
+use crate::sketch::{slot, DEPTH};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+const SHARDS: usize = 8;
+const WIDTH: usize = 512;
+const SAMPLE_SIZE: u64 = WIDTH as u64 * 10;
+pub(crate) const TRACKED_KEYS: usize = 128;
+
+struct State {
+  rows: [Vec<u32>; DEPTH],
+  candidates: HashMap<String, u32>,
+  floor: u32,
+  accesses: u64,
+}
+
+pub(crate) struct HotKeys {
+  shards: [Mutex<State>; SHARDS],
+}
+
+impl Default for HotKeys {
+  fn default() -> Self {
+    Self {
+      shards: std::array::from_fn(|_| Mutex::new(State::default())),
+    }
+  }
+}
+
+impl Default for State {
+  fn default() -> Self {
+    Self {
+      rows: std::array::from_fn(|_| vec![0; WIDTH]),
+      candidates: HashMap::with_capacity(TRACKED_KEYS + 1),
+      floor: 0,
+      accesses: 0,
+    }
+  }
+}
+
+impl HotKeys {
+  pub(crate) fn record(&self, key: &str) {
+    let mut state = self.shards[slot(DEPTH, key, SHARDS - 1)].lock();
+    let mut estimate = u32::MAX;
+    for row in 0..DEPTH {
+      let counter = &mut state.rows[row][slot(row, key, WIDTH - 1)];
+      *counter = counter.saturating_add(1);
+      estimate = estimate.min(*counter);
+    }
+
+    state.accesses += 1;
+    if let Some(count) = state.candidates.get_mut(key) {
+      *count = estimate;
+    } else if state.candidates.len() < TRACKED_KEYS {
+      state.candidates.insert(key.to_string(), estimate);
+    } else if estimate > state.floor {
+      state.admit(key, estimate);
+    }
+
+    if state.accesses >= SAMPLE_SIZE {
+      state.halve();
+    }
+  }
+
+  pub(crate) fn top(&self, limit: usize) -> (Vec<(String, u32)>, u64) {
+    let mut top = Vec::new();
+    let mut accesses = 0;
+    for shard in &self.shards {
+      let state = shard.lock();
+      top.extend(
+        state
+          .candidates
+          .iter()
+          .map(|(key, count)| (key.clone(), *count)),
+      );
+      accesses += state.accesses;
+    }
+    top.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top.truncate(limit);
+    (top, accesses)
+  }
+
+  pub(crate) fn reset(&self) {
+    for shard in &self.shards {
+      *shard.lock() = State::default();
+    }
+  }
+}
+
+impl State {
+  fn admit(&mut self, key: &str, estimate: u32) {
+    let Some((coldest, count)) = self
+      .candidates
+      .iter()
+      .min_by_key(|(_, count)| **count)
+      .map(|(key, count)| (key.clone(), *count))
+    else {
+      return;
+    };
+
+    if estimate > count {
+      self.candidates.remove(&coldest);
+      self.candidates.insert(key.to_string(), estimate);
+      self.floor = self.candidates.values().copied().min().unwrap_or(0);
+    } else {
+      self.floor = count;
+    }
+  }
+
+  fn halve(&mut self) {
+    for row in &mut self.rows {
+      for counter in row.iter_mut() {
+        *counter /= 2;
+      }
+    }
+    for count in self.candidates.values_mut() {
+      *count /= 2;
+    }
+    self.floor /= 2;
+    self.accesses /= 2;
+  }
+}
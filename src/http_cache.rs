@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct StoredResponse {
+  pub(crate) status: u32,
+  pub(crate) headers: HashMap<String, String>,
+  pub(crate) body: String,
+  pub(crate) fresh_until: u64,
+  pub(crate) initial_age_ms: u64,
+  #[serde(default)]
+  pub(crate) stored_at: Option<u64>,
+}
+
+pub(crate) struct Freshness {
+  pub(crate) fresh_ms: u64,
+  pub(crate) stale_ms: u64,
+  pub(crate) age_ms: u64,
+}
+
+fn header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+  headers
+    .iter()
+    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+    .map(|(_, value)| value.trim())
+}
+
+pub(crate) fn sets_cookie(headers: &HashMap<String, String>) -> bool {
+  header(headers, "set-cookie").is_some()
+}
+
+pub(crate) fn vary(headers: &HashMap<String, String>) -> Option<Vec<String>> {
+  let mut names = Vec::new();
+  for name in header(headers, "vary").unwrap_or_default().split(',') {
+    match name.trim() {
+      "" => {}
+      "*" => return None,
+      name => names.push(name.to_ascii_lowercase()),
+    }
+  }
+  names.sort();
+  names.dedup();
+  Some(names)
+}
+
+pub(crate) fn vary_key(url: &str) -> String {
+  format!("{url} vary")
+}
+
+pub(crate) fn variant_key(
+  url: &str,
+  request_headers: &HashMap<String, String>,
+  vary: &[String],
+) -> String {
+  let mut key = url.to_string();
+  for name in vary {
+    let value = header(request_headers, name).unwrap_or_default();
+    key.push_str(&format!(" {name}={value:?}"));
+  }
+  key
+}
+
+pub(crate) fn freshness(headers: &HashMap<String, String>, now: SystemTime) -> Option<Freshness> {
+  let header = |name: &str| header(headers, name);
+
+  let mut max_age = None;
+  let mut shared_max_age = None;
+  let mut stale_while_revalidate = 0;
+  for directive in header("cache-control").unwrap_or_default().split(',') {
+    let (name, value) = match directive.split_once('=') {
+      Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+      None => (directive.trim(), None),
+    };
+    let seconds = || value.and_then(|value| value.parse::<u64>().ok());
+    match name.to_ascii_lowercase().as_str() {
+      "no-store" | "no-cache" | "private" => return None,
+      "max-age" => max_age = seconds(),
+      "s-maxage" => shared_max_age = seconds(),
+      "stale-while-revalidate" => stale_while_revalidate = seconds().unwrap_or(0),
+      _ => {}
+    }
+  }
+
+  let lifetime = match shared_max_age.or(max_age) {
+    Some(seconds) => Duration::from_secs(seconds),
+    None => {
+      let expires = httpdate::parse_http_date(header("expires")?).ok()?;
+      let date = header("date")
+        .and_then(|date| httpdate::parse_http_date(date).ok())
+        .unwrap_or(now);
+      expires.duration_since(date).unwrap_or_default()
+    }
+  };
+  let age = Duration::from_secs(header("age").and_then(|age| age.parse().ok()).unwrap_or(0));
+
+  let fresh_ms = lifetime.saturating_sub(age).as_millis() as u64;
+  let stale_ms = stale_while_revalidate * 1000;
+  (fresh_ms + stale_ms > 0).then_some(Freshness {
+    fresh_ms,
+    stale_ms,
+    age_ms: age.as_millis() as u64,
+  })
+}
@@ -0,0 +1,56 @@
+use xxhash_rust::xxh3::xxh3_64;
+
+const PRECISION: u32 = 14;
+const REGISTERS: usize = 1 << PRECISION;
+const MAGIC: &[u8; 4] = b"HYLL";
+
+pub(crate) fn empty() -> Vec<u8> {
+  let mut sketch = Vec::with_capacity(MAGIC.len() + REGISTERS);
+  sketch.extend_from_slice(MAGIC);
+  sketch.resize(MAGIC.len() + REGISTERS, 0);
+  sketch
+}
+
+pub(crate) fn registers(sketch: &[u8]) -> Option<&[u8]> {
+  sketch
+    .strip_prefix(MAGIC)
+    .filter(|registers| registers.len() == REGISTERS)
+}
+
+pub(crate) fn add(sketch: &mut [u8], item: &str) -> bool {
+  let hash = xxh3_64(item.as_bytes());
+
+  let index = (hash >> (64 - PRECISION)) as usize;
+  let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+  let register = &mut sketch[MAGIC.len() + index];
+  if rank > *register {
+    *register = rank;
+    true
+  } else {
+    false
+  }
+}
+
+pub(crate) fn count(sketches: &[&[u8]]) -> u64 {
+  let mut sum = 0.0;
+  let mut zeros = 0;
+  for index in 0..REGISTERS {
+    let rank = sketches
+      .iter()
+      .map(|registers| registers[index])
+      .max()
+      .unwrap_or(0);
+    if rank == 0 {
+      zeros += 1;
+    }
+    sum += 2f64.powi(-(rank as i32));
+  }
+
+  let m = REGISTERS as f64;
+  let estimate = 0.7213 / (1.0 + 1.079 / m) * m * m / sum;
+  if estimate <= 2.5 * m && zeros > 0 {
+    (m * (m / zeros as f64).ln()).round() as u64
+  } else {
+    estimate.round() as u64
+  }
+}
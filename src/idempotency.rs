@@ -0,0 +1,111 @@
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+enum State {
+  InProgress,
+  Completed(String),
+}
+
+struct Request {
+  state: State,
+  expires_at: u64,
+}
+
+impl Request {
+  fn bytes(&self) -> u64 {
+    match &self.state {
+      State::InProgress => 0,
+      State::Completed(response) => response.len() as u64,
+    }
+  }
+}
+
+pub(crate) enum Claim {
+  Claimed,
+  InProgress,
+  Completed(String),
+}
+
+#[derive(Default)]
+pub(crate) struct IdempotencyStore {
+  requests: DashMap<String, Request>,
+  bytes: AtomicU64,
+}
+
+impl IdempotencyStore {
+  pub(crate) fn begin(&self, key: String, ttl_ms: u64, capacity: usize, now: u64) -> Option<Claim> {
+    if self.requests.len() >= capacity && !self.requests.contains_key(&key) {
+      self.purge_expired(now);
+      if self.requests.len() >= capacity {
+        return None;
+      }
+    }
+    let claim = match self.requests.entry(key) {
+      Entry::Occupied(occupied) if occupied.get().expires_at > now => match &occupied.get().state {
+        State::InProgress => Claim::InProgress,
+        State::Completed(response) => Claim::Completed(response.clone()),
+      },
+      Entry::Occupied(mut occupied) => {
+        let expired = occupied.insert(Request {
+          state: State::InProgress,
+          expires_at: now + ttl_ms,
+        });
+        self.bytes.fetch_sub(expired.bytes(), Ordering::Relaxed);
+        Claim::Claimed
+      }
+      Entry::Vacant(vacant) => {
+        vacant.insert(Request {
+          state: State::InProgress,
+          expires_at: now + ttl_ms,
+        });
+        Claim::Claimed
+      }
+    };
+    Some(claim)
+  }
+
+  pub(crate) fn complete(
+    &self,
+    key: &str,
+    response: String,
+    ttl_ms: Option<u64>,
+    now: u64,
+  ) -> bool {
+    let Some(mut request) = self.requests.get_mut(key) else {
+      return false;
+    };
+    if request.expires_at <= now || !matches!(request.state, State::InProgress) {
+      return false;
+    }
+    self
+      .bytes
+      .fetch_add(response.len() as u64, Ordering::Relaxed);
+    request.state = State::Completed(response);
+    if let Some(ttl_ms) = ttl_ms {
+      request.expires_at = now + ttl_ms;
+    }
+    true
+  }
+
+  pub(crate) fn release(&self, key: &str) -> bool {
+    self
+      .requests
+      .remove_if(key, |_, request| matches!(request.state, State::InProgress))
+      .is_some()
+  }
+
+  pub(crate) fn purge_expired(&self, now: u64) {
+    self.requests.retain(|_, request| {
+      let live = request.expires_at > now;
+      if !live {
+        self.bytes.fetch_sub(request.bytes(), Ordering::Relaxed);
+      }
+      live
+    });
+  }
+
+  pub(crate) fn bytes(&self) -> u64 {
+    self.bytes.load(Ordering::Relaxed)
+  }
+}
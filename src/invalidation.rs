@@ -0,0 +1,83 @@
+mod nats;
+mod redis;
+
+use serde::{Deserialize, Serialize};
+use std::io;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum Invalidation {
+  Key { key: String },
+  Tag { tag: String },
+  Prefix { prefix: String },
+  Generation { namespace: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Message {
+  origin: u64,
+  #[serde(flatten)]
+  invalidation: Invalidation,
+}
+
+pub(crate) type PayloadHandler = Box<dyn Fn(&[u8]) + Send + Sync>;
+
+pub(crate) enum LinkEvent {
+  Lost,
+  Restored,
+}
+
+pub(crate) type LinkHandler = Box<dyn Fn(LinkEvent) + Send + Sync>;
+
+pub(crate) trait Transport: Send + Sync {
+  fn publish(&self, payload: &[u8]) -> io::Result<()>;
+}
+
+pub(crate) struct InvalidationBus {
+  transport: Box<dyn Transport>,
+  origin: u64,
+}
+
+impl InvalidationBus {
+  pub(crate) fn connect(
+    url: &str,
+    channel: &str,
+    on_invalidation: impl Fn(Invalidation) + Send + Sync + 'static,
+    on_link: impl Fn(LinkEvent) + Send + Sync + 'static,
+  ) -> io::Result<Self> {
+    let origin = fastrand::u64(..);
+    let handler: PayloadHandler = Box::new(move |payload| {
+      if let Ok(message) = serde_json::from_slice::<Message>(payload) {
+        if message.origin != origin {
+          on_invalidation(message.invalidation);
+        }
+      }
+    });
+
+    let transport: Box<dyn Transport> = match url.split_once("://").map(|(scheme, _)| scheme) {
+      Some("redis" | "rediss" | "redis+unix") => Box::new(redis::RedisTransport::connect(
+        url,
+        channel,
+        handler,
+        Box::new(on_link),
+      )?),
+      Some("nats" | "tls") => Box::new(nats::NatsTransport::connect(url, channel, handler)?),
+      _ => {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "unsupported invalidation transport",
+        ))
+      }
+    };
+
+    Ok(Self { transport, origin })
+  }
+
+  pub(crate) fn publish(&self, invalidation: Invalidation) -> io::Result<()> {
+    let payload = serde_json::to_vec(&Message {
+      origin: self.origin,
+      invalidation,
+    })?;
+    self.transport.publish(&payload)
+  }
+}
@@ -0,0 +1,61 @@
+use super::{PayloadHandler, Transport};
+use futures_util::StreamExt;
+use napi::bindgen_prelude::{block_on, spawn};
+use std::io;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub(crate) struct NatsTransport {
+  client: async_nats::Client,
+  subject: String,
+  listener: JoinHandle<()>,
+}
+
+impl NatsTransport {
+  pub(crate) fn connect(url: &str, subject: &str, handler: PayloadHandler) -> io::Result<Self> {
+    let (client, mut subscriber) = block_on(async {
+      let client = async_nats::ConnectOptions::new()
+        .connection_timeout(CONNECT_TIMEOUT)
+        .connect(url)
+        .await
+        .map_err(io::Error::other)?;
+      let subscriber = client
+        .subscribe(subject.to_string())
+        .await
+        .map_err(io::Error::other)?;
+      client.flush().await.map_err(io::Error::other)?;
+      Ok::<_, io::Error>((client, subscriber))
+    })?;
+
+    let listener = spawn(async move {
+      while let Some(message) = subscriber.next().await {
+        handler(&message.payload);
+      }
+    });
+
+    Ok(Self {
+      client,
+      subject: subject.to_string(),
+      listener,
+    })
+  }
+}
+
+impl Transport for NatsTransport {
+  fn publish(&self, payload: &[u8]) -> io::Result<()> {
+    block_on(
+      self
+        .client
+        .publish(self.subject.clone(), payload.to_vec().into()),
+    )
+    .map_err(io::Error::other)
+  }
+}
+
+impl Drop for NatsTransport {
+  fn drop(&mut self) {
+    self.listener.abort();
+  }
+}
@@ -0,0 +1,147 @@
+use super::{LinkEvent, LinkHandler, PayloadHandler, Transport};
+use parking_lot::Mutex;
+use redis::{Client, Commands, Connection, RedisResult};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+pub(crate) struct RedisTransport {
+  client: Client,
+  publisher: Mutex<Connection>,
+  channel: String,
+  stopped: Arc<AtomicBool>,
+  listener: Option<JoinHandle<()>>,
+}
+
+impl RedisTransport {
+  pub(crate) fn connect(
+    url: &str,
+    channel: &str,
+    handler: PayloadHandler,
+    on_link: LinkHandler,
+  ) -> io::Result<Self> {
+    let client = Client::open(url).map_err(io::Error::other)?;
+    let publisher = client
+      .get_connection_with_timeout(CONNECT_TIMEOUT)
+      .map_err(io::Error::other)?;
+    let mut subscriber = client
+      .get_connection_with_timeout(CONNECT_TIMEOUT)
+      .map_err(io::Error::other)?;
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    let (ready, subscribed) = mpsc::channel();
+
+    let listener = {
+      let (client, channel) = (client.clone(), channel.to_string());
+      let stopped = stopped.clone();
+      thread::Builder::new()
+        .name("microservice-cache-invalidation".to_string())
+        .spawn(move || {
+          let mut subscribed = false;
+          let listened = listen(&mut subscriber, &channel, &stopped, &handler, || {
+            subscribed = true;
+            let _ = ready.send(Ok(()));
+          });
+          if !subscribed {
+            let _ = ready.send(listened);
+            return;
+          }
+
+          let mut backoff = MIN_BACKOFF;
+          while !stopped.load(Ordering::Relaxed) {
+            on_link(LinkEvent::Lost);
+            pause(&stopped, backoff);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            let Ok(mut subscriber) = client.get_connection_with_timeout(CONNECT_TIMEOUT) else {
+              continue;
+            };
+            let _ = listen(&mut subscriber, &channel, &stopped, &handler, || {
+              backoff = MIN_BACKOFF;
+              on_link(LinkEvent::Restored);
+            });
+          }
+        })?
+    };
+
+    let transport = Self {
+      client,
+      publisher: Mutex::new(publisher),
+      channel: channel.to_string(),
+      stopped,
+      listener: Some(listener),
+    };
+    match subscribed.recv() {
+      Ok(Ok(())) => Ok(transport),
+      Ok(Err(err)) => Err(io::Error::other(err)),
+      Err(_) => Err(io::Error::other("invalidation listener exited")),
+    }
+  }
+}
+
+fn listen(
+  connection: &mut Connection,
+  channel: &str,
+  stopped: &AtomicBool,
+  handler: &PayloadHandler,
+  subscribed: impl FnOnce(),
+) -> RedisResult<()> {
+  let mut pubsub = connection.as_pubsub();
+  pubsub.subscribe(channel)?;
+  pubsub.set_read_timeout(Some(POLL_INTERVAL))?;
+  subscribed();
+
+  while !stopped.load(Ordering::Relaxed) {
+    match pubsub.get_message() {
+      Ok(message) => handler(message.get_payload_bytes()),
+      Err(err) if err.is_timeout() => {}
+      Err(err) => return Err(err),
+    }
+  }
+  Ok(())
+}
+
+fn pause(stopped: &AtomicBool, duration: Duration) {
+  let mut remaining = duration;
+  while !remaining.is_zero() && !stopped.load(Ordering::Relaxed) {
+    let step = remaining.min(POLL_INTERVAL);
+    thread::sleep(step);
+    remaining -= step;
+  }
+}
+
+impl Transport for RedisTransport {
+  fn publish(&self, payload: &[u8]) -> io::Result<()> {
+    let mut publisher = self.publisher.lock();
+    if publisher
+      .publish::<_, _, usize>(&self.channel, payload)
+      .is_ok()
+    {
+      return Ok(());
+    }
+
+    *publisher = self
+      .client
+      .get_connection_with_timeout(CONNECT_TIMEOUT)
+      .map_err(io::Error::other)?;
+    let _: usize = publisher
+      .publish(&self.channel, payload)
+      .map_err(io::Error::other)?;
+    Ok(())
+  }
+}
+
+impl Drop for RedisTransport {
+  fn drop(&mut self) {
+    self.stopped.store(true, Ordering::Relaxed);
+    if let Some(listener) = self.listener.take() {
+      let _ = listener.join();
+    }
+  }
+}
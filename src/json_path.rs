@@ -0,0 +1,64 @@
+use serde_json::Value;
+
+enum Segment {
+  Field(String),
+  Index(usize),
+}
+
+pub(crate) struct JsonPath(Vec<Segment>);
+
+impl JsonPath {
+  pub(crate) fn parse(path: &str) -> Result<Self, String> {
+    parse_segments(path).map(JsonPath)
+  }
+
+  pub(crate) fn select<'a>(&self, document: &'a Value) -> Option<&'a Value> {
+    self
+      .0
+      .iter()
+      .try_fold(document, |current, segment| match segment {
+        Segment::Field(field) => current.get(field.as_str()),
+        Segment::Index(index) => current.get(*index),
+      })
+  }
+}
+
+fn parse_segments(path: &str) -> Result<Vec<Segment>, String> {
+  let invalid = || format!("Invalid JSON path '{path}'");
+  let mut rest = path.strip_prefix('$').ok_or_else(invalid)?;
+  let mut segments = Vec::new();
+
+  while let Some(head) = rest.chars().next() {
+    match head {
+      '.' => {
+        let end = rest[1..].find(['.', '[']).map_or(rest.len(), |end| end + 1);
+        let field = &rest[1..end];
+        if field.is_empty() {
+          return Err(invalid());
+        }
+        segments.push(Segment::Field(field.to_string()));
+        rest = &rest[end..];
+      }
+      '[' => {
+        let end = rest.find(']').ok_or_else(invalid)?;
+        let inner = &rest[1..end];
+        let quoted = inner
+          .strip_prefix('\'')
+          .and_then(|inner| inner.strip_suffix('\''))
+          .or_else(|| {
+            inner
+              .strip_prefix('"')
+              .and_then(|inner| inner.strip_suffix('"'))
+          });
+        segments.push(match quoted {
+          Some(field) => Segment::Field(field.to_string()),
+          None => Segment::Index(inner.parse().map_err(|_| invalid())?),
+        });
+        rest = &rest[end + 1..];
+      }
+      _ => return Err(invalid()),
+    }
+  }
+
+  Ok(segments)
+}
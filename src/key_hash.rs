@@ -0,0 +1,78 @@
+use crate::KeyHash;
+use fnv::FnvHasher;
+use sha2::{Digest, Sha256};
+use std::hash::{Hash, Hasher};
+use xxhash_rust::xxh3::Xxh3;
+use xxhash_rust::xxh64::Xxh64;
+
+const ALPHABET: &[u8; 64] = b"-0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ_abcdefghijklmnopqrstuvwxyz";
+const POSITION_DIGITS: usize = 10;
+const SHA256_DIGEST_BYTES: usize = 16;
+
+pub(crate) fn digest(algorithm: KeyHash, generation: Option<u64>, key: &str) -> String {
+  match algorithm {
+    KeyHash::Fnv => {
+      let mut hasher = FnvHasher::default();
+      if let Some(generation) = generation {
+        generation.hash(&mut hasher);
+      }
+      key.hash(&mut hasher);
+      encode(&hasher.finish().to_be_bytes())
+    }
+    KeyHash::Xxh64 => {
+      let mut hasher = Xxh64::new(0);
+      if let Some(generation) = generation {
+        hasher.update(&generation.to_le_bytes());
+      }
+      hasher.update(key.as_bytes());
+      encode(&hasher.digest().to_be_bytes())
+    }
+    KeyHash::Xxh3 => {
+      let mut hasher = Xxh3::new();
+      if let Some(generation) = generation {
+        hasher.update(&generation.to_le_bytes());
+      }
+      hasher.update(key.as_bytes());
+      encode(&hasher.digest().to_be_bytes())
+    }
+    KeyHash::Sha256 => {
+      let mut hasher = Sha256::new();
+      if let Some(generation) = generation {
+        hasher.update(generation.to_le_bytes());
+      }
+      hasher.update(key.as_bytes());
+      encode(&hasher.finalize()[..SHA256_DIGEST_BYTES])
+    }
+  }
+}
+
+fn encode(bytes: &[u8]) -> String {
+  let mut encoded = String::with_capacity((bytes.len() * 8).div_ceil(6));
+  let mut buffer = 0u32;
+  let mut bits = 0;
+  for byte in bytes {
+    buffer = (buffer << 8) | *byte as u32;
+    bits += 8;
+    while bits >= 6 {
+      bits -= 6;
+      encoded.push(ALPHABET[((buffer >> bits) & 0x3f) as usize] as char);
+    }
+  }
+  if bits > 0 {
+    encoded.push(ALPHABET[((buffer << (6 - bits)) & 0x3f) as usize] as char);
+  }
+  encoded
+}
+
+pub(crate) fn position(storage_key: &str, hashed: bool) -> Option<u64> {
+  if hashed {
+    let digits = storage_key.as_bytes().get(..POSITION_DIGITS)?;
+    return digits.iter().try_fold(0u64, |position, digit| {
+      let value = ALPHABET.iter().position(|candidate| candidate == digit)?;
+      Some((position << 6) | value as u64)
+    });
+  }
+  let mut hasher = FnvHasher::default();
+  storage_key.hash(&mut hasher);
+  Some(hasher.finish())
+}
@@ -0,0 +1,55 @@
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+struct Lease {
+  token: u64,
+  expires_at: u64,
+}
+
+pub(crate) enum Acquire {
+  Granted(u64),
+  Held,
+  Full,
+}
+
+#[derive(Default)]
+pub(crate) struct Leases {
+  held: DashMap<String, Lease>,
+  last_token: AtomicU64,
+}
+
+impl Leases {
+  pub(crate) fn acquire(&self, name: String, ttl_ms: u64, capacity: usize, now: u64) -> Acquire {
+    if self.held.len() >= capacity && !self.held.contains_key(&name) {
+      self.purge_expired(now);
+      if self.held.len() >= capacity {
+        return Acquire::Full;
+      }
+    }
+    match self.held.entry(name) {
+      Entry::Occupied(occupied) if occupied.get().expires_at > now => Acquire::Held,
+      entry => {
+        let token = self.last_token.fetch_add(1, Ordering::Relaxed) + 1;
+        entry.insert(Lease {
+          token,
+          expires_at: now + ttl_ms,
+        });
+        Acquire::Granted(token)
+      }
+    }
+  }
+
+  pub(crate) fn release(&self, name: &str, token: u64, now: u64) -> bool {
+    self
+      .held
+      .remove_if(name, |_, lease| {
+        lease.token == token && lease.expires_at > now
+      })
+      .is_some()
+  }
+
+  pub(crate) fn purge_expired(&self, now: u64) {
+    self.held.retain(|_, lease| lease.expires_at > now);
+  }
+}
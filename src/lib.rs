@@ -1,32 +1,302 @@
-use dashmap::DashMap;
-use fnv::FnvHasher;
+mod append_log;
+mod background;
+mod bloom;
+mod breaker;
+mod chain;
+mod clock;
+mod collection;
+mod compression;
+mod counters;
+mod disk_tier;
+mod encryption;
+mod events;
+mod eviction;
+mod expiry;
+mod graphql;
+mod groups;
+mod hot_keys;
+mod http_cache;
+mod hyperloglog;
+mod idempotency;
+mod invalidation;
+mod json_path;
+mod key_hash;
+mod lease;
+mod metrics;
+mod namespace;
+mod occupancy;
+mod pattern;
+mod rate_limit;
+mod remote_tier;
+mod retry;
+mod rolling;
+mod shared;
+mod sketch;
+mod snapshot;
+mod statsd;
+mod storage;
+mod telemetry;
+mod tenant;
+mod value;
+mod write_behind;
+
+use append_log::{AppendLog, LogQueue, Record, Replayed};
+use background::BackgroundTask;
+use bloom::BloomFilter;
+use breaker::CircuitBreaker;
+use chain::{bump, Chain, SecondLevel};
+use clock::Clock;
+use collection::{Collection, HashFields, ListItems, ScoredMembers, SetMembers};
+use compression::Codec;
+use counters::{Counters, Stat};
+use dashmap::mapref::entry::Entry;
+use dashmap::{DashMap, DashSet};
+use disk_tier::DiskTier;
+use encryption::Keyring;
+use events::{EventBus, EventListener, EvictionListener};
+use expiry::ExpiryIndex;
+use groups::{GroupCounters, GroupUsage};
+use hot_keys::HotKeys;
+use http_cache::StoredResponse;
+use idempotency::{Claim, IdempotencyStore};
+use invalidation::{Invalidation, InvalidationBus, LinkEvent};
+use json_path::JsonPath;
+use lease::{Acquire, Leases};
+use metrics::{Histogram, Latencies, Metric};
+use namespace::Namespace;
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::ThreadsafeFunction;
 use napi_derive::napi;
+use parking_lot::{Mutex, RwLock};
+use pattern::KeyPattern;
+use rate_limit::RateLimiter;
+use remote_tier::{FlushReport, RedisTier, RemoteWrite};
+use retry::RetryPolicy;
+use rolling::{RollingCounters, Window};
 use serde::{Deserialize, Serialize};
-use std::hash::{Hash, Hasher};
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use shared::SharedCache;
+use statsd::{StatsdEmitter, StatsdSample};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use storage::Storage;
+use telemetry::{OperationSpan, Telemetry, TraceParent};
+use tenant::{Quota, Tenant};
+use tokio::sync::{broadcast, mpsc};
+use value::{CacheValue, SharedBytes, SharedText};
+use write_behind::{BatchWriter, WriteBehind};
+
+type Loader = ThreadsafeFunction<(), Promise<String>, (), Status, false>;
+type KeyLoader = ThreadsafeFunction<String, Promise<String>, String, Status, false, true>;
+type Persister = ThreadsafeFunction<ChangeRecord, Promise<()>, ChangeRecord, Status, false, true>;
+type AsyncTransform =
+  ThreadsafeFunction<Option<String>, Promise<Option<String>>, Option<String>, Status, false>;
+type LoadResult = std::result::Result<String, String>;
+
+pub enum CacheErrorCode {
+  CapacityExceeded,
+  ValueTooLarge,
+  KeyTooLong,
+  SerializationFailed,
+  LockHeld,
+  VersionConflict,
+  GenericFailure,
+}
+
+impl AsRef<str> for CacheErrorCode {
+  fn as_ref(&self) -> &str {
+    match self {
+      CacheErrorCode::CapacityExceeded => "ERR_CAPACITY_EXCEEDED",
+      CacheErrorCode::ValueTooLarge => "ERR_VALUE_TOO_LARGE",
+      CacheErrorCode::KeyTooLong => "ERR_KEY_TOO_LONG",
+      CacheErrorCode::SerializationFailed => "ERR_SERIALIZATION_FAILED",
+      CacheErrorCode::LockHeld => "ERR_LOCK_HELD",
+      CacheErrorCode::VersionConflict => "ERR_VERSION_CONFLICT",
+      CacheErrorCode::GenericFailure => "GenericFailure",
+    }
+  }
+}
+
+enum LoadSlot {
+  Leader,
+  Waiter(broadcast::Receiver<LoadResult>),
+}
+
+static CLOCK: LazyLock<(Instant, u64)> = LazyLock::new(|| (Instant::now(), system_micros()));
+
+fn now_millis() -> u64 {
+  let (started, wall_us) = *CLOCK;
+  (wall_us + started.elapsed().as_micros() as u64) / 1000
+}
+
+fn wall_millis() -> u64 {
+  system_micros() / 1000
+}
+
+fn system_micros() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |elapsed| elapsed.as_micros() as u64)
+}
+
+// Skew between the wall clock and the monotonic one, rounded so that
+// two conversions of the same instant agree.
+fn to_wall_millis(at: u64) -> i64 {
+  let (started, wall_us) = *CLOCK;
+  let elapsed = started.elapsed().as_micros() as i64;
+  let skew = system_micros() as i64 - (wall_us as i64 + elapsed);
+  at as i64 + (skew + 500).div_euclid(1000)
+}
+
+fn format_etag(etag: u64) -> String {
+  format!("\"{etag:016x}\"")
+}
+
+fn coded_error(err: Error) -> Error<CacheErrorCode> {
+  Error::new(CacheErrorCode::GenericFailure, err.reason)
+}
+
+fn plain_error(err: Error<CacheErrorCode>) -> Error {
+  Error::from_reason(err.reason)
+}
+
+fn seconds_to_millis(seconds: u32) -> u64 {
+  seconds as u64 * 1000
+}
+
+fn jitter(ttl_ms: u64, percent: u64) -> u64 {
+  let band = ttl_ms * percent / 100;
+  if band == 0 {
+    return ttl_ms;
+  }
+  ttl_ms - fastrand::u64(0..=band)
+}
+
+fn range_bounds(len: usize, start: i64, stop: i64) -> Option<(usize, usize)> {
+  let len = len as i64;
+  let resolve = |index: i64| if index < 0 { len + index } else { index };
+  let start = resolve(start).max(0);
+  let stop = resolve(stop).min(len - 1);
+  (start <= stop).then(|| (start as usize, (stop - start + 1) as usize))
+}
+
+fn validate_namespace(name: &str) -> Result<()> {
+  if name.is_empty() || name.contains(groups::NAMESPACE_SEPARATOR) {
+    return Err(Error::from_reason(format!(
+      "Invalid namespace '{name}': names must be non-empty and cannot contain '{}'",
+      groups::NAMESPACE_SEPARATOR
+    )));
+  }
+  Ok(())
+}
+
+fn window_stats(window: &Window) -> WindowStats {
+  let counts = &window.counts;
+  let requests = counts.hits + counts.misses;
+  let operations = requests + counts.sets + counts.deletes;
+  WindowStats {
+    window_seconds: window.seconds as u32,
+    hits: counts.hits as i64,
+    misses: counts.misses as i64,
+    hit_rate: if requests > 0 {
+      counts.hits as f64 / requests as f64
+    } else {
+      0.0
+    },
+    sets: counts.sets as i64,
+    deletes: counts.deletes as i64,
+    evictions: counts.evictions as i64,
+    operations_per_second: operations as f64 / window.seconds as f64,
+  }
+}
+
+fn group_stats(usage: HashMap<String, GroupUsage>) -> HashMap<String, GroupStats> {
+  usage
+    .into_iter()
+    .map(|(name, usage)| {
+      let requests = usage.hits + usage.misses;
+      let stats = GroupStats {
+        hits: usage.hits as i64,
+        misses: usage.misses as i64,
+        hit_rate: if requests > 0 {
+          usage.hits as f64 / requests as f64
+        } else {
+          0.0
+        },
+        entries: usage.entries as u32,
+        bytes: usage.bytes as i64,
+      };
+      (name, stats)
+    })
+    .collect()
+}
+
+fn latency_stats(histogram: &Histogram) -> LatencyStats {
+  let percentiles = histogram.percentiles();
+  let micros = |elapsed: Duration| elapsed.as_nanos() as f64 / 1000.0;
+  LatencyStats {
+    count: percentiles.count as i64,
+    mean_us: micros(percentiles.mean),
+    p50_us: micros(percentiles.p50),
+    p95_us: micros(percentiles.p95),
+    p99_us: micros(percentiles.p99),
+    max_us: micros(percentiles.max),
+  }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 
 struct CacheEntry {
-  value: String,
+  value: CacheValue,
   created_at: u64,
   expires_at: Option<u64>,
   access_count: u64,
   last_accessed: u64,
   tags: Vec<String>,
   original_key: String,
+  version: u64,
+  absolute_expires_at: Option<u64>,
+  idle_timeout: Option<u64>,
+  ttl_ms: Option<u64>,
+  stale_at: Option<u64>,
+  refresh_at: Option<u64>,
+  negative: bool,
+  tenant: Option<String>,
+  priority: Priority,
+  etag: u64,
+  compute_ms: u64,
+  depends_on: Vec<String>,
 }
 
-impl CacheEntry {
-  fn new(value: String, ttl_seconds: Option<u32>, tags: Vec<String>, original_key: String) -> Self {
-    let now = SystemTime::now()
-      .duration_since(UNIX_EPOCH)
-      .unwrap()
-      .as_secs();
+const ENTRY_OVERHEAD_BYTES: usize = size_of::<String>() + size_of::<CacheEntry>();
+const LOG_FLUSH_INTERVAL: Duration = Duration::from_millis(10);
+const REMOTE_FLUSH_INTERVAL: Duration = Duration::from_millis(10);
+const DEFAULT_CHANGE_STREAM_CAPACITY: usize = 10_000;
+const PRESSURE_DECAY_MS: u64 = 30_000;
+const MIN_PRESSURE_ENTRIES: usize = 16;
+const MIN_PRESSURE_BYTES: u64 = 1 << 20;
+const MAX_COMPUTE_ATTEMPTS: usize = 8;
+
+fn live_version(entry: Option<&CacheEntry>) -> i64 {
+  entry
+    .filter(|entry| !entry.negative)
+    .map_or(0, |entry| entry.version as i64)
+}
 
-    let expires_at = ttl_seconds.map(|ttl| now + ttl as u64);
+impl CacheEntry {
+  fn new(
+    value: CacheValue,
+    ttl_ms: Option<u64>,
+    tags: Vec<String>,
+    original_key: String,
+    now: u64,
+  ) -> Self {
+    let expires_at = ttl_ms.map(|ttl| now + ttl);
 
     Self {
       value,
@@ -36,181 +306,6823 @@ impl CacheEntry {
       last_accessed: now,
       tags,
       original_key,
+      version: 0,
+      absolute_expires_at: expires_at,
+      idle_timeout: None,
+      ttl_ms: None,
+      stale_at: None,
+      refresh_at: None,
+      negative: false,
+      tenant: None,
+      priority: Priority::Normal,
+      etag: 0,
+      compute_ms: 0,
+      depends_on: Vec::new(),
+    }
+  }
+
+  fn with_refresh_ahead(mut self, percent: u64) -> Self {
+    if percent > 0 {
+      self.refresh_at = self
+        .expires_at
+        .map(|expires_at| self.created_at + (expires_at - self.created_at) * percent / 100);
+    }
+    self
+  }
+
+  fn with_stale_window(mut self, window_ms: u64) -> Self {
+    if window_ms > 0 {
+      if let Some(fresh_until) = self.expires_at {
+        self.stale_at = Some(fresh_until);
+        self.expires_at = Some(fresh_until + window_ms);
+        self.absolute_expires_at = self.expires_at;
+      }
+    }
+    self
+  }
+
+  fn is_stale(&self, now: u64) -> bool {
+    self.stale_at.is_some_and(|stale_at| now > stale_at)
+  }
+
+  fn needs_refresh(&self, now: u64) -> bool {
+    self.is_stale(now) || self.refresh_at.is_some_and(|refresh_at| now >= refresh_at)
+  }
+
+  fn with_compute_cost(mut self, elapsed: Duration) -> Self {
+    self.compute_ms = elapsed.as_millis() as u64;
+    self
+  }
+
+  fn with_priority(mut self, priority: Option<Priority>) -> Self {
+    self.priority = priority.unwrap_or_default();
+    self
+  }
+
+  fn with_dependencies(mut self, depends_on: Vec<String>) -> Self {
+    self.depends_on = depends_on;
+    self
+  }
+
+  fn with_tenant(mut self, tenant: String) -> Self {
+    self.tenant = Some(tenant);
+    self
+  }
+
+  fn with_idle_timeout(mut self, idle_timeout: Option<u64>) -> Self {
+    self.idle_timeout = idle_timeout;
+    self.slide_expiration();
+    self
+  }
+
+  fn slide_expiration(&mut self) {
+    if let Some(idle_timeout) = self.idle_timeout {
+      let idle_deadline = self.last_accessed + idle_timeout;
+      self.expires_at = Some(match self.absolute_expires_at {
+        Some(absolute) => absolute.min(idle_deadline),
+        None => idle_deadline,
+      });
+    }
+  }
+
+  fn weight(&self) -> u64 {
+    let tags: usize = self.tags.iter().map(String::len).sum();
+    (self.original_key.len() + self.value.len() + tags) as u64
+  }
+
+  fn metadata(&self) -> EntryMetadata {
+    EntryMetadata {
+      created_at: to_wall_millis(self.created_at),
+      last_accessed: to_wall_millis(self.last_accessed),
+      expires_at: self.expires_at.map(to_wall_millis),
+      access_count: self.access_count as i64,
+      version: self.version as i64,
+      tags: self.tags.clone(),
+      bytes: self.weight() as i64,
+      priority: self.priority,
+      etag: format_etag(self.etag),
     }
   }
 
-  fn is_expired(&self) -> bool {
+  fn is_expired(&self, now: u64) -> bool {
     if let Some(expires_at) = self.expires_at {
-      let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
       now > expires_at
     } else {
       false
     }
   }
 
-  fn touch(&mut self) {
+  fn set_expiry(&mut self, expires_at: Option<u64>, now: u64) {
+    self.ttl_ms = expires_at.map(|at| at.saturating_sub(now));
+    self.absolute_expires_at = expires_at;
+    self.expires_at = expires_at;
+    self.stale_at = None;
+    self.refresh_at = None;
+    self.slide_expiration();
+  }
+
+  fn touch(&mut self, now: u64) {
     self.access_count += 1;
-    self.last_accessed = SystemTime::now()
-      .duration_since(UNIX_EPOCH)
-      .unwrap()
-      .as_secs();
+    self.last_accessed = now;
+    self.slide_expiration();
   }
 }
 
-#[napi]
-pub struct MicroserviceCache {
-  storage: Arc<DashMap<String, CacheEntry>>,
-  max_size: usize,
-  default_ttl: Option<u32>,
-  stats: Arc<DashMap<String, u64>>,
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy)]
+pub enum EvictionPolicy {
+  Lru,
+  Lfu,
+  Fifo,
+  Random,
+  TinyLfu,
 }
 
-#[napi]
-impl MicroserviceCache {
-  #[napi(constructor)]
-  pub fn new(max_size: Option<u32>, default_ttl_seconds: Option<u32>) -> Self {
-    Self {
-      storage: Arc::new(DashMap::new()),
-      max_size: max_size.unwrap_or(10000) as usize,
-      default_ttl: default_ttl_seconds,
-      stats: Arc::new(DashMap::new()),
-    }
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Priority {
+  Low,
+  #[default]
+  Normal,
+  High,
+}
+
+impl Priority {
+  const TIERS: usize = 3;
+
+  fn tier(self) -> usize {
+    self as usize
   }
+}
 
-  #[napi]
-  pub fn set(
-    &self,
-    key: String,
-    value: String,
-    ttl_seconds: Option<u32>,
-    tags: Option<Vec<String>>,
-  ) -> Result<bool> {
-    let key_hash = self.hash_key(&key);
-    let effective_ttl = ttl_seconds.or(self.default_ttl);
-    let tags = tags.unwrap_or_default();
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy, Default, PartialEq, PartialOrd)]
+pub enum MemoryPressure {
+  #[default]
+  None,
+  Moderate,
+  Critical,
+}
 
-    let entry = CacheEntry::new(value, effective_ttl, tags, key.clone());
+impl MemoryPressure {
+  fn retained_share(self) -> f64 {
+    match self {
+      MemoryPressure::None => 1.0,
+      MemoryPressure::Moderate => 0.75,
+      MemoryPressure::Critical => 0.5,
+    }
+  }
 
-    if self.storage.len() >= self.max_size {
-      self.evict_lru()?;
+  fn relaxed(self, steps: u64) -> Self {
+    match (self, steps) {
+      (level, 0) => level,
+      (MemoryPressure::Critical, 1) => MemoryPressure::Moderate,
+      _ => MemoryPressure::None,
     }
+  }
+}
 
-    self.storage.insert(key_hash, entry);
-    self.increment_stat("sets");
+#[napi]
+pub struct CacheMiss {}
 
-    Ok(true)
-  }
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy, Default)]
+pub enum KeyHash {
+  #[default]
+  Fnv,
+  Xxh64,
+  Xxh3,
+  Sha256,
+}
 
-  #[napi]
-  pub fn get(&self, key: String) -> Option<String> {
-    let key_hash = self.hash_key(&key);
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy, Default)]
+pub enum ClockMode {
+  #[default]
+  System,
+  Manual,
+}
 
-    if let Some(mut entry_ref) = self.storage.get_mut(&key_hash) {
-      if entry_ref.is_expired() {
-        drop(entry_ref);
-        self.storage.remove(&key_hash);
-        self.increment_stat("expired_hits");
-        return None;
-      }
+#[napi(string_enum = "lowercase")]
+pub enum Compression {
+  Lz4,
+  Zstd,
+  Gzip,
+}
 
-      entry_ref.touch();
-      let value = entry_ref.value.clone();
-      self.increment_stat("hits");
-      Some(value)
-    } else {
-      self.increment_stat("misses");
-      None
-    }
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy)]
+pub enum CacheEventType {
+  Set,
+  Delete,
+  Expire,
+  Evict,
+}
+
+#[napi(object)]
+pub struct CacheEvent {
+  #[napi(js_name = "type")]
+  pub event_type: CacheEventType,
+  pub key: String,
+  pub timestamp: i64,
+}
+
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy)]
+pub enum EvictionReason {
+  Capacity,
+  Ttl,
+  Delete,
+  Flush,
+}
+
+#[napi(object)]
+pub struct EvictedEntry {
+  pub key: String,
+  pub value: Option<String>,
+  pub reason: EvictionReason,
+}
+
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy)]
+pub enum ChangeOperation {
+  Set,
+  Delete,
+  Expire,
+  Evict,
+  Flush,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct ChangeRecord {
+  pub sequence: i64,
+  pub op: ChangeOperation,
+  pub key: String,
+  pub value: Option<String>,
+  pub timestamp: i64,
+}
+
+#[napi(async_iterator)]
+pub struct ChangeStream {
+  records: Arc<tokio::sync::Mutex<mpsc::Receiver<ChangeRecord>>>,
+}
+
+#[napi]
+impl AsyncGenerator for ChangeStream {
+  type Yield = ChangeRecord;
+  type Next = ();
+  type Return = ();
+
+  fn next(
+    &mut self,
+    _value: Option<()>,
+  ) -> impl Future<Output = Result<Option<ChangeRecord>>> + Send + 'static {
+    let records = self.records.clone();
+    async move { Ok(records.lock().await.recv().await) }
   }
 
-  #[napi]
-  pub fn delete(&self, key: String) -> bool {
-    let key_hash = self.hash_key(&key);
-    let removed = self.storage.remove(&key_hash).is_some();
-    if removed {
-      self.increment_stat("deletes");
+  fn complete(
+    &mut self,
+    _value: Option<()>,
+  ) -> impl Future<Output = Result<Option<ChangeRecord>>> + Send + 'static {
+    let records = self.records.clone();
+    async move {
+      records.lock().await.close();
+      Ok(None)
     }
-    removed
   }
+}
 
-  #[napi]
-  pub fn get_stats(&self) -> String {
-    let total_keys = self.storage.len();
-    let mut stats = std::collections::HashMap::new();
-
-    stats.insert("total_keys".to_string(), total_keys as u64);
-    stats.insert("max_size".to_string(), self.max_size as u64);
+#[napi(object)]
+pub struct EntryMetadata {
+  pub created_at: i64,
+  pub last_accessed: i64,
+  pub expires_at: Option<i64>,
+  pub access_count: i64,
+  pub version: i64,
+  pub tags: Vec<String>,
+  pub bytes: i64,
+  pub priority: Priority,
+  pub etag: String,
+}
 
-    for entry in self.stats.iter() {
-      stats.insert(entry.key().clone(), *entry.value());
-    }
+#[napi(object)]
+pub struct EntryRecord {
+  pub key: String,
+  pub value: Option<String>,
+  pub metadata: EntryMetadata,
+}
 
-    let hits = stats.get("hits").unwrap_or(&0);
-    let misses = stats.get("misses").unwrap_or(&0);
-    let total_requests = hits + misses;
+#[derive(Clone, Copy, Default)]
+struct ScanCursor {
+  shard: usize,
+  after: u64,
+}
 
-    if total_requests > 0 {
-      let hit_rate = (*hits as f64 / total_requests as f64 * 100.0) as u64;
-      stats.insert("hit_rate_percent".to_string(), hit_rate);
+impl ScanCursor {
+  fn parse(cursor: &str) -> Option<Self> {
+    if cursor == "0" {
+      return Some(Self::default());
     }
+    let (shard, after) = cursor.split_once('-')?;
+    Some(Self {
+      shard: usize::from_str_radix(shard, 16).ok()?,
+      after: u64::from_str_radix(after, 16).ok()?,
+    })
+  }
 
-    serde_json::to_string(&stats).unwrap_or_default()
+  fn format(cursor: Option<Self>) -> String {
+    match cursor {
+      Some(Self { shard, after }) => format!("{shard:x}-{after:x}"),
+      None => "0".to_string(),
+    }
   }
+}
 
-  #[napi]
-  pub fn keys(&self) -> Vec<String> {
-    let mut keys = Vec::new();
+#[napi(async_iterator)]
+pub struct EntryStream {
+  core: CacheCore,
+  pattern: Option<KeyPattern>,
+  batch_size: usize,
+  cursor: Option<ScanCursor>,
+  on_disk: Option<VecDeque<String>>,
+  pending: VecDeque<EntryRecord>,
+}
 
-    for entry in self.storage.iter() {
-      if !entry.is_expired() {
-        keys.push(entry.original_key.clone());
+#[napi]
+impl AsyncGenerator for EntryStream {
+  type Yield = EntryRecord;
+  type Next = ();
+  type Return = ();
+
+  fn next(
+    &mut self,
+    _value: Option<()>,
+  ) -> impl Future<Output = Result<Option<EntryRecord>>> + Send + 'static {
+    while self.pending.is_empty() {
+      if let Some(after) = self.cursor {
+        let (page, cursor) = self
+          .core
+          .scan_page(after, self.pattern.as_ref(), self.batch_size);
+        self.cursor = cursor;
+        self.pending.extend(
+          page
+            .iter()
+            .filter_map(|key_hash| self.core.entry_record(key_hash)),
+        );
+        continue;
+      }
+      let on_disk = self
+        .on_disk
+        .get_or_insert_with(|| self.core.disk_scan(self.pattern.as_ref()));
+      if on_disk.is_empty() {
+        break;
       }
+      let batch: Vec<String> = on_disk
+        .drain(..self.batch_size.min(on_disk.len()))
+        .collect();
+      self.pending.extend(
+        batch
+          .iter()
+          .filter_map(|key_hash| self.core.disk_record(key_hash)),
+      );
     }
-
-    keys
+    std::future::ready(Ok(self.pending.pop_front()))
   }
 
-  #[napi]
-  pub fn flush(&self) -> u32 {
-    let count = self.storage.len() as u32;
-    self.storage.clear();
-    self.increment_stat("flushes");
-    count
+  fn complete(
+    &mut self,
+    _value: Option<()>,
+  ) -> impl Future<Output = Result<Option<EntryRecord>>> + Send + 'static {
+    self.cursor = None;
+    self.on_disk = Some(VecDeque::new());
+    self.pending.clear();
+    std::future::ready(Ok(None))
   }
+}
 
-  fn hash_key(&self, key: &str) -> String {
-    let mut hasher = FnvHasher::default();
-    key.hash(&mut hasher);
-    format!("{:x}", hasher.finish())
-  }
+#[napi(object)]
+pub struct HttpResponse {
+  pub status: u32,
+  pub headers: HashMap<String, String>,
+  pub body: String,
+  pub age_seconds: u32,
+  pub stale: bool,
+}
 
-  fn increment_stat(&self, stat_name: &str) {
-    self
-      .stats
-      .entry(stat_name.to_string())
-      .and_modify(|v| *v += 1)
-      .or_insert(1);
-  }
+#[napi(object)]
+pub struct ConditionalValue {
+  pub not_modified: bool,
+  pub value: Option<String>,
+  pub etag: String,
+}
 
-  fn evict_lru(&self) -> Result<()> {
-    let mut oldest_key: Option<String> = None;
-    let mut oldest_time = u64::MAX;
+#[napi(string_enum = "lowercase")]
+pub enum IdempotencyStatus {
+  Claimed,
+  #[napi(value = "in-progress")]
+  InProgress,
+  Completed,
+}
 
-    for entry in self.storage.iter() {
-      if entry.last_accessed < oldest_time {
-        oldest_time = entry.last_accessed;
-        oldest_key = Some(entry.key().clone());
-      }
-    }
+#[napi(object)]
+pub struct IdempotencyClaim {
+  pub status: IdempotencyStatus,
+  pub response: Option<String>,
+}
 
-    if let Some(key) = oldest_key {
-      self.storage.remove(&key);
-      self.increment_stat("evictions");
-    }
+#[napi(string_enum = "lowercase")]
+pub enum CircuitStatus {
+  Closed,
+  Open,
+  #[napi(value = "half-open")]
+  HalfOpen,
+}
 
-    Ok(())
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum ChainDemotion {
+  #[default]
+  #[napi(value = "write-through")]
+  WriteThrough,
+  Evict,
+}
+
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy)]
+pub enum TransactionCommand {
+  Get,
+  Set,
+  Delete,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct ChainOptions {
+  pub redis_url: Option<String>,
+  pub redis_prefix: Option<String>,
+  pub disk_path: Option<String>,
+  pub ttl_seconds: Option<u32>,
+  pub l1_ttl_seconds: Option<u32>,
+  pub promote_after_hits: Option<u32>,
+  pub demotion: Option<ChainDemotion>,
+}
+
+#[napi(object)]
+pub struct ChainLevelStats {
+  pub hits: i64,
+  pub misses: i64,
+  pub writes: i64,
+  pub errors: i64,
+}
+
+#[napi(object)]
+pub struct ChainStats {
+  pub l1: ChainLevelStats,
+  pub l2: ChainLevelStats,
+  pub loader: ChainLevelStats,
+  pub promotions: i64,
+  pub demotions: i64,
+}
+
+#[napi(object)]
+pub struct CircuitState {
+  pub status: CircuitStatus,
+  pub failures: u32,
+  pub opened_at: Option<i64>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct CacheOptions {
+  pub max_size: Option<u32>,
+  pub max_bytes: Option<i64>,
+  pub default_ttl_seconds: Option<u32>,
+  pub default_tti_seconds: Option<u32>,
+  pub eviction_policy: Option<EvictionPolicy>,
+  pub ttl_jitter_percent: Option<u32>,
+  pub stale_while_revalidate_seconds: Option<u32>,
+  pub refresh_ahead_percent: Option<u32>,
+  pub negative_ttl_seconds: Option<u32>,
+  pub compression: Option<Compression>,
+  pub compression_threshold_bytes: Option<u32>,
+  pub encryption_key: Option<Buffer>,
+  pub sweeper_interval_ms: Option<u32>,
+  pub hash_keys: Option<bool>,
+  pub key_hash: Option<KeyHash>,
+  pub shard_count: Option<u32>,
+  pub initial_capacity: Option<u32>,
+  pub max_value_bytes: Option<u32>,
+  pub max_key_length: Option<u32>,
+  pub tag_ttl_seconds: Option<HashMap<String, u32>>,
+  pub clock: Option<ClockMode>,
+}
+
+#[napi(object)]
+pub struct CacheSettings {
+  pub max_size: Option<u32>,
+  pub max_bytes: Option<i64>,
+  pub default_ttl_seconds: Option<u32>,
+  pub default_tti_seconds: Option<u32>,
+  pub ttl_jitter_percent: Option<u32>,
+  pub stale_while_revalidate_seconds: Option<u32>,
+  pub refresh_ahead_percent: Option<u32>,
+  pub negative_ttl_seconds: Option<u32>,
+  pub max_value_bytes: Option<u32>,
+  pub max_key_length: Option<u32>,
+}
+
+#[napi(object)]
+pub struct ShutdownReport {
+  pub flushed: u32,
+  pub snapshot_entries: Option<u32>,
+}
+
+#[napi(object)]
+pub struct TransactionOperation {
+  pub op: TransactionCommand,
+  pub key: String,
+  pub value: Option<String>,
+  pub ttl_seconds: Option<u32>,
+  pub tags: Option<Vec<String>>,
+}
+
+#[napi(object)]
+pub struct TransactionResult {
+  pub committed: bool,
+  pub conflicts: Vec<String>,
+  pub values: Vec<Option<String>>,
+  pub versions: Vec<i64>,
+}
+
+#[napi(object)]
+pub struct VersionedValue {
+  pub value: String,
+  pub version: i64,
+}
+
+#[napi(object)]
+pub struct LeasedValue {
+  pub value: Option<String>,
+  pub stale: bool,
+  pub token: Option<i64>,
+  pub wait: bool,
+}
+
+#[napi(object)]
+pub struct RateLimitResult {
+  pub allowed: bool,
+  pub remaining: u32,
+  pub retry_after_ms: Option<i64>,
+}
+
+#[napi(object)]
+pub struct ScoredMember {
+  pub member: String,
+  pub score: f64,
+}
+
+#[napi(object)]
+pub struct ScanPage {
+  pub keys: Vec<String>,
+  pub cursor: String,
+}
+
+#[napi(object)]
+pub struct SetOptions {
+  pub tti_seconds: Option<u32>,
+  pub priority: Option<Priority>,
+}
+
+#[napi(object)]
+pub struct SetManyEntry {
+  pub key: String,
+  pub value: String,
+  pub ttl_seconds: Option<u32>,
+  pub tags: Option<Vec<String>>,
+  pub tti_seconds: Option<u32>,
+  pub priority: Option<Priority>,
+  pub expected_version: Option<i64>,
+}
+
+#[napi(object)]
+pub struct GroupStats {
+  pub hits: i64,
+  pub misses: i64,
+  pub hit_rate: f64,
+  pub entries: u32,
+  pub bytes: i64,
+}
+
+#[napi(object)]
+pub struct HotKey {
+  pub key: String,
+  pub count: i64,
+  pub share: f64,
+}
+
+#[napi(object)]
+pub struct NamespaceStats {
+  pub entries: u32,
+  pub max_size: Option<u32>,
+  pub hits: i64,
+  pub misses: i64,
+  pub hit_rate: f64,
+  pub sets: i64,
+  pub deletes: i64,
+  pub evictions: i64,
+  pub bytes: i64,
+}
+
+#[napi(object)]
+pub struct TenantStats {
+  pub entries: u32,
+  pub bytes: i64,
+  pub max_entries: Option<u32>,
+  pub max_bytes: Option<i64>,
+  pub evictions: i64,
+}
+
+#[napi(object)]
+pub struct OperationStats {
+  pub gets: i64,
+  pub sets: i64,
+  pub deletes: i64,
+  pub loads: i64,
+  pub refreshes: i64,
+  pub evictions: i64,
+  pub expirations: i64,
+  pub flushes: i64,
+}
+
+#[napi(object)]
+pub struct MemoryStats {
+  pub total_bytes: i64,
+  pub metadata_bytes: i64,
+  pub max_bytes: Option<i64>,
+  pub average_entry_bytes: f64,
+  pub compressed_values: i64,
+  pub compression_input_bytes: i64,
+  pub compression_output_bytes: i64,
+  pub compression_ratio: Option<f64>,
+}
+
+#[napi(object)]
+pub struct MemoryUsage {
+  pub live_entries: u32,
+  pub expired_entries: u32,
+  pub data_bytes: i64,
+  pub metadata_bytes: i64,
+  pub total_bytes: i64,
+  pub max_bytes: Option<i64>,
+  pub pressure: MemoryPressure,
+}
+
+#[napi(object)]
+pub struct CacheStats {
+  pub entries: u32,
+  pub live_entries: u32,
+  pub max_size: u32,
+  pub pinned: u32,
+  pub open_circuits: u32,
+  pub hits: i64,
+  pub misses: i64,
+  pub hit_rate: f64,
+  pub operations: OperationStats,
+  pub memory: MemoryStats,
+  pub latency: OperationLatencies,
+  pub windows: RollingWindows,
+  pub counters: HashMap<String, i64>,
+}
+
+#[napi(object)]
+pub struct WindowStats {
+  pub window_seconds: u32,
+  pub hits: i64,
+  pub misses: i64,
+  pub hit_rate: f64,
+  pub sets: i64,
+  pub deletes: i64,
+  pub evictions: i64,
+  pub operations_per_second: f64,
+}
+
+#[napi(object)]
+pub struct RollingWindows {
+  pub one_minute: WindowStats,
+  pub five_minutes: WindowStats,
+  pub fifteen_minutes: WindowStats,
+}
+
+#[napi(object)]
+pub struct LatencyStats {
+  pub count: i64,
+  pub mean_us: f64,
+  pub p50_us: f64,
+  pub p95_us: f64,
+  pub p99_us: f64,
+  pub max_us: f64,
+}
+
+#[napi(object)]
+pub struct OperationLatencies {
+  pub get: LatencyStats,
+  pub set: LatencyStats,
+  pub delete: LatencyStats,
+  pub loader: LatencyStats,
+}
+
+#[derive(Clone, Copy)]
+struct PressureReport {
+  level: MemoryPressure,
+  reported_at: u64,
+  baseline_bytes: u64,
+}
+
+#[derive(Clone, Copy)]
+struct PressureCap {
+  level: MemoryPressure,
+  max_entries: usize,
+  max_bytes: u64,
+}
+
+#[derive(Clone, Copy)]
+struct Limits {
+  max_size: usize,
+  max_bytes: Option<u64>,
+  default_ttl_ms: Option<u64>,
+  default_tti_ms: Option<u64>,
+  ttl_jitter_percent: u64,
+  stale_window_ms: u64,
+  refresh_ahead_percent: u64,
+  negative_ttl_ms: u64,
+  max_value_bytes: Option<u64>,
+  max_key_length: Option<usize>,
+}
+
+#[derive(Clone)]
+pub struct CacheCore {
+  storage: Arc<Storage<CacheEntry>>,
+  clock: Arc<Clock>,
+  key_hasher: Option<KeyHash>,
+  limits: Arc<RwLock<Limits>>,
+  total_bytes: Arc<AtomicU64>,
+  compression: Option<(Codec, usize)>,
+  keyring: Option<Arc<Keyring>>,
+  append_log: Arc<Mutex<Option<AppendLog>>>,
+  log_queue: Arc<LogQueue>,
+  disk_tier: Arc<RwLock<Option<DiskTier>>>,
+  remote_tier: Arc<RwLock<Option<RedisTier>>>,
+  events: Arc<EventBus>,
+  latencies: Arc<Latencies>,
+  rolling: Arc<RollingCounters>,
+  hot_keys: Arc<HotKeys>,
+  groups: Arc<GroupCounters>,
+  namespaces: Arc<DashMap<String, Arc<Namespace>>>,
+  write_through: Arc<DashMap<String, Arc<Persister>>>,
+  chains: Arc<RwLock<Vec<Weak<Chain>>>>,
+  stale_generations: Arc<AtomicBool>,
+  tenants: Arc<DashMap<String, Arc<Tenant>>>,
+  telemetry: Arc<RwLock<Option<Telemetry>>>,
+  loader: Arc<RwLock<Option<Arc<KeyLoader>>>>,
+  fallback: Arc<RwLock<Option<Arc<KeyLoader>>>>,
+  retry_policy: Arc<RwLock<Option<RetryPolicy>>>,
+  stats: Arc<Counters>,
+  inflight: Arc<DashMap<String, broadcast::Sender<LoadResult>>>,
+  remote_fetches: Arc<DashMap<String, broadcast::Sender<()>>>,
+  computing: Arc<DashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+  tag_index: Arc<DashMap<String, HashSet<String>>>,
+  tag_ttls: Arc<DashMap<String, u64>>,
+  dependents: Arc<DashMap<String, HashSet<String>>>,
+  pinned: Arc<DashSet<String>>,
+  leases: Arc<Leases>,
+  fill_leases: Arc<Leases>,
+  rate_limiter: Arc<RateLimiter>,
+  idempotency: Arc<IdempotencyStore>,
+  bloom: Arc<RwLock<Option<BloomFilter>>>,
+  breaker: Arc<RwLock<Option<CircuitBreaker>>>,
+  write_behind: Arc<RwLock<Option<Arc<WriteBehind>>>>,
+  xfetch_beta: Arc<AtomicU64>,
+  draining: Arc<AtomicBool>,
+  pressure: Arc<RwLock<Option<PressureReport>>>,
+  version_counter: Arc<AtomicU64>,
+  expiry_index: Arc<ExpiryIndex>,
+  eviction: Arc<eviction::Prioritized>,
+}
+
+#[napi]
+pub struct MicroserviceCache {
+  core: CacheCore,
+  sweeper: Mutex<Option<BackgroundTask>>,
+  autosave: Mutex<Option<BackgroundTask>>,
+  compactor: Mutex<Option<BackgroundTask>>,
+  log_writer: Mutex<Option<BackgroundTask>>,
+  remote_writer: Mutex<Option<BackgroundTask>>,
+  invalidation: Arc<Mutex<Option<InvalidationBus>>>,
+  statsd: Mutex<Option<BackgroundTask>>,
+  write_behind_flusher: Mutex<Option<BackgroundTask>>,
+}
+
+#[napi]
+pub struct CacheChain {
+  core: CacheCore,
+  chain: Arc<Chain>,
+}
+
+enum TransactionOp {
+  ExpectVersion(String, i64),
+  Get(String),
+  Set(Box<CacheEntry>),
+  Delete(String),
+}
+
+impl TransactionOp {
+  fn key(&self) -> &str {
+    match self {
+      TransactionOp::ExpectVersion(key, _)
+      | TransactionOp::Get(key)
+      | TransactionOp::Delete(key) => key,
+      TransactionOp::Set(entry) => &entry.original_key,
+    }
+  }
+}
+
+#[napi]
+pub struct CacheTransaction {
+  core: CacheCore,
+  invalidation: Arc<Mutex<Option<InvalidationBus>>>,
+  ops: Mutex<Vec<TransactionOp>>,
+}
+
+#[napi]
+pub struct CacheNamespace {
+  core: CacheCore,
+  namespace: Arc<Namespace>,
+  invalidation: Arc<Mutex<Option<InvalidationBus>>>,
+}
+
+impl Deref for MicroserviceCache {
+  type Target = CacheCore;
+
+  fn deref(&self) -> &CacheCore {
+    &self.core
+  }
+}
+
+#[napi]
+impl MicroserviceCache {
+  #[napi(constructor)]
+  pub fn new(options: Option<CacheOptions>) -> Result<Self> {
+    let CacheOptions {
+      max_size,
+      max_bytes,
+      default_ttl_seconds,
+      default_tti_seconds,
+      eviction_policy,
+      ttl_jitter_percent,
+      stale_while_revalidate_seconds,
+      refresh_ahead_percent,
+      negative_ttl_seconds,
+      compression,
+      compression_threshold_bytes,
+      encryption_key,
+      sweeper_interval_ms,
+      hash_keys,
+      key_hash,
+      shard_count,
+      initial_capacity,
+      max_value_bytes,
+      max_key_length,
+      tag_ttl_seconds,
+      clock,
+    } = options.unwrap_or_default();
+    let max_size = max_size.unwrap_or(10000) as usize;
+    let initial_capacity = initial_capacity.unwrap_or(0) as usize;
+    let storage = match shard_count.map(|count| count as usize) {
+      Some(count) if count < 2 || !count.is_power_of_two() => {
+        return Err(Error::from_reason(format!(
+          "shardCount must be a power of two greater than 1, got {count}"
+        )));
+      }
+      Some(count) => DashMap::with_capacity_and_shard_amount(initial_capacity, count),
+      None => DashMap::with_capacity(initial_capacity),
+    };
+    let keyring = encryption_key
+      .map(|key| Keyring::new(&key).map(Arc::new))
+      .transpose()
+      .map_err(Error::from_reason)?;
+    let sketch = eviction::SharedSketch::new(max_size);
+
+    let cache = Self {
+      core: CacheCore {
+        storage: Arc::new(Storage::new(storage)),
+        clock: Arc::new(Clock::new(clock.unwrap_or_default())),
+        key_hasher: hash_keys
+          .unwrap_or(true)
+          .then(|| key_hash.unwrap_or_default()),
+        limits: Arc::new(RwLock::new(Limits {
+          max_size,
+          max_bytes: max_bytes.map(|bytes| bytes.max(0) as u64),
+          default_ttl_ms: default_ttl_seconds.map(seconds_to_millis),
+          default_tti_ms: default_tti_seconds.map(seconds_to_millis),
+          ttl_jitter_percent: ttl_jitter_percent.unwrap_or(0).min(100) as u64,
+          stale_window_ms: seconds_to_millis(stale_while_revalidate_seconds.unwrap_or(0)),
+          refresh_ahead_percent: refresh_ahead_percent.unwrap_or(0).min(100) as u64,
+          negative_ttl_ms: seconds_to_millis(negative_ttl_seconds.unwrap_or(60)),
+          max_value_bytes: max_value_bytes.map(u64::from),
+          max_key_length: max_key_length.map(|length| length as usize),
+        })),
+        total_bytes: Arc::new(AtomicU64::new(0)),
+        compression: compression.map(|compression| {
+          let codec = match compression {
+            Compression::Lz4 => Codec::Lz4,
+            Compression::Zstd => Codec::Zstd,
+            Compression::Gzip => Codec::Gzip,
+          };
+          (codec, compression_threshold_bytes.unwrap_or(1024) as usize)
+        }),
+        keyring,
+        append_log: Arc::new(Mutex::new(None)),
+        log_queue: Arc::new(LogQueue::default()),
+        disk_tier: Arc::new(RwLock::new(None)),
+        remote_tier: Arc::new(RwLock::new(None)),
+        events: Arc::new(EventBus::default()),
+        latencies: Arc::new(Latencies::default()),
+        rolling: Arc::new(RollingCounters::default()),
+        hot_keys: Arc::new(HotKeys::default()),
+        groups: Arc::new(GroupCounters::default()),
+        namespaces: Arc::new(DashMap::new()),
+        write_through: Arc::new(DashMap::new()),
+        chains: Arc::new(RwLock::new(Vec::new())),
+        stale_generations: Arc::new(AtomicBool::new(false)),
+        tenants: Arc::new(DashMap::new()),
+        telemetry: Arc::new(RwLock::new(None)),
+        loader: Arc::new(RwLock::new(None)),
+        fallback: Arc::new(RwLock::new(None)),
+        retry_policy: Arc::new(RwLock::new(None)),
+        stats: Arc::new(Counters::default()),
+        inflight: Arc::new(DashMap::new()),
+        remote_fetches: Arc::new(DashMap::new()),
+        computing: Arc::new(DashMap::new()),
+        tag_index: Arc::new(DashMap::new()),
+        tag_ttls: Arc::new(
+          tag_ttl_seconds
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, seconds)| *seconds > 0)
+            .map(|(tag, seconds)| (tag, seconds_to_millis(seconds)))
+            .collect(),
+        ),
+        dependents: Arc::new(DashMap::new()),
+        pinned: Arc::new(DashSet::new()),
+        leases: Arc::new(Leases::default()),
+        fill_leases: Arc::new(Leases::default()),
+        rate_limiter: Arc::new(RateLimiter::default()),
+        idempotency: Arc::new(IdempotencyStore::default()),
+        bloom: Arc::new(RwLock::new(None)),
+        breaker: Arc::new(RwLock::new(None)),
+        write_behind: Arc::new(RwLock::new(None)),
+        xfetch_beta: Arc::new(AtomicU64::new(0)),
+        draining: Arc::new(AtomicBool::new(false)),
+        pressure: Arc::new(RwLock::new(None)),
+        version_counter: Arc::new(AtomicU64::new(0)),
+        expiry_index: Arc::new(ExpiryIndex::default()),
+        eviction: Arc::new(eviction::Prioritized::new(
+          Priority::TIERS,
+          || match eviction_policy.unwrap_or(EvictionPolicy::Lru) {
+            EvictionPolicy::Lru => Box::new(eviction::Lru::new()),
+            EvictionPolicy::Lfu => Box::new(eviction::Lfu::default()),
+            EvictionPolicy::Fifo => Box::new(eviction::Fifo::new()),
+            EvictionPolicy::Random => Box::new(eviction::Random::default()),
+            EvictionPolicy::TinyLfu => Box::new(eviction::TinyLfu::new(max_size, sketch.clone())),
+          },
+        )),
+      },
+      sweeper: Mutex::new(None),
+      autosave: Mutex::new(None),
+      compactor: Mutex::new(None),
+      log_writer: Mutex::new(None),
+      remote_writer: Mutex::new(None),
+      invalidation: Arc::new(Mutex::new(None)),
+      statsd: Mutex::new(None),
+      write_behind_flusher: Mutex::new(None),
+    };
+    if sweeper_interval_ms.is_some() {
+      cache.start_sweeper(sweeper_interval_ms)?;
+    }
+    Ok(cache)
+  }
+
+  #[napi(factory)]
+  pub fn attach(token: String) -> Result<Self> {
+    let shared = shared::find(&token)
+      .ok_or_else(|| Error::from_reason(format!("No shared cache registered as '{token}'")))?;
+    Ok(Self::from_shared(shared))
+  }
+
+  #[napi(factory)]
+  pub fn get_or_create(name: String, options: Option<CacheOptions>) -> Result<Self> {
+    if let Some(shared) = shared::find(&name) {
+      return Ok(Self::from_shared(shared));
+    }
+
+    let cache = Self::new(options)?;
+    if cache.publish(name.clone()) {
+      return Ok(cache);
+    }
+    shared::find(&name)
+      .map(Self::from_shared)
+      .ok_or_else(|| Error::from_reason(format!("No shared cache registered as '{name}'")))
+  }
+
+  #[napi]
+  pub fn share(&self, name: Option<String>) -> Result<String> {
+    let token = name.unwrap_or_else(|| format!("cache-{:016x}", fastrand::u64(..)));
+    if !self.publish(token.clone()) {
+      return Err(Error::from_reason(format!(
+        "Another cache is already shared as '{token}'"
+      )));
+    }
+    Ok(token)
+  }
+
+  #[napi]
+  pub fn unshare(&self, token: String) -> bool {
+    shared::withdraw(&token, &self.core)
+  }
+
+  #[napi]
+  pub fn advance_time(&self, ms: u32) -> Result<i64> {
+    let now = self.clock.advance(ms as u64).map_err(Error::from_reason)?;
+    Ok(now as i64)
+  }
+
+  #[napi]
+  pub fn set_time(&self, unix_ms: i64) -> Result<()> {
+    self
+      .clock
+      .set_wall(unix_ms.max(0) as u64)
+      .map_err(Error::from_reason)
+  }
+
+  #[napi]
+  pub fn set(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+    options: Option<SetOptions>,
+  ) -> Result<i64, CacheErrorCode> {
+    let (tti_seconds, priority) = options.map_or((None, None), |options| {
+      (options.tti_seconds, options.priority)
+    });
+    self.store_text(key, value, ttl_seconds, tags, tti_seconds, priority)
+  }
+
+  #[napi]
+  pub fn set_with_dependencies(
+    &self,
+    key: String,
+    value: String,
+    depends_on: Vec<String>,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64, CacheErrorCode> {
+    let depends_on = depends_on
+      .iter()
+      .map(|parent| self.hash_key(parent))
+      .collect();
+    let entry = self
+      .new_entry(key, value.into(), ttl_seconds.map(seconds_to_millis), tags)?
+      .with_dependencies(depends_on);
+    self.store_entry(entry)
+  }
+
+  #[napi]
+  pub async fn set_write_through(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64> {
+    self.ensure_writable()?;
+    let key_hash = self.hash_key(&key);
+    let persisted = async {
+      self
+        .persist_change(ChangeOperation::Set, &key, Some(value.clone()))
+        .await?;
+      self
+        .text_entry(key.clone(), value, ttl_seconds, tags, None, None)
+        .and_then(|entry| self.store_persisted(entry))
+        .map_err(plain_error)
+    };
+    self.in_turn(&key_hash, persisted).await
+  }
+
+  #[napi]
+  pub async fn delete_write_through(&self, key: String) -> Result<bool> {
+    let key_hash = self.hash_key(&key);
+    let persisted = async {
+      self
+        .persist_change(ChangeOperation::Delete, &key, None)
+        .await?;
+      self.delete(key.clone())
+    };
+    self.in_turn(&key_hash, persisted).await
+  }
+
+  #[napi]
+  pub fn set_px(
+    &self,
+    key: String,
+    value: String,
+    ttl_ms: u32,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64, CacheErrorCode> {
+    let entry = self.new_entry(key, value.into(), Some(ttl_ms as u64), tags)?;
+    self.store_entry(entry)
+  }
+
+  #[napi]
+  pub fn set_buffer(
+    &self,
+    key: String,
+    value: Buffer,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64, CacheErrorCode> {
+    let value = CacheValue::from(value.to_vec());
+    let entry = self.new_entry(key, value, ttl_seconds.map(seconds_to_millis), tags)?;
+    self.store_entry(entry)
+  }
+
+  #[napi(
+    ts_args_type = "key: string, value: any, ttlSeconds?: number | undefined | null, tags?: Array<string> | undefined | null"
+  )]
+  pub fn set_json(
+    &self,
+    key: String,
+    value: serde_json::Value,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64, CacheErrorCode> {
+    let entry = self.new_entry(key, value.into(), ttl_seconds.map(seconds_to_millis), tags)?;
+    self.store_entry(entry)
+  }
+
+  #[napi]
+  pub fn set_http_response(
+    &self,
+    key: String,
+    status: u32,
+    headers: HashMap<String, String>,
+    body: String,
+    tags: Option<Vec<String>>,
+  ) -> Result<Option<i64>, CacheErrorCode> {
+    let wall = UNIX_EPOCH + Duration::from_millis(self.clock.wall());
+    let freshness =
+      http_cache::freshness(&headers, wall).filter(|_| !http_cache::sets_cookie(&headers));
+    let Some(freshness) = freshness else {
+      self.increment_stat(Stat::HttpUncacheable);
+      return Ok(None);
+    };
+
+    let now = self.now();
+    let response = StoredResponse {
+      status,
+      headers,
+      body,
+      fresh_until: now + freshness.fresh_ms,
+      initial_age_ms: freshness.age_ms,
+      stored_at: Some(now),
+    };
+    let document = serde_json::to_value(response).map_err(|err| {
+      Error::new(
+        CacheErrorCode::SerializationFailed,
+        format!("Failed to store response '{key}': {err}"),
+      )
+    })?;
+    let ttl_ms = freshness.fresh_ms + freshness.stale_ms;
+    let entry = self.new_entry(key, document.into(), Some(ttl_ms), tags)?;
+    self.store_entry(entry).map(Some)
+  }
+
+  #[napi]
+  pub fn get_http_response(&self, key: String) -> Result<Option<HttpResponse>> {
+    let Some(Some(document)) = self.lookup(key.clone(), CacheValue::to_json) else {
+      return Ok(None);
+    };
+    let response: StoredResponse = document.and_then(serde_json::from_value).map_err(|err| {
+      Error::from_reason(format!("Value at key '{key}' is not a response: {err}"))
+    })?;
+
+    let now = self.now();
+    let age_ms = response.initial_age_ms + now.saturating_sub(response.stored_at.unwrap_or(now));
+    Ok(Some(HttpResponse {
+      status: response.status,
+      headers: response.headers,
+      body: response.body,
+      age_seconds: (age_ms / 1000) as u32,
+      stale: now > response.fresh_until,
+    }))
+  }
+
+  #[napi]
+  pub fn set_if_absent(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<bool, CacheErrorCode> {
+    let key_hash = self.hash_key(&key);
+    let entry = self.new_entry(
+      key.clone(),
+      value.into(),
+      ttl_seconds.map(seconds_to_millis),
+      tags,
+    )?;
+    self.check_entry(&entry)?;
+
+    self.read_through(&key, &key_hash);
+    if !self.has(key) {
+      self.make_room(&key_hash, entry.weight())?;
+    }
+
+    let written = self
+      .insert_entry_if(key_hash.clone(), entry, |live| live.is_none())
+      .is_some();
+    if written {
+      self.increment_stat(Stat::Sets);
+      self.mirror(&key_hash);
+    }
+    Ok(written)
+  }
+
+  #[napi]
+  pub fn set_if_present(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<bool, CacheErrorCode> {
+    let key_hash = self.hash_key(&key);
+    self.read_through(&key, &key_hash);
+    let entry = self.new_entry(key, value.into(), ttl_seconds.map(seconds_to_millis), tags)?;
+    self.check_entry(&entry)?;
+
+    let written = self
+      .insert_entry_if(key_hash.clone(), entry, |live| live.is_some())
+      .is_some();
+    if written {
+      self.increment_stat(Stat::Sets);
+      self.mirror(&key_hash);
+    }
+    Ok(written)
+  }
+
+  #[napi]
+  pub fn set_if_version(
+    &self,
+    key: String,
+    value: String,
+    expected_version: i64,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64, CacheErrorCode> {
+    let entry = self.text_entry(key, value, ttl_seconds, tags, None, None)?;
+    self.store_versioned(entry, expected_version)
+  }
+
+  #[napi(
+    ts_args_type = "key: string, transform: (current: string | null) => string | null, ttlSeconds?: number | undefined | null, tags?: Array<string> | undefined | null"
+  )]
+  pub fn compute(
+    &self,
+    key: String,
+    transform: Function<Option<String>, Option<String>>,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<Option<String>, CacheErrorCode> {
+    self.ensure_direct_write(&key).map_err(coded_error)?;
+    let key_hash = self.hash_key(&key);
+    for _ in 0..MAX_COMPUTE_ATTEMPTS {
+      let (current, version) = self.versioned_text(&key);
+      let next = transform.call(current).map_err(coded_error)?;
+
+      let locks = self.storage.lock_keys([key_hash.as_str()]);
+      if self.current_version(&key_hash) != version {
+        continue;
+      }
+      let deleted = self.apply_computed(&key, next.clone(), ttl_seconds, tags.clone())?;
+      drop(locks);
+      if deleted {
+        self.broadcast(Invalidation::Key { key });
+      }
+      return Ok(next);
+    }
+    Err(self.compute_conflict(&key))
+  }
+
+  #[napi(
+    ts_args_type = "key: string, transform: (current: string | null) => Promise<string | null>, ttlSeconds?: number | undefined | null, tags?: Array<string> | undefined | null"
+  )]
+  pub async fn compute_async(
+    &self,
+    key: String,
+    transform: AsyncTransform,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<Option<String>> {
+    self.ensure_direct_write(&key)?;
+    let key_hash = self.hash_key(&key);
+    self
+      .in_turn(
+        &key_hash,
+        self.compute_queued(&key, &key_hash, &transform, ttl_seconds, tags),
+      )
+      .await
+      .map_err(plain_error)
+  }
+
+  #[napi]
+  pub fn get_versioned(&self, key: String) -> Option<VersionedValue> {
+    self
+      .lookup_versioned(key, |value, version| {
+        Some(VersionedValue {
+          value: value.to_text()?,
+          version: version as i64,
+        })
+      })
+      .flatten()
+  }
+
+  #[napi(ts_return_type = "string | CacheMiss | null")]
+  pub fn get(&self, key: String) -> Option<Either<SharedText, CacheMiss>> {
+    self
+      .lookup(key, CacheValue::shared_text)
+      .map(|value| match value {
+        Some(value) => Either::A(value),
+        None => Either::B(CacheMiss {}),
+      })
+  }
+
+  #[napi(ts_return_type = "Buffer | null")]
+  pub fn get_buffer(&self, key: String) -> Option<SharedBytes> {
+    self.lookup(key, CacheValue::shared_bytes).flatten()
+  }
+
+  #[napi(ts_return_type = "any")]
+  pub fn get_json(&self, key: String) -> Result<Option<serde_json::Value>, CacheErrorCode> {
+    let document = self.lookup(key.clone(), CacheValue::to_json).flatten();
+    document.transpose().map_err(|err| {
+      Error::new(
+        CacheErrorCode::SerializationFailed,
+        format!("Value at key '{key}' is not valid JSON: {err}"),
+      )
+    })
+  }
+
+  #[napi(ts_return_type = "any")]
+  pub fn get_json_path(
+    &self,
+    key: String,
+    path: String,
+  ) -> Result<Option<serde_json::Value>, CacheErrorCode> {
+    let path = JsonPath::parse(&path)
+      .map_err(|reason| Error::new(CacheErrorCode::GenericFailure, reason))?;
+    let selected = self
+      .lookup(key.clone(), |value| {
+        value.with_json(|document| path.select(document).cloned())
+      })
+      .flatten();
+    selected.transpose().map(Option::flatten).map_err(|err| {
+      Error::new(
+        CacheErrorCode::SerializationFailed,
+        format!("Value at key '{key}' is not valid JSON: {err}"),
+      )
+    })
+  }
+
+  #[napi]
+  pub fn http_variant_key(
+    &self,
+    url: String,
+    request_headers: HashMap<String, String>,
+    vary: Vec<String>,
+  ) -> String {
+    let mut vary: Vec<String> = vary.iter().map(|name| name.to_ascii_lowercase()).collect();
+    vary.sort();
+    vary.dedup();
+    http_cache::variant_key(&url, &request_headers, &vary)
+  }
+
+  #[napi]
+  pub fn set_http_variant(
+    &self,
+    url: String,
+    request_headers: HashMap<String, String>,
+    status: u32,
+    headers: HashMap<String, String>,
+    body: String,
+    tags: Option<Vec<String>>,
+  ) -> Result<Option<i64>, CacheErrorCode> {
+    let Some(vary) = http_cache::vary(&headers) else {
+      self.increment_stat(Stat::HttpUncacheable);
+      return Ok(None);
+    };
+    let key = http_cache::variant_key(&url, &request_headers, &vary);
+    let Some(version) = self.set_http_response(key.clone(), status, headers, body, tags)? else {
+      return Ok(None);
+    };
+    let vary_key = http_cache::vary_key(&url);
+    let expires_at = [&key, &vary_key]
+      .into_iter()
+      .filter_map(|key| self.inspect(key, |entry| entry.expires_at))
+      .max();
+    let ttl_ms = expires_at.map(|expires_at| expires_at.saturating_sub(self.now()));
+    let entry = self.new_entry(vary_key, vary.join(",").into(), ttl_ms, None)?;
+    self.store_entry(entry)?;
+    Ok(Some(version))
+  }
+
+  #[napi]
+  pub fn get_http_variant(
+    &self,
+    url: String,
+    request_headers: HashMap<String, String>,
+  ) -> Result<Option<HttpResponse>> {
+    let Some(key) = self.http_variant_of(&url, &request_headers) else {
+      self.increment_stat(Stat::Misses);
+      return Ok(None);
+    };
+    self.get_http_response(key)
+  }
+
+  #[napi]
+  pub fn delete_http_variant(
+    &self,
+    url: String,
+    request_headers: HashMap<String, String>,
+  ) -> Result<bool> {
+    match self.http_variant_of(&url, &request_headers) {
+      Some(key) => self.delete(key),
+      None => Ok(false),
+    }
+  }
+
+  #[napi]
+  pub fn delete_http_resource(&self, url: String) -> Result<u32> {
+    self.delete(http_cache::vary_key(&url))?;
+    let variants = format!("{url} ");
+    let deleted = self.delete(url.clone())? as u32;
+    Ok(deleted + self.delete_by_prefix(variants)?)
+  }
+
+  #[napi]
+  pub fn get_if_none_match(&self, key: String, etag: String) -> Option<ConditionalValue> {
+    let value = self.lookup(key.clone(), CacheValue::to_text)??;
+    let current = self.storage.get(&self.locate(&key))?.etag;
+    let current = format_etag(current);
+    let not_modified = etag.split(',').map(str::trim).any(|candidate| {
+      candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == current
+    });
+    if not_modified {
+      self.increment_stat(Stat::NotModified);
+    }
+    Some(ConditionalValue {
+      not_modified,
+      value: (!not_modified).then_some(value),
+      etag: current,
+    })
+  }
+
+  #[napi]
+  pub fn set_negative(&self, key: String, ttl_seconds: Option<u32>) -> Result<i64, CacheErrorCode> {
+    self.ensure_direct_write(&key).map_err(coded_error)?;
+    let ttl_ms = ttl_seconds.map_or(self.limits().negative_ttl_ms, seconds_to_millis);
+    let mut entry = CacheEntry::new(
+      String::new().into(),
+      Some(ttl_ms),
+      Vec::new(),
+      key,
+      self.now(),
+    );
+    entry.negative = true;
+    self.increment_stat(Stat::NegativeSets);
+    self.store_entry(entry)
+  }
+
+  #[napi]
+  pub fn has(&self, key: String) -> bool {
+    self.inspect(&key, |_| Some(())).is_some()
+  }
+
+  #[napi]
+  pub fn peek(&self, key: String) -> Option<String> {
+    self.inspect(&key, |entry| self.read_value(entry, CacheValue::to_text))
+  }
+
+  #[napi]
+  pub fn get_entry_info(&self, key: String) -> Option<EntryMetadata> {
+    self.inspect(&key, |entry| Some(entry.metadata()))
+  }
+
+  #[napi]
+  pub fn expire(&self, key: String, ttl_seconds: u32) -> Result<bool> {
+    self.ensure_writable()?;
+    let expires_at = self.now() + seconds_to_millis(ttl_seconds);
+    Ok(self.update_expiry(&key, Some(expires_at)))
+  }
+
+  #[napi]
+  pub fn expire_at(&self, key: String, unix_timestamp: i64) -> Result<bool> {
+    self.ensure_writable()?;
+    let remaining_ms = unix_timestamp.saturating_mul(1000) - self.clock.wall() as i64;
+    let expires_at = (self.now() as i64).saturating_add(remaining_ms).max(0);
+    Ok(self.update_expiry(&key, Some(expires_at as u64)))
+  }
+
+  #[napi]
+  pub fn persist(&self, key: String) -> Result<bool> {
+    self.ensure_writable()?;
+    let has_ttl = self.inspect(&key, |entry| entry.expires_at).is_some();
+    Ok(has_ttl && self.update_expiry(&key, None))
+  }
+
+  #[napi]
+  pub fn pin(&self, key: String) -> Result<bool> {
+    let key_hash = self.locate(&key);
+    if self
+      .storage
+      .get(&key_hash)
+      .is_none_or(|entry| entry.is_expired(self.now()))
+    {
+      return Ok(false);
+    }
+    if self.pinned.contains(&key_hash) {
+      return Ok(true);
+    }
+
+    let max_pinned = (self.limits().max_size / 2).max(1);
+    if self.pinned.len() >= max_pinned {
+      return Err(Error::from_reason(format!(
+        "Failed to pin '{key}': {max_pinned} entries are already pinned"
+      )));
+    }
+    self.pinned.insert(key_hash);
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn unpin(&self, key: String) -> bool {
+    let key_hash = self.locate(&key);
+    if self.pinned.remove(&key_hash).is_none() {
+      return false;
+    }
+    if let Some(entry) = self.storage.get(&key_hash) {
+      self.eviction.on_insert(&key_hash, entry.priority.tier());
+      if let Some(namespace) = self.namespace_for(&entry.original_key) {
+        namespace.track(&key_hash);
+      }
+      if let Some(tenant) = self.tenant_for(entry.tenant.as_deref()) {
+        tenant.track(&key_hash);
+      }
+    }
+    true
+  }
+
+  #[napi]
+  pub fn is_pinned(&self, key: String) -> bool {
+    self.pinned.contains(&self.locate(&key))
+  }
+
+  #[napi]
+  pub fn touch(&self, key: String, ttl_seconds: Option<u32>) -> Result<bool> {
+    self.ensure_writable()?;
+    let key_hash = self.hash_key(&key);
+    let touched = match self.storage.get_mut(&key_hash) {
+      Some(mut entry) if !entry.is_expired(self.now()) => {
+        self.touch_entry(&key_hash, &mut entry);
+        if let Some(ttl_seconds) = ttl_seconds {
+          let expires_at = self.now() + seconds_to_millis(ttl_seconds);
+          self.reschedule(&key_hash, &mut entry, |entry| {
+            entry.set_expiry(Some(expires_at), self.now())
+          });
+          self.log_put(&entry);
+          drop(entry);
+          self.mirror(&key_hash);
+        }
+        true
+      }
+      Some(_) => false,
+      None => self.update_on_disk(&key_hash, |entry| {
+        if let Some(ttl_seconds) = ttl_seconds {
+          entry.set_expiry(
+            Some(self.now() + seconds_to_millis(ttl_seconds)),
+            self.now(),
+          );
+        }
+      }),
+    };
+    Ok(touched)
+  }
+
+  #[napi]
+  pub fn get_ttl(&self, key: String) -> Option<i64> {
+    self.inspect(&key, |entry| {
+      Some(match entry.expires_at {
+        Some(expires_at) => expires_at.saturating_sub(self.now()).div_ceil(1000) as i64,
+        None => -1,
+      })
+    })
+  }
+
+  #[napi]
+  pub fn get_version(&self, key: String) -> Option<i64> {
+    self.inspect(&key, |entry| Some(entry.version as i64))
+  }
+
+  #[napi]
+  pub fn compare_and_swap(
+    &self,
+    key: String,
+    expected_version: i64,
+    new_value: String,
+  ) -> Result<Option<i64>, CacheErrorCode> {
+    self.ensure_direct_write(&key).map_err(coded_error)?;
+    if self.is_draining() {
+      return Ok(None);
+    }
+    let key_hash = self.locate(&key);
+
+    let swapped = match self.storage.get_mut(&key_hash) {
+      Some(mut entry)
+        if !entry.is_expired(self.now()) && entry.version as i64 == expected_version =>
+      {
+        self.rewrite_value(&key_hash, &mut entry, new_value.into())?;
+        Some(entry.version as i64)
+      }
+      _ => None,
+    };
+
+    if swapped.is_some() {
+      self.increment_stat(Stat::Sets);
+      self.mirror(&key_hash);
+    } else {
+      self.increment_stat(Stat::CasConflicts);
+    }
+    Ok(swapped)
+  }
+
+  #[napi(
+    ts_args_type = "key: string, loader: () => Promise<string>, ttlSeconds?: number | undefined | null, tags?: Array<string> | undefined | null"
+  )]
+  pub async fn get_or_set(
+    &self,
+    key: String,
+    loader: Loader,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<String> {
+    let stale = self.circuit_stale(&key);
+    match self.lookup(key.clone(), CacheValue::to_text) {
+      Some(Some(value)) => return Ok(value),
+      Some(None) => {
+        return Err(Error::from_reason(format!(
+          "Key '{key}' is cached as not found"
+        )))
+      }
+      None => {}
+    }
+
+    let key_hash = self.hash_key(&key);
+    if let Some(mut fetched) = self.prefetch(&key, &key_hash) {
+      let _ = fetched.recv().await;
+    }
+    if self.storage.contains_key(&key_hash) {
+      if let Some(Some(value)) = self.lookup(key.clone(), CacheValue::to_text) {
+        return Ok(value);
+      }
+    }
+
+    if !self.admit_load(&key) {
+      if let Some(stale) = stale {
+        self.increment_stat(Stat::StaleHits);
+        return Ok(stale);
+      }
+      let fallback = self
+        .breaker
+        .read()
+        .as_ref()
+        .and_then(|breaker| breaker.fallback.clone());
+      return match fallback {
+        Some(fallback) => Ok(fallback),
+        None => {
+          let err = Error::from_reason(format!("Circuit open for loads of key '{key}'"));
+          self.fall_back(&key, err).await
+        }
+      };
+    }
+
+    if let LoadSlot::Waiter(mut receiver) = self.claim_load(&key_hash) {
+      self.increment_stat(Stat::CoalescedLoads);
+      return match receiver.recv().await {
+        Ok(result) => result.map_err(Error::from_reason),
+        Err(err) => Err(Error::from_reason(format!("In-flight load aborted: {err}"))),
+      };
+    }
+
+    let result = self.run_loader(key, loader, ttl_seconds, tags).await;
+    self.finish_load(&key_hash, &result);
+
+    result
+  }
+
+  #[napi]
+  pub fn rotate_encryption_key(&self, key: Buffer) -> Result<u32> {
+    let keyring = self
+      .keyring
+      .as_ref()
+      .ok_or_else(|| Error::from_reason("Encryption is not enabled for this cache"))?;
+    keyring.rotate(&key).map_err(Error::from_reason)
+  }
+
+  #[napi(ts_args_type = "loader: (key: string) => Promise<string>")]
+  pub fn register_loader(&self, loader: KeyLoader) {
+    *self.loader.write() = Some(Arc::new(loader));
+  }
+
+  #[napi(
+    ts_args_type = "callback: (event: CacheEvent) => void, pattern?: string | undefined | null"
+  )]
+  pub fn on_event(&self, callback: EventListener, pattern: Option<String>) -> u32 {
+    self.events.subscribe(callback, pattern.as_deref())
+  }
+
+  #[napi]
+  pub fn off_event(&self, subscription_id: u32) -> bool {
+    self.events.unsubscribe(subscription_id)
+  }
+
+  #[napi]
+  pub fn create_change_stream(&self, capacity: Option<u32>) -> ChangeStream {
+    let capacity = capacity.map_or(DEFAULT_CHANGE_STREAM_CAPACITY, |capacity| capacity as usize);
+    ChangeStream {
+      records: Arc::new(tokio::sync::Mutex::new(
+        self.events.open_change_stream(capacity),
+      )),
+    }
+  }
+
+  #[napi(ts_args_type = "callback: (entry: EvictedEntry) => void")]
+  pub fn on_evicted(&self, callback: EvictionListener) {
+    self.events.set_eviction_listener(Some(callback));
+  }
+
+  #[napi]
+  pub fn off_evicted(&self) -> bool {
+    self.events.set_eviction_listener(None)
+  }
+
+  #[napi]
+  pub fn unregister_loader(&self) -> bool {
+    self.loader.write().take().is_some()
+  }
+
+  #[napi(ts_args_type = "fallback: (key: string) => Promise<string>")]
+  pub fn register_fallback(&self, fallback: KeyLoader) {
+    *self.fallback.write() = Some(Arc::new(fallback));
+  }
+
+  #[napi]
+  pub fn unregister_fallback(&self) -> bool {
+    self.fallback.write().take().is_some()
+  }
+
+  #[napi]
+  pub fn enable_loader_retries(
+    &self,
+    max_retries: u32,
+    base_delay_ms: Option<u32>,
+    max_delay_ms: Option<u32>,
+  ) -> Result<bool> {
+    let base_delay_ms = base_delay_ms.unwrap_or(50);
+    if max_retries == 0 || base_delay_ms == 0 {
+      return Err(Error::from_reason(
+        "Invalid loader retries: maxRetries and baseDelayMs must be positive",
+      ));
+    }
+
+    let mut policy = self.retry_policy.write();
+    if policy.is_some() {
+      return Ok(false);
+    }
+    *policy = Some(RetryPolicy::new(
+      max_retries,
+      base_delay_ms as u64,
+      max_delay_ms.unwrap_or(2_000) as u64,
+    ));
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn disable_loader_retries(&self) -> bool {
+    self.retry_policy.write().take().is_some()
+  }
+
+  #[napi]
+  pub fn enable_circuit_breaker(
+    &self,
+    failure_threshold: u32,
+    cooldown_ms: u32,
+    prefix_delimiter: Option<String>,
+    fallback: Option<String>,
+  ) -> Result<bool> {
+    if failure_threshold == 0 || cooldown_ms == 0 {
+      return Err(Error::from_reason(
+        "Invalid circuit breaker: failureThreshold and cooldownMs must be positive",
+      ));
+    }
+
+    let mut breaker = self.breaker.write();
+    if breaker.is_some() {
+      return Ok(false);
+    }
+    *breaker = Some(CircuitBreaker::new(
+      failure_threshold,
+      cooldown_ms as u64,
+      prefix_delimiter,
+      fallback,
+    ));
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn disable_circuit_breaker(&self) -> bool {
+    self.breaker.write().take().is_some()
+  }
+
+  #[napi]
+  pub fn get_circuit_states(&self) -> HashMap<String, CircuitState> {
+    let breaker = self.breaker.read();
+    let Some(breaker) = breaker.as_ref() else {
+      return HashMap::new();
+    };
+    breaker
+      .snapshot(self.now())
+      .into_iter()
+      .map(|circuit| {
+        let status = match circuit.opened_at {
+          None => CircuitStatus::Closed,
+          Some(_) if circuit.half_open => CircuitStatus::HalfOpen,
+          Some(_) => CircuitStatus::Open,
+        };
+        let state = CircuitState {
+          status,
+          failures: circuit.failures,
+          opened_at: circuit.opened_at.map(to_wall_millis),
+        };
+        (circuit.scope, state)
+      })
+      .collect()
+  }
+
+  #[napi]
+  pub fn delete(&self, key: String) -> Result<bool> {
+    self.ensure_writable()?;
+    let removed = self.delete_key(&key);
+    self.broadcast(Invalidation::Key { key });
+    Ok(removed)
+  }
+
+  #[napi]
+  pub fn delete_if_equals(&self, key: String, expected_value: String) -> Result<bool> {
+    self.ensure_writable()?;
+    let key_hash = self.hash_key(&key);
+    self.read_through(&key, &key_hash);
+    let locks = self.storage.lock_keys([key_hash.as_str()]);
+    let matches = self
+      .storage
+      .get(&key_hash)
+      .filter(|entry| !entry.is_expired(self.now()))
+      .and_then(|entry| self.read_value(&entry, CacheValue::to_text))
+      .is_some_and(|value| value == expected_value);
+    let removed = matches && self.delete_key(&key);
+    drop(locks);
+    if removed {
+      self.broadcast(Invalidation::Key { key });
+    }
+    Ok(removed)
+  }
+
+  #[napi]
+  pub fn increment(
+    &self,
+    key: String,
+    delta: Option<i64>,
+    ttl_seconds: Option<u32>,
+  ) -> Result<i64> {
+    self.ensure_direct_write(&key)?;
+    self.ensure_writable()?;
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+    let delta = delta.unwrap_or(1);
+
+    self
+      .make_room(&key_hash, (key.len() + 20) as u64)
+      .map_err(plain_error)?;
+
+    let result = loop {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      match live {
+        Some(mut entry) => {
+          let current: i64 = match self.revealed(&entry.value, CacheValue::to_text) {
+            Some(text) => text
+              .parse()
+              .map_err(|_| Error::from_reason(format!("Value at key '{key}' is not an integer")))?,
+            None => 0,
+          };
+          let next = current.checked_add(delta).ok_or_else(|| {
+            Error::from_reason(format!("Increment of key '{key}' would overflow"))
+          })?;
+          self
+            .rewrite_value(&key_hash, &mut entry, next.to_string().into())
+            .map_err(plain_error)?;
+          break next;
+        }
+        None => {
+          let counter = self
+            .new_entry(
+              key.clone(),
+              delta.to_string().into(),
+              ttl_seconds.map(seconds_to_millis),
+              None,
+            )
+            .map_err(plain_error)?;
+          if self
+            .insert_entry_if(key_hash.clone(), counter, |live| live.is_none())
+            .is_some()
+          {
+            break delta;
+          }
+        }
+      }
+    };
+
+    self.increment_stat(Stat::Sets);
+    self.mirror(&key_hash);
+    Ok(result)
+  }
+
+  #[napi]
+  pub fn hset(
+    &self,
+    key: String,
+    field: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+  ) -> Result<bool> {
+    self.ensure_direct_write(&key)?;
+    self.ensure_writable()?;
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+
+    self
+      .make_room(&key_hash, (key.len() + field.len() + value.len()) as u64)
+      .map_err(plain_error)?;
+
+    let added = loop {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      match live {
+        Some(mut entry) => {
+          let added = self.edit_value(&key, &key_hash, &mut entry, |stored| {
+            Ok(Some(
+              CacheCore::hash_of(&key, stored)?.insert(field.clone(), value.clone()),
+            ))
+          })?;
+          break added.unwrap_or_default();
+        }
+        None => {
+          let fields = HashFields::from_iter([(field.clone(), value.clone())]);
+          let entry = self
+            .new_entry(
+              key.clone(),
+              CacheValue::Collection(Collection::Hash(fields)),
+              ttl_seconds.map(seconds_to_millis),
+              None,
+            )
+            .map_err(plain_error)?;
+          if self
+            .insert_entry_if(key_hash.clone(), entry, |live| live.is_none())
+            .is_some()
+          {
+            break true;
+          }
+        }
+      }
+    };
+
+    self.increment_stat(Stat::Sets);
+    self.mirror(&key_hash);
+    Ok(added)
+  }
+
+  #[napi]
+  pub fn hget(&self, key: String, field: String) -> Result<Option<String>> {
+    Ok(
+      self
+        .read_hash(&key, |fields| fields.get(&field).cloned())?
+        .flatten(),
+    )
+  }
+
+  #[napi]
+  pub fn hget_all(&self, key: String) -> Result<Option<HashMap<String, String>>> {
+    self.read_hash(&key, |fields| {
+      fields
+        .iter()
+        .map(|(field, value)| (field.clone(), value.clone()))
+        .collect()
+    })
+  }
+
+  #[napi]
+  pub fn hdel(&self, key: String, field: String) -> Result<bool> {
+    self.ensure_writable()?;
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+
+    let locks = self.storage.lock_keys([key_hash.as_str()]);
+    let emptied = {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      let Some(mut entry) = live else {
+        return Ok(false);
+      };
+      self.edit_value(&key, &key_hash, &mut entry, |stored| {
+        let fields = CacheCore::hash_of(&key, stored)?;
+        Ok(fields.remove(&field).then(|| fields.is_empty()))
+      })?
+    };
+    let Some(emptied) = emptied else {
+      return Ok(false);
+    };
+    if emptied {
+      self.delete_key(&key);
+    }
+    drop(locks);
+
+    if emptied {
+      self.broadcast(Invalidation::Key { key });
+    } else {
+      self.mirror(&key_hash);
+    }
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn lpush(
+    &self,
+    key: String,
+    values: Either<String, Vec<String>>,
+    ttl_seconds: Option<u32>,
+    max_length: Option<u32>,
+  ) -> Result<u32> {
+    self.push_items(key, values, ttl_seconds, max_length, true)
+  }
+
+  #[napi]
+  pub fn rpush(
+    &self,
+    key: String,
+    values: Either<String, Vec<String>>,
+    ttl_seconds: Option<u32>,
+    max_length: Option<u32>,
+  ) -> Result<u32> {
+    self.push_items(key, values, ttl_seconds, max_length, false)
+  }
+
+  #[napi]
+  pub fn lpop(&self, key: String) -> Result<Option<String>> {
+    self.pop_item(key, true)
+  }
+
+  #[napi]
+  pub fn rpop(&self, key: String) -> Result<Option<String>> {
+    self.pop_item(key, false)
+  }
+
+  #[napi]
+  pub fn lrange(&self, key: String, start: i64, stop: i64) -> Result<Vec<String>> {
+    let items = self.read_list(&key, |items| {
+      range_bounds(items.len(), start, stop)
+        .map(|(skip, take)| items.range(skip, take).cloned().collect())
+    })?;
+    Ok(items.flatten().unwrap_or_default())
+  }
+
+  #[napi]
+  pub fn sadd(
+    &self,
+    key: String,
+    members: Either<String, Vec<String>>,
+    ttl_seconds: Option<u32>,
+  ) -> Result<u32> {
+    self.ensure_direct_write(&key)?;
+    self.ensure_writable()?;
+    let additions = Self::one_or_many(members);
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+
+    let added_bytes: usize = additions.iter().map(String::len).sum();
+    self
+      .make_room(&key_hash, (key.len() + added_bytes) as u64)
+      .map_err(plain_error)?;
+
+    let added = loop {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      match live {
+        Some(mut entry) => {
+          let added = self.edit_value(&key, &key_hash, &mut entry, |stored| {
+            let members = CacheCore::set_of(&key, stored)?;
+            let added = additions
+              .iter()
+              .filter(|member| members.insert((*member).clone()))
+              .count() as u32;
+            Ok((added > 0).then_some(added))
+          })?;
+          break added.unwrap_or_default();
+        }
+        None => {
+          let members: SetMembers = additions.iter().cloned().collect();
+          let added = members.len() as u32;
+          let entry = self
+            .new_entry(
+              key.clone(),
+              CacheValue::Collection(Collection::Set(members)),
+              ttl_seconds.map(seconds_to_millis),
+              None,
+            )
+            .map_err(plain_error)?;
+          if self
+            .insert_entry_if(key_hash.clone(), entry, |live| live.is_none())
+            .is_some()
+          {
+            break added;
+          }
+        }
+      }
+    };
+
+    if added > 0 {
+      self.increment_stat(Stat::Sets);
+      self.mirror(&key_hash);
+    }
+    Ok(added)
+  }
+
+  #[napi]
+  pub fn srem(&self, key: String, members: Either<String, Vec<String>>) -> Result<u32> {
+    self.ensure_writable()?;
+    let removals = Self::one_or_many(members);
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+
+    let locks = self.storage.lock_keys([key_hash.as_str()]);
+    let removed = {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      let Some(mut entry) = live else {
+        return Ok(0);
+      };
+      self.edit_value(&key, &key_hash, &mut entry, |stored| {
+        let members = CacheCore::set_of(&key, stored)?;
+        let removed = removals
+          .iter()
+          .filter(|member| members.remove(member))
+          .count() as u32;
+        Ok((removed > 0).then(|| (removed, members.is_empty())))
+      })?
+    };
+    let Some((removed, emptied)) = removed else {
+      return Ok(0);
+    };
+    if emptied {
+      self.delete_key(&key);
+    }
+    drop(locks);
+
+    if emptied {
+      self.broadcast(Invalidation::Key { key });
+    } else {
+      self.mirror(&key_hash);
+    }
+    Ok(removed)
+  }
+
+  #[napi]
+  pub fn smembers(&self, key: String) -> Result<Vec<String>> {
+    let members = self.read_set(&key, |members| members.iter().cloned().collect())?;
+    Ok(members.unwrap_or_default())
+  }
+
+  #[napi]
+  pub fn sismember(&self, key: String, member: String) -> Result<bool> {
+    let found = self.read_set(&key, |members| members.contains(&member))?;
+    Ok(found.unwrap_or_default())
+  }
+
+  #[napi]
+  pub fn sinter(&self, keys: Vec<String>) -> Result<Vec<String>> {
+    let mut keys = keys.iter();
+    let Some(first) = keys.next() else {
+      return Ok(Vec::new());
+    };
+    let mut members: BTreeSet<String> = self
+      .read_set(first, |members| members.iter().cloned().collect())?
+      .unwrap_or_default();
+    for key in keys {
+      if members.is_empty() {
+        break;
+      }
+      let found = self.read_set(key, |other| members.retain(|member| other.contains(member)))?;
+      if found.is_none() {
+        members.clear();
+      }
+    }
+    Ok(members.into_iter().collect())
+  }
+
+  #[napi]
+  pub fn sunion(&self, keys: Vec<String>) -> Result<Vec<String>> {
+    let mut members = BTreeSet::new();
+    for key in &keys {
+      self.read_set(key, |other| members.extend(other.iter().cloned()))?;
+    }
+    Ok(members.into_iter().collect())
+  }
+
+  #[napi]
+  pub fn zadd(
+    &self,
+    key: String,
+    member: String,
+    score: f64,
+    ttl_seconds: Option<u32>,
+  ) -> Result<bool> {
+    let (added, _) = self.update_score(key, member, ttl_seconds, |_| score)?;
+    Ok(added)
+  }
+
+  #[napi]
+  pub fn zincrby(
+    &self,
+    key: String,
+    member: String,
+    increment: f64,
+    ttl_seconds: Option<u32>,
+  ) -> Result<f64> {
+    let (_, score) = self.update_score(key, member, ttl_seconds, |previous| {
+      previous.unwrap_or(0.0) + increment
+    })?;
+    Ok(score)
+  }
+
+  #[napi]
+  pub fn zrange(
+    &self,
+    key: String,
+    start: i64,
+    stop: i64,
+    reverse: Option<bool>,
+  ) -> Result<Vec<ScoredMember>> {
+    let members = self.read_sorted_set(&key, |members| {
+      range_bounds(members.len(), start, stop)
+        .map(|(skip, take)| members.range(skip, take, reverse.unwrap_or(false)))
+    })?;
+    Ok(
+      members
+        .flatten()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(member, score)| ScoredMember { member, score })
+        .collect(),
+    )
+  }
+
+  #[napi]
+  pub fn zrank(&self, key: String, member: String, reverse: Option<bool>) -> Result<Option<u32>> {
+    let rank = self.read_sorted_set(&key, |members| {
+      members.rank(&member).map(|index| {
+        if reverse.unwrap_or(false) {
+          members.len() - 1 - index
+        } else {
+          index
+        }
+      })
+    })?;
+    Ok(rank.flatten().map(|rank| rank as u32))
+  }
+
+  #[napi]
+  pub fn decrement(&self, key: String, delta: Option<i64>) -> Result<i64> {
+    let delta = delta.unwrap_or(1);
+    let negated = delta
+      .checked_neg()
+      .ok_or_else(|| Error::from_reason(format!("Decrement of key '{key}' would overflow")))?;
+    self.increment(key, Some(negated), None)
+  }
+
+  #[napi]
+  pub fn acquire_lock(&self, name: String, ttl_ms: u32) -> Result<i64, CacheErrorCode> {
+    self.ensure_writable().map_err(coded_error)?;
+    let max_locks = self.limits().max_size;
+    match self
+      .leases
+      .acquire(name.clone(), ttl_ms as u64, max_locks, self.now())
+    {
+      Acquire::Granted(token) => {
+        self.increment_stat(Stat::LocksAcquired);
+        Ok(token as i64)
+      }
+      Acquire::Held => {
+        self.increment_stat(Stat::LocksContended);
+        Err(Error::new(
+          CacheErrorCode::LockHeld,
+          format!("Lock '{name}' is held by another owner"),
+        ))
+      }
+      Acquire::Full => Err(Error::new(
+        CacheErrorCode::CapacityExceeded,
+        format!("Cannot acquire lock '{name}': {max_locks} locks are already held"),
+      )),
+    }
+  }
+
+  #[napi]
+  pub fn release_lock(&self, name: String, token: i64) -> bool {
+    self.leases.release(&name, token as u64, self.now())
+  }
+
+  #[napi]
+  pub fn get_with_lease(&self, key: String, lease_ms: u32) -> LeasedValue {
+    let key_hash = self.locate(&key);
+    let found = self.lookup(key, CacheValue::to_text);
+    let stale = self
+      .storage
+      .get(&key_hash)
+      .is_some_and(|entry| entry.is_stale(self.now()));
+
+    match found {
+      Some(None) => LeasedValue {
+        value: None,
+        stale: false,
+        token: None,
+        wait: false,
+      },
+      Some(value) if !stale => LeasedValue {
+        value,
+        stale,
+        token: None,
+        wait: false,
+      },
+      found => {
+        let value = found.flatten();
+        let token = match self.fill_leases.acquire(
+          key_hash,
+          lease_ms as u64,
+          self.limits().max_size,
+          self.now(),
+        ) {
+          Acquire::Granted(token) => Some(token),
+          Acquire::Held | Acquire::Full => None,
+        };
+        if token.is_some() {
+          self.increment_stat(Stat::FillLeases);
+        }
+        LeasedValue {
+          wait: value.is_none() && token.is_none(),
+          value,
+          stale,
+          token: token.map(|token| token as i64),
+        }
+      }
+    }
+  }
+
+  #[napi]
+  pub fn set_with_lease(
+    &self,
+    key: String,
+    token: i64,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<Option<i64>, CacheErrorCode> {
+    if !self
+      .fill_leases
+      .release(&self.locate(&key), token as u64, self.now())
+    {
+      self.increment_stat(Stat::FillLeasesLost);
+      return Ok(None);
+    }
+    self.set(key, value, ttl_seconds, tags, None).map(Some)
+  }
+
+  #[napi]
+  pub fn check_rate_limit(
+    &self,
+    key: String,
+    limit: u32,
+    window_ms: u32,
+    cost: Option<u32>,
+  ) -> Result<RateLimitResult, CacheErrorCode> {
+    if limit == 0 || window_ms == 0 {
+      return Err(Error::new(
+        CacheErrorCode::GenericFailure,
+        format!("Invalid rate limit for '{key}': limit and windowMs must be positive"),
+      ));
+    }
+
+    let max_buckets = self.limits().max_size;
+    let Some(decision) = self.rate_limiter.take(
+      key.clone(),
+      limit,
+      window_ms as u64,
+      cost.unwrap_or(1),
+      max_buckets,
+      self.now(),
+    ) else {
+      return Err(Error::new(
+        CacheErrorCode::CapacityExceeded,
+        format!("Cannot track rate limit '{key}': {max_buckets} buckets are already in use"),
+      ));
+    };
+    self.increment_stat(if decision.allowed {
+      Stat::RateLimitAllowed
+    } else {
+      Stat::RateLimitRejected
+    });
+    Ok(RateLimitResult {
+      allowed: decision.allowed,
+      remaining: decision.remaining,
+      retry_after_ms: (!decision.allowed && decision.retry_after_ms != u64::MAX)
+        .then_some(decision.retry_after_ms as i64),
+    })
+  }
+
+  #[napi]
+  pub fn reset_rate_limit(&self, key: String) -> bool {
+    self.rate_limiter.reset(&key)
+  }
+
+  #[napi]
+  pub fn begin_idempotent(
+    &self,
+    key: String,
+    ttl_seconds: u32,
+  ) -> Result<IdempotencyClaim, CacheErrorCode> {
+    let max_requests = self.limits().max_size;
+    let claim = self
+      .idempotency
+      .begin(
+        key.clone(),
+        seconds_to_millis(ttl_seconds),
+        max_requests,
+        self.now(),
+      )
+      .ok_or_else(|| {
+        Error::new(
+          CacheErrorCode::CapacityExceeded,
+          format!("Cannot claim idempotency key '{key}': {max_requests} keys are already tracked"),
+        )
+      })?;
+    let (status, response) = match claim {
+      Claim::Claimed => (IdempotencyStatus::Claimed, None),
+      Claim::InProgress => (IdempotencyStatus::InProgress, None),
+      Claim::Completed(response) => (IdempotencyStatus::Completed, Some(response)),
+    };
+    Ok(IdempotencyClaim { status, response })
+  }
+
+  #[napi]
+  pub fn complete_idempotent(
+    &self,
+    key: String,
+    response: String,
+    ttl_seconds: Option<u32>,
+  ) -> Result<bool, CacheErrorCode> {
+    self.make_byte_room(response.len() as u64)?;
+    Ok(self.idempotency.complete(
+      &key,
+      response,
+      ttl_seconds.map(seconds_to_millis),
+      self.now(),
+    ))
+  }
+
+  #[napi]
+  pub fn release_idempotent(&self, key: String) -> bool {
+    self.idempotency.release(&key)
+  }
+
+  #[napi]
+  pub fn get_keys_by_tag(&self, tag: String) -> Vec<String> {
+    let key_hashes = self
+      .tag_index
+      .get(&tag)
+      .map(|hashes| hashes.clone())
+      .unwrap_or_default();
+
+    let mut keys: Vec<String> = key_hashes
+      .iter()
+      .filter_map(|key_hash| {
+        self
+          .storage
+          .get(key_hash)
+          .filter(|entry| !entry.is_expired(self.now()) && entry.tags.contains(&tag))
+          .map(|entry| entry.original_key.clone())
+      })
+      .collect();
+    keys.extend(self.disk_keys(Some(&tag)).into_iter().map(|(_, key)| key));
+    keys
+  }
+
+  #[napi]
+  pub fn delete_by_tag(&self, tag: String) -> Result<u32> {
+    self.ensure_writable()?;
+    let removed = self.remove_tagged(&tag);
+    for entry in &removed {
+      self.delete_through(&entry.original_key);
+      self.notify_removal(entry, EvictionReason::Delete);
+    }
+    self.add_stat(Stat::Deletes, removed.len() as u64);
+    self.broadcast(Invalidation::Tag { tag });
+    Ok(removed.len() as u32)
+  }
+
+  #[napi(
+    ts_args_type = "key: string, response: any, ttlSeconds?: number | undefined | null, tags?: Array<string> | undefined | null"
+  )]
+  pub fn set_graphql_response(
+    &self,
+    key: String,
+    response: serde_json::Value,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64, CacheErrorCode> {
+    let mut tags = tags.unwrap_or_default();
+    for entity in graphql::entity_tags(response.get("data").unwrap_or(&response)) {
+      if !tags.contains(&entity) {
+        tags.push(entity);
+      }
+    }
+    self.set_json(key, response, ttl_seconds, Some(tags))
+  }
+
+  #[napi]
+  pub fn invalidate_entity(&self, entity: String) -> Result<u32> {
+    self.delete_by_tag(entity)
+  }
+
+  #[napi]
+  pub fn take(&self, key: String) -> Result<Option<String>> {
+    self.ensure_writable()?;
+    let key_hash = self.hash_key(&key);
+    self.read_through(&key, &key_hash);
+    self.delete_through(&key);
+    self.broadcast(Invalidation::Key { key: key.clone() });
+
+    let removed = self.remove_entry(&key_hash);
+    self.invalidate_dependents(&key_hash);
+    Ok(match removed {
+      Some(entry) if !entry.is_expired(self.now()) => {
+        self.increment_stat(Stat::Hits);
+        self.increment_stat(Stat::Deletes);
+        self.notify_removal(&entry, EvictionReason::Delete);
+        self.read_value(&entry, CacheValue::to_text)
+      }
+      Some(entry) => {
+        self.increment_stat(Stat::ExpiredHits);
+        self.notify_removal(&entry, EvictionReason::Ttl);
+        None
+      }
+      None => {
+        self.increment_stat(Stat::Misses);
+        None
+      }
+    })
+  }
+
+  #[napi]
+  pub fn pop(&self, key: String) -> Result<Option<String>> {
+    self.take(key)
+  }
+
+  #[napi]
+  pub fn rename(&self, old_key: String, new_key: String) -> Result<bool, CacheErrorCode> {
+    self.relocate(old_key, new_key, false, None)
+  }
+
+  #[napi]
+  pub fn copy(
+    &self,
+    src_key: String,
+    dst_key: String,
+    ttl_seconds: Option<u32>,
+  ) -> Result<bool, CacheErrorCode> {
+    self.relocate(src_key, dst_key, true, ttl_seconds)
+  }
+
+  #[napi]
+  pub fn get_set(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<Option<String>> {
+    self.ensure_direct_write(&key)?;
+    let key_hash = self.hash_key(&key);
+    let entry = self
+      .new_entry(
+        key.clone(),
+        value.into(),
+        ttl_seconds.map(seconds_to_millis),
+        tags,
+      )
+      .map_err(plain_error)?;
+
+    self.read_through(&key, &key_hash);
+    self
+      .make_room(&key_hash, entry.weight())
+      .map_err(plain_error)?;
+
+    let mut previous = None;
+    self.write_through(&entry);
+    self.insert_entry_if(key_hash, entry, |live| {
+      previous = live.and_then(|entry| self.read_value(entry, CacheValue::to_text));
+      true
+    });
+    self.increment_stat(Stat::Sets);
+
+    Ok(previous)
+  }
+
+  #[napi]
+  pub fn get_many(&self, keys: Vec<String>) -> Vec<Option<String>> {
+    keys
+      .into_iter()
+      .map(|key| self.lookup(key, CacheValue::to_text).flatten())
+      .collect()
+  }
+
+  #[napi]
+  pub fn set_many(&self, entries: Vec<SetManyEntry>) -> Result<u32, CacheErrorCode> {
+    let mut written = 0;
+    for entry in entries {
+      match entry.expected_version {
+        Some(expected_version) => {
+          let versioned = self.text_entry(
+            entry.key,
+            entry.value,
+            entry.ttl_seconds,
+            entry.tags,
+            entry.tti_seconds,
+            entry.priority,
+          )?;
+          self.store_versioned(versioned, expected_version)?;
+        }
+        None => {
+          self.store_text(
+            entry.key,
+            entry.value,
+            entry.ttl_seconds,
+            entry.tags,
+            entry.tti_seconds,
+            entry.priority,
+          )?;
+        }
+      }
+      written += 1;
+    }
+    Ok(written)
+  }
+
+  #[napi]
+  pub fn delete_many(&self, keys: Vec<String>) -> Result<u32> {
+    self.ensure_writable()?;
+    let mut deleted = 0;
+    for key in keys {
+      deleted += self.delete(key)? as u32;
+    }
+    Ok(deleted)
+  }
+
+  #[napi]
+  pub fn get_stats(&self) -> CacheStats {
+    let limits = self.limits();
+    let stat = |stat: Stat| self.stats.get(stat) as i64;
+    let (hits, misses) = (stat(Stat::Hits), stat(Stat::Misses));
+    let entries = self.storage.len();
+    let total_bytes = self.total_bytes.load(Ordering::Relaxed) as i64;
+    let (compression_input_bytes, compression_output_bytes) = (
+      stat(Stat::CompressionInputBytes),
+      stat(Stat::CompressionOutputBytes),
+    );
+
+    CacheStats {
+      entries: entries as u32,
+      live_entries: self.live_count() as u32,
+      max_size: limits.max_size as u32,
+      pinned: self.pinned.len() as u32,
+      open_circuits: self
+        .breaker
+        .read()
+        .as_ref()
+        .map_or(0, |breaker| breaker.open_count() as u32),
+      hits,
+      misses,
+      hit_rate: if hits + misses > 0 {
+        hits as f64 / (hits + misses) as f64
+      } else {
+        0.0
+      },
+      operations: OperationStats {
+        gets: hits + misses,
+        sets: stat(Stat::Sets),
+        deletes: stat(Stat::Deletes),
+        loads: stat(Stat::Loads),
+        refreshes: stat(Stat::Refreshes),
+        evictions: stat(Stat::Evictions),
+        expirations: stat(Stat::ExpiredHits) + stat(Stat::SweptEntries),
+        flushes: stat(Stat::Flushes),
+      },
+      memory: MemoryStats {
+        total_bytes,
+        metadata_bytes: (entries * ENTRY_OVERHEAD_BYTES) as i64,
+        max_bytes: limits.max_bytes.map(|bytes| bytes as i64),
+        average_entry_bytes: if entries > 0 {
+          total_bytes as f64 / entries as f64
+        } else {
+          0.0
+        },
+        compressed_values: stat(Stat::CompressedValues),
+        compression_input_bytes,
+        compression_output_bytes,
+        compression_ratio: (compression_input_bytes > 0)
+          .then(|| compression_output_bytes as f64 / compression_input_bytes as f64),
+      },
+      latency: OperationLatencies {
+        get: latency_stats(&self.latencies.get),
+        set: latency_stats(&self.latencies.set),
+        delete: latency_stats(&self.latencies.delete),
+        loader: latency_stats(&self.latencies.loader),
+      },
+      windows: {
+        let [one_minute, five_minutes, fifteen_minutes] = self.rolling.windows();
+        RollingWindows {
+          one_minute: window_stats(&one_minute),
+          five_minutes: window_stats(&five_minutes),
+          fifteen_minutes: window_stats(&fifteen_minutes),
+        }
+      },
+      counters: self
+        .stats
+        .iter()
+        .map(|(stat, value)| (stat.name().to_string(), value as i64))
+        .collect(),
+    }
+  }
+
+  #[napi]
+  pub fn memory_usage(&self) -> MemoryUsage {
+    let entries = self.storage.len();
+    let live_entries = self.live_count();
+    let data_bytes = self.total_bytes.load(Ordering::Relaxed) as i64;
+    let metadata_bytes = (entries * ENTRY_OVERHEAD_BYTES) as i64;
+    MemoryUsage {
+      live_entries: live_entries as u32,
+      expired_entries: entries.saturating_sub(live_entries) as u32,
+      data_bytes,
+      metadata_bytes,
+      total_bytes: data_bytes + metadata_bytes,
+      max_bytes: self.limits().max_bytes.map(|bytes| bytes as i64),
+      pressure: self
+        .pressure_cap()
+        .map_or(MemoryPressure::None, |cap| cap.level),
+    }
+  }
+
+  #[napi]
+  pub fn memory_usage_of(&self, key: String) -> Option<i64> {
+    let key_hash = self.locate(&key);
+    let entry = self
+      .storage
+      .get(&key_hash)
+      .filter(|entry| !entry.is_expired(self.now()))?;
+    Some((entry.weight() + ENTRY_OVERHEAD_BYTES as u64) as i64)
+  }
+
+  #[napi]
+  pub fn report_memory_pressure(&self, level: MemoryPressure) -> u32 {
+    if level == MemoryPressure::None {
+      *self.pressure.write() = None;
+      return 0;
+    }
+    {
+      let mut pressure = self.pressure.write();
+      let baseline_bytes = pressure.map_or_else(
+        || self.total_bytes.load(Ordering::Relaxed),
+        |report| report.baseline_bytes,
+      );
+      *pressure = Some(PressureReport {
+        level,
+        reported_at: self.now(),
+        baseline_bytes,
+      });
+    }
+    let Some(cap) = self.pressure_cap() else {
+      return 0;
+    };
+
+    let mut reclaimed = self.sweep_expired();
+    let mut evicted = 0;
+    while (self.storage.len() > cap.max_entries || self.used_bytes() > cap.max_bytes)
+      && self.evict_one()
+    {
+      evicted += 1;
+    }
+    self.add_stat(Stat::PressureEvictions, evicted as u64);
+    reclaimed += evicted;
+    reclaimed
+  }
+
+  #[napi]
+  pub fn reset_stats(&self) {
+    self.stats.reset();
+    self.latencies.reset();
+    self.rolling.reset();
+    self.hot_keys.reset();
+    self.groups.reset();
+  }
+
+  #[napi]
+  pub fn configure(&self, settings: CacheSettings) -> u32 {
+    let limits = {
+      let mut limits = self.limits.write();
+      if let Some(max_size) = settings.max_size {
+        limits.max_size = max_size.max(1) as usize;
+      }
+      if let Some(max_bytes) = settings.max_bytes {
+        limits.max_bytes = (max_bytes > 0).then_some(max_bytes as u64);
+      }
+      if let Some(ttl_seconds) = settings.default_ttl_seconds {
+        limits.default_ttl_ms = (ttl_seconds > 0).then(|| seconds_to_millis(ttl_seconds));
+      }
+      if let Some(tti_seconds) = settings.default_tti_seconds {
+        limits.default_tti_ms = (tti_seconds > 0).then(|| seconds_to_millis(tti_seconds));
+      }
+      if let Some(percent) = settings.ttl_jitter_percent {
+        limits.ttl_jitter_percent = percent.min(100) as u64;
+      }
+      if let Some(seconds) = settings.stale_while_revalidate_seconds {
+        limits.stale_window_ms = seconds_to_millis(seconds);
+      }
+      if let Some(percent) = settings.refresh_ahead_percent {
+        limits.refresh_ahead_percent = percent.min(100) as u64;
+      }
+      if let Some(seconds) = settings.negative_ttl_seconds {
+        limits.negative_ttl_ms = seconds_to_millis(seconds);
+      }
+      if let Some(bytes) = settings.max_value_bytes {
+        limits.max_value_bytes = (bytes > 0).then_some(bytes as u64);
+      }
+      if let Some(length) = settings.max_key_length {
+        limits.max_key_length = (length > 0).then_some(length as usize);
+      }
+      *limits
+    };
+    if settings.max_size.is_some() {
+      self.eviction.resize(limits.max_size);
+    }
+    self.increment_stat(Stat::Reconfigurations);
+
+    let over_limits = || {
+      self.storage.len() > limits.max_size
+        || limits
+          .max_bytes
+          .is_some_and(|max_bytes| self.used_bytes() > max_bytes)
+    };
+    let mut evicted = 0;
+    while over_limits() && self.evict_one() {
+      evicted += 1;
+    }
+    evicted
+  }
+
+  #[napi]
+  pub fn namespace(
+    &self,
+    name: String,
+    max_size: Option<u32>,
+    default_ttl_seconds: Option<u32>,
+  ) -> Result<CacheNamespace> {
+    validate_namespace(&name)?;
+    let namespace = self.register_namespace(&name);
+    namespace.configure(
+      max_size.map(|max_size| max_size.max(1) as usize),
+      default_ttl_seconds.map(seconds_to_millis),
+    );
+    Ok(CacheNamespace {
+      core: self.core.clone(),
+      namespace,
+      invalidation: self.invalidation.clone(),
+    })
+  }
+
+  #[napi]
+  pub fn transaction(&self) -> CacheTransaction {
+    CacheTransaction {
+      core: self.core.clone(),
+      invalidation: self.invalidation.clone(),
+      ops: Mutex::new(Vec::new()),
+    }
+  }
+
+  #[napi]
+  pub fn watch(&self, keys: Vec<String>) -> CacheTransaction {
+    let transaction = self.transaction();
+    transaction.watch(keys);
+    transaction
+  }
+
+  #[napi]
+  pub fn set_tenant_quota(&self, tenant: String, max_entries: Option<u32>, max_bytes: Option<i64>) {
+    self.register_tenant(&tenant).set_quota(Quota {
+      max_entries: max_entries.map(|max_entries| max_entries.max(1) as u64),
+      max_bytes: max_bytes.map(|max_bytes| max_bytes.max(0) as u64),
+    });
+  }
+
+  #[napi]
+  pub fn set_for_tenant(
+    &self,
+    tenant: String,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64, CacheErrorCode> {
+    let entry = self
+      .new_entry(key, value.into(), ttl_seconds.map(seconds_to_millis), tags)?
+      .with_tenant(tenant.clone());
+    self.check_entry(&entry)?;
+    let key_hash = self.hash_key(&entry.original_key);
+    self.make_tenant_room(&self.register_tenant(&tenant), &key_hash, &entry)?;
+    self.store_entry(entry)
+  }
+
+  #[napi]
+  pub fn get_tenant_stats(&self) -> HashMap<String, TenantStats> {
+    self
+      .tenants
+      .iter()
+      .map(|tenant| {
+        let quota = tenant.quota();
+        let stats = TenantStats {
+          entries: tenant.entries() as u32,
+          bytes: tenant.bytes() as i64,
+          max_entries: quota.max_entries.map(|max_entries| max_entries as u32),
+          max_bytes: quota.max_bytes.map(|max_bytes| max_bytes as i64),
+          evictions: tenant.evictions.load(Ordering::Relaxed) as i64,
+        };
+        (tenant.key().clone(), stats)
+      })
+      .collect()
+  }
+
+  #[napi]
+  pub fn bump_generation(&self, namespace: String) -> Result<i64> {
+    validate_namespace(&namespace)?;
+    let generation = self.bump_namespace_generation(&namespace);
+    self.broadcast(Invalidation::Generation { namespace });
+    Ok(generation as i64)
+  }
+
+  #[napi]
+  pub fn set_tag_ttl(&self, tag: String, ttl_seconds: u32) {
+    if ttl_seconds == 0 {
+      self.tag_ttls.remove(&tag);
+    } else {
+      self.tag_ttls.insert(tag, seconds_to_millis(ttl_seconds));
+    }
+  }
+
+  #[napi]
+  pub fn get_tag_ttls(&self) -> HashMap<String, u32> {
+    self
+      .tag_ttls
+      .iter()
+      .map(|policy| (policy.key().clone(), (*policy.value() / 1000) as u32))
+      .collect()
+  }
+
+  #[napi]
+  pub fn get_tag_stats(&self) -> HashMap<String, GroupStats> {
+    let mut usage = self.groups.tag_usage();
+    for entry in self.storage.iter() {
+      for tag in &entry.tags {
+        let group = usage.entry(tag.clone()).or_default();
+        group.entries += 1;
+        group.bytes += entry.weight();
+      }
+    }
+    group_stats(usage)
+  }
+
+  #[napi]
+  pub fn get_namespace_stats(&self) -> HashMap<String, GroupStats> {
+    let mut usage = self.groups.namespace_usage();
+    for entry in self.storage.iter() {
+      if let Some(namespace) = groups::namespace_of(&entry.original_key) {
+        let group = usage.entry(namespace.to_string()).or_default();
+        group.entries += 1;
+        group.bytes += entry.weight();
+      }
+    }
+    group_stats(usage)
+  }
+
+  #[napi]
+  pub fn get_hot_keys(&self, limit: Option<u32>) -> Vec<HotKey> {
+    let limit = (limit.unwrap_or(10) as usize).min(hot_keys::TRACKED_KEYS);
+    let (top, accesses) = self.hot_keys.top(limit);
+    top
+      .into_iter()
+      .map(|(key, count)| HotKey {
+        key,
+        count: count as i64,
+        share: if accesses > 0 {
+          count as f64 / accesses as f64
+        } else {
+          0.0
+        },
+      })
+      .collect()
+  }
+
+  #[napi]
+  pub fn get_prometheus_metrics(&self) -> String {
+    let stat = |stat: Stat| self.stats.get(stat);
+    let (hits, misses) = (stat(Stat::Hits), stat(Stat::Misses));
+    let hit_ratio = if hits + misses > 0 {
+      hits as f64 / (hits + misses) as f64
+    } else {
+      0.0
+    };
+
+    metrics::render_prometheus(
+      &[
+        Metric::Counter("hits", "Lookups served from the cache.", hits),
+        Metric::Counter("misses", "Lookups that found no entry.", misses),
+        Metric::Counter(
+          "evictions",
+          "Entries evicted to make room.",
+          stat(Stat::Evictions),
+        ),
+        Metric::Counter("sets", "Entries written.", stat(Stat::Sets)),
+        Metric::Counter(
+          "deletes",
+          "Entries explicitly deleted.",
+          stat(Stat::Deletes),
+        ),
+        Metric::Counter(
+          "expired_hits",
+          "Lookups that found an expired entry.",
+          stat(Stat::ExpiredHits),
+        ),
+        Metric::Gauge(
+          "entries",
+          "Entries currently held in memory.",
+          self.storage.len() as f64,
+        ),
+        Metric::Gauge(
+          "max_entries",
+          "Configured entry capacity.",
+          self.limits().max_size as f64,
+        ),
+        Metric::Gauge(
+          "bytes",
+          "Bytes currently held in memory.",
+          self.total_bytes.load(Ordering::Relaxed) as f64,
+        ),
+        Metric::Gauge("hit_ratio", "Lifetime ratio of hits to lookups.", hit_ratio),
+      ],
+      &self.latencies,
+    )
+  }
+
+  #[napi]
+  pub fn scan(
+    &self,
+    cursor: String,
+    pattern: Option<String>,
+    limit: Option<u32>,
+  ) -> Result<ScanPage> {
+    let after = ScanCursor::parse(&cursor)
+      .ok_or_else(|| Error::from_reason(format!("Invalid scan cursor '{cursor}'")))?;
+    let pattern = pattern.as_deref().map(KeyPattern::parse);
+    let limit = limit.unwrap_or(100).clamp(1, 10_000) as usize;
+
+    let (page, cursor) = self.scan_page(after, pattern.as_ref(), limit);
+    Ok(ScanPage {
+      keys: page
+        .iter()
+        .filter_map(|key_hash| {
+          self
+            .storage
+            .get(key_hash)
+            .map(|entry| entry.original_key.clone())
+        })
+        .collect(),
+      cursor: ScanCursor::format(cursor),
+    })
+  }
+
+  #[napi]
+  pub fn entries(&self, pattern: Option<String>, batch_size: Option<u32>) -> EntryStream {
+    EntryStream {
+      core: self.core.clone(),
+      pattern: pattern.as_deref().map(KeyPattern::parse),
+      batch_size: batch_size.unwrap_or(100).clamp(1, 10_000) as usize,
+      cursor: Some(ScanCursor::default()),
+      on_disk: None,
+      pending: VecDeque::new(),
+    }
+  }
+
+  #[napi]
+  pub fn keys(&self) -> Vec<String> {
+    self.matching_keys(|_| true)
+  }
+
+  #[napi]
+  pub fn keys_by_prefix(&self, prefix: String) -> Vec<String> {
+    self.matching_keys(|key| key.starts_with(&prefix))
+  }
+
+  #[napi]
+  pub fn keys_matching(&self, pattern: String) -> Vec<String> {
+    let pattern = KeyPattern::parse(&pattern);
+    self.matching_keys(|key| pattern.matches(key))
+  }
+
+  #[napi]
+  pub fn delete_by_prefix(&self, prefix: String) -> Result<u32> {
+    self.ensure_writable()?;
+    let deleted = self
+      .matching_keys(|key| key.starts_with(&prefix))
+      .into_iter()
+      .filter(|key| self.delete_key(key))
+      .count() as u32;
+    self.broadcast(Invalidation::Prefix { prefix });
+    Ok(deleted)
+  }
+
+  #[napi]
+  pub fn start_sweeper(&self, interval_ms: Option<u32>) -> Result<bool> {
+    let mut sweeper = self.sweeper.lock();
+    if sweeper.is_some() {
+      return Ok(false);
+    }
+
+    let core = self.core.clone();
+    let interval = Duration::from_millis(interval_ms.unwrap_or(1000).max(1) as u64);
+    let task = BackgroundTask::spawn("microservice-cache-sweeper", interval, move || {
+      core.sweep_expired();
+    })
+    .map_err(|err| Error::from_reason(format!("Failed to start sweeper: {err}")))?;
+
+    *sweeper = Some(task);
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn prune_expired(&self) -> u32 {
+    self.sweep_expired()
+  }
+
+  #[napi]
+  pub fn evict(&self, count: u32) -> u32 {
+    let mut evicted = 0;
+    while evicted < count && self.evict_one() {
+      evicted += 1;
+    }
+    evicted
+  }
+
+  #[napi]
+  pub fn stop_sweeper(&self) -> bool {
+    self.sweeper.lock().take().is_some()
+  }
+
+  #[napi]
+  pub fn save_snapshot(&self, path: String) -> Result<u32> {
+    self
+      .core
+      .save_snapshot(Path::new(&path))
+      .map_err(|err| Error::from_reason(format!("Failed to save snapshot to '{path}': {err}")))
+  }
+
+  #[napi]
+  pub fn load_snapshot(&self, path: String) -> Result<u32> {
+    let snapshot = snapshot::read(Path::new(&path))
+      .map_err(|err| Error::from_reason(format!("Failed to load snapshot from '{path}': {err}")))?;
+
+    for (namespace, generation) in snapshot.generations {
+      self.restore_generation(&namespace, generation);
+    }
+    let mut loaded = 0;
+    for entry in snapshot.entries {
+      loaded += self.restore_entry(entry) as u32;
+    }
+    Ok(loaded)
+  }
+
+  #[napi]
+  pub fn enable_append_log(
+    &self,
+    path: String,
+    compact_interval_seconds: Option<u32>,
+  ) -> Result<u32> {
+    let mut compactor = self.compactor.lock();
+    if self.append_log.lock().is_some() {
+      return Err(Error::from_reason("Append log is already enabled"));
+    }
+
+    let path = Path::new(&path).to_path_buf();
+    let failed = |err: std::io::Error| {
+      Error::from_reason(format!(
+        "Failed to open append log '{}': {err}",
+        path.display()
+      ))
+    };
+
+    let replay = append_log::replay(&path).map_err(failed)?;
+    self.add_stat(Stat::AppendLogTruncatedBytes, replay.truncated_bytes);
+
+    let mut replayed = 0;
+    for record in replay.records {
+      match record {
+        Replayed::Put(entry) => replayed += self.restore_entry(*entry) as u32,
+        Replayed::Remove(key, version) => {
+          self.version_counter.fetch_max(version, Ordering::Relaxed);
+          let key_hash = self.hash_key(&key);
+          let current = self
+            .storage
+            .get(&key_hash)
+            .is_none_or(|entry| entry.version == version);
+          if current {
+            self.remove_entry(&key_hash);
+          }
+        }
+        Replayed::Clear => {
+          self.clear_entries();
+        }
+        Replayed::Generation(namespace, generation) => {
+          self.restore_generation(&namespace, generation);
+        }
+      }
+    }
+    if self.stale_generations.swap(false, Ordering::Relaxed) {
+      self.purge_stale_generations();
+    }
+
+    *self.append_log.lock() = Some(AppendLog::open(&path).map_err(failed)?);
+    self.log_queue.open();
+
+    let core = self.core.clone();
+    let writer = BackgroundTask::spawn(
+      "microservice-cache-log-writer",
+      LOG_FLUSH_INTERVAL,
+      move || {
+        if core.flush_log().is_err() {
+          core.increment_stat(Stat::AppendLogFailures);
+        }
+      },
+    )
+    .map_err(|err| Error::from_reason(format!("Failed to start append log writer: {err}")))?;
+    *self.log_writer.lock() = Some(writer);
+    self.compact_append_log()?;
+
+    if let Some(interval_seconds) = compact_interval_seconds {
+      let core = self.core.clone();
+      let interval = Duration::from_secs(interval_seconds.max(1) as u64);
+      let task = BackgroundTask::spawn("microservice-cache-compactor", interval, move || {
+        if core.compact_log().is_err() {
+          core.increment_stat(Stat::AppendLogFailures);
+        }
+      })
+      .map_err(|err| Error::from_reason(format!("Failed to start compaction: {err}")))?;
+      *compactor = Some(task);
+    }
+
+    Ok(replayed)
+  }
+
+  #[napi]
+  pub fn compact_append_log(&self) -> Result<u32> {
+    self
+      .core
+      .compact_log()
+      .map_err(|err| Error::from_reason(format!("Failed to compact append log: {err}")))
+  }
+
+  #[napi]
+  pub fn disable_append_log(&self) -> bool {
+    self.compactor.lock().take();
+    self.log_writer.lock().take();
+    let mut log = self.append_log.lock();
+    let queued = self.log_queue.close();
+    match log.take() {
+      Some(mut log) => {
+        if log.append(&queued).and_then(|_| log.sync()).is_err() {
+          self.increment_stat(Stat::AppendLogFailures);
+        }
+        true
+      }
+      None => false,
+    }
+  }
+
+  #[napi]
+  pub fn enable_autosave(&self, interval_seconds: u32, path: String) -> Result<bool> {
+    let mut autosave = self.autosave.lock();
+    if autosave.is_some() {
+      return Ok(false);
+    }
+
+    let core = self.core.clone();
+    let interval = Duration::from_secs(interval_seconds.max(1) as u64);
+    let task = BackgroundTask::spawn("microservice-cache-autosave", interval, move || {
+      if core.save_snapshot(Path::new(&path)).is_err() {
+        core.increment_stat(Stat::SnapshotFailures);
+      }
+    })
+    .map_err(|err| Error::from_reason(format!("Failed to start autosave: {err}")))?;
+
+    *autosave = Some(task);
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn disable_autosave(&self) -> bool {
+    self.autosave.lock().take().is_some()
+  }
+
+  #[napi]
+  pub fn start_statsd_emitter(
+    &self,
+    address: String,
+    interval_ms: Option<u32>,
+    prefix: Option<String>,
+    tags: Option<Vec<String>>,
+  ) -> Result<bool> {
+    let mut statsd = self.statsd.lock();
+    if statsd.is_some() {
+      return Ok(false);
+    }
+
+    let prefix = prefix.unwrap_or_else(|| "microservice_cache.".to_string());
+    let mut emitter = StatsdEmitter::connect(&address, prefix, &tags.unwrap_or_default())
+      .map_err(|err| Error::from_reason(format!("Failed to reach StatsD at '{address}': {err}")))?;
+
+    let core = self.core.clone();
+    let interval = Duration::from_millis(interval_ms.unwrap_or(10_000).max(1) as u64);
+    let task = BackgroundTask::spawn("microservice-cache-statsd", interval, move || {
+      emitter.emit(&core.statsd_sample());
+    })
+    .map_err(|err| Error::from_reason(format!("Failed to start StatsD emitter: {err}")))?;
+
+    *statsd = Some(task);
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn stop_statsd_emitter(&self) -> bool {
+    self.statsd.lock().take().is_some()
+  }
+
+  #[napi(ts_args_type = "namespace: string, persist: (record: ChangeRecord) => Promise<void>")]
+  pub fn enable_write_through(&self, namespace: String, persist: Persister) -> Result<bool> {
+    validate_namespace(&namespace)?;
+    match self.write_through.entry(namespace) {
+      Entry::Occupied(_) => Ok(false),
+      Entry::Vacant(slot) => {
+        slot.insert(Arc::new(persist));
+        Ok(true)
+      }
+    }
+  }
+
+  #[napi]
+  pub fn disable_write_through(&self, namespace: String) -> bool {
+    self.write_through.remove(&namespace).is_some()
+  }
+
+  #[napi(
+    ts_args_type = "writer: (batch: Array<ChangeRecord>) => Promise<void>, batchSize?: number | undefined | null, flushIntervalMs?: number | undefined | null, maxQueue?: number | undefined | null"
+  )]
+  pub fn enable_write_behind(
+    &self,
+    writer: BatchWriter,
+    batch_size: Option<u32>,
+    flush_interval_ms: Option<u32>,
+    max_queue: Option<u32>,
+  ) -> Result<bool> {
+    let batch_size = batch_size.unwrap_or(100);
+    if batch_size == 0 {
+      return Err(Error::from_reason(
+        "Invalid write-behind queue: batchSize must be positive",
+      ));
+    }
+
+    let mut flusher = self.write_behind_flusher.lock();
+    if flusher.is_some() {
+      return Ok(false);
+    }
+
+    let queue = Arc::new(WriteBehind::new(
+      writer,
+      batch_size as usize,
+      max_queue.unwrap_or(10_000) as usize,
+    ));
+    let core = self.core.clone();
+    let flushed = queue.clone();
+    let interval = Duration::from_millis(flush_interval_ms.unwrap_or(1_000).max(1) as u64);
+    let task = BackgroundTask::spawn("microservice-cache-write-behind", interval, move || {
+      core.dispatch_write_behind(&flushed, true);
+    })
+    .map_err(|err| Error::from_reason(format!("Failed to start write-behind flusher: {err}")))?;
+
+    *self.write_behind.write() = Some(queue);
+    *flusher = Some(task);
+    Ok(true)
+  }
+
+  #[napi]
+  pub async fn flush_write_behind(&self) -> Result<u32> {
+    let Some(queue) = self.write_behind.read().clone() else {
+      return Ok(0);
+    };
+
+    let pending = queue.pending() as u32;
+    loop {
+      match queue.take_batch(true) {
+        Some(batch) => {
+          self.write_batch(&queue, batch).await?;
+        }
+        None if queue.is_idle() => return Ok(pending),
+        None => tokio::time::sleep(Duration::from_millis(5)).await,
+      }
+    }
+  }
+
+  #[napi]
+  pub fn disable_write_behind(&self) -> bool {
+    self.write_behind.write().take();
+    self.write_behind_flusher.lock().take().is_some()
+  }
+
+  #[napi]
+  pub async fn shutdown(&self, snapshot_path: Option<String>) -> Result<ShutdownReport> {
+    self.draining.store(true, Ordering::Release);
+    self.stop_sweeper();
+    self.disable_autosave();
+    self.stop_statsd_emitter();
+
+    let flushed = self.flush_write_behind().await;
+    if flushed.is_ok() {
+      self.disable_write_behind();
+    }
+    let snapshot_entries = snapshot_path
+      .map(|path| self.save_snapshot(path))
+      .transpose();
+    self.disable_append_log();
+    self.flush_remote();
+
+    Ok(ShutdownReport {
+      flushed: flushed?,
+      snapshot_entries: snapshot_entries?,
+    })
+  }
+
+  #[napi]
+  pub fn is_draining(&self) -> bool {
+    self.core.is_draining()
+  }
+
+  #[napi]
+  pub fn enable_open_telemetry(
+    &self,
+    endpoint: String,
+    service_name: Option<String>,
+    traces: Option<bool>,
+  ) -> Result<bool> {
+    let mut telemetry = self.telemetry.write();
+    if telemetry.is_some() {
+      return Ok(false);
+    }
+
+    let service_name = service_name.unwrap_or_else(|| "microservice-cache".to_string());
+    let exporter =
+      Telemetry::start(&endpoint, &service_name, traces.unwrap_or(false)).map_err(|err| {
+        Error::from_reason(format!(
+          "Failed to start OpenTelemetry export to '{endpoint}': {err}"
+        ))
+      })?;
+    *telemetry = Some(exporter);
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn disable_open_telemetry(&self) -> bool {
+    let exporter = self.telemetry.write().take();
+    exporter.is_some()
+  }
+
+  #[napi(
+    ts_generic_types = "T",
+    ts_args_type = "traceparent: string, callback: () => T",
+    ts_return_type = "T"
+  )]
+  pub fn with_trace_context<'scope>(
+    &self,
+    traceparent: String,
+    callback: Function<'scope, (), Unknown<'scope>>,
+  ) -> Result<Unknown<'scope>> {
+    let parent = TraceParent::parse(&traceparent)
+      .ok_or_else(|| Error::from_reason(format!("Invalid traceparent '{traceparent}'")))?;
+    telemetry::with_trace_parent(parent, || callback.call(()))
+  }
+
+  #[napi]
+  pub fn enable_early_expiration(&self, beta: Option<f64>) -> Result<bool> {
+    let beta = beta.unwrap_or(1.0);
+    if !(beta > 0.0 && beta.is_finite()) {
+      return Err(Error::from_reason(
+        "Invalid early expiration: beta must be a positive number",
+      ));
+    }
+    Ok(
+      self
+        .xfetch_beta
+        .compare_exchange(0, beta.to_bits(), Ordering::Relaxed, Ordering::Relaxed)
+        .is_ok(),
+    )
+  }
+
+  #[napi]
+  pub fn disable_early_expiration(&self) -> bool {
+    self.xfetch_beta.swap(0, Ordering::Relaxed) != 0
+  }
+
+  #[napi]
+  pub fn enable_bloom_filter(
+    &self,
+    expected_items: u32,
+    false_positive_rate: Option<f64>,
+  ) -> Result<bool> {
+    let false_positive_rate = false_positive_rate.unwrap_or(0.01);
+    if expected_items == 0 || !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+      return Err(Error::from_reason(
+        "Invalid Bloom filter: expectedItems must be positive and falsePositiveRate between 0 and 1",
+      ));
+    }
+
+    let mut bloom = self.bloom.write();
+    if bloom.is_some() {
+      return Ok(false);
+    }
+    *bloom = Some(BloomFilter::new(expected_items as u64, false_positive_rate));
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn disable_bloom_filter(&self) -> bool {
+    self.bloom.write().take().is_some()
+  }
+
+  #[napi]
+  pub fn mark_missing(&self, key: String) -> bool {
+    match self.bloom.read().as_ref() {
+      Some(bloom) => {
+        bloom.insert(&key);
+        true
+      }
+      None => false,
+    }
+  }
+
+  #[napi]
+  pub fn might_contain(&self, key: String) -> bool {
+    self
+      .bloom
+      .read()
+      .as_ref()
+      .is_some_and(|bloom| bloom.contains(&key))
+  }
+
+  #[napi]
+  pub fn pf_add(
+    &self,
+    key: String,
+    items: Either<String, Vec<String>>,
+    ttl_seconds: Option<u32>,
+  ) -> Result<bool> {
+    self.ensure_direct_write(&key)?;
+    self.ensure_writable()?;
+    let items = Self::one_or_many(items);
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+
+    let changed = loop {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      match live {
+        Some(mut entry) => {
+          let mut sketch = self
+            .revealed(&entry.value, CacheValue::to_bytes)
+            .unwrap_or_else(hyperloglog::empty);
+          if hyperloglog::registers(&sketch).is_none() {
+            return Err(Error::from_reason(format!(
+              "Value at key '{key}' is not a HyperLogLog"
+            )));
+          }
+          let mut changed = false;
+          for item in &items {
+            changed |= hyperloglog::add(&mut sketch, item);
+          }
+          if !changed {
+            break false;
+          }
+          self
+            .rewrite_value(&key_hash, &mut entry, sketch.into())
+            .map_err(plain_error)?;
+          break true;
+        }
+        None => {
+          let mut sketch = hyperloglog::empty();
+          self
+            .make_room(&key_hash, (key.len() + sketch.len()) as u64)
+            .map_err(plain_error)?;
+          for item in &items {
+            hyperloglog::add(&mut sketch, item);
+          }
+          let entry = self
+            .new_entry(
+              key.clone(),
+              sketch.into(),
+              ttl_seconds.map(seconds_to_millis),
+              None,
+            )
+            .map_err(plain_error)?;
+          if self
+            .insert_entry_if(key_hash.clone(), entry, |live| live.is_none())
+            .is_some()
+          {
+            break true;
+          }
+        }
+      }
+    };
+
+    if changed {
+      self.increment_stat(Stat::Sets);
+      self.mirror(&key_hash);
+    }
+    Ok(changed)
+  }
+
+  #[napi]
+  pub fn pf_count(&self, keys: Either<String, Vec<String>>) -> Result<i64> {
+    let keys = Self::one_or_many(keys);
+
+    let mut sketches = Vec::with_capacity(keys.len());
+    for key in &keys {
+      let key_hash = self.locate(key);
+      self.read_through(key, &key_hash);
+      let Some(entry) = self.storage.get(&key_hash) else {
+        continue;
+      };
+      if entry.is_expired(self.now()) {
+        continue;
+      }
+      let Some(sketch) = self.revealed(&entry.value, CacheValue::to_bytes) else {
+        continue;
+      };
+      if hyperloglog::registers(&sketch).is_none() {
+        return Err(Error::from_reason(format!(
+          "Value at key '{key}' is not a HyperLogLog"
+        )));
+      }
+      sketches.push(sketch);
+    }
+
+    let registers: Vec<&[u8]> = sketches
+      .iter()
+      .filter_map(|sketch| hyperloglog::registers(sketch))
+      .collect();
+    Ok(hyperloglog::count(&registers) as i64)
+  }
+
+  #[napi]
+  pub fn enable_disk_tier(&self, path: String) -> Result<bool> {
+    let mut disk_tier = self.disk_tier.write();
+    if disk_tier.is_some() {
+      return Ok(false);
+    }
+
+    let tier = DiskTier::open(Path::new(&path), self.key_hasher_name())
+      .map_err(|err| Error::from_reason(format!("Failed to open disk tier '{path}': {err}")))?;
+    *disk_tier = Some(tier);
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn disable_disk_tier(&self) -> bool {
+    match self.disk_tier.write().take() {
+      Some(tier) => {
+        tier.flush();
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Writes and deletes reach Redis from a background queue. Read-modify-write calls
+  /// (`setIfAbsent`, `increment`, `hset`, ...) on keys missing from memory still block the
+  /// JavaScript thread on a Redis round-trip of up to 1 second.
+  #[napi]
+  pub fn enable_redis_tier(&self, url: String, key_prefix: Option<String>) -> Result<bool> {
+    let mut remote_tier = self.remote_tier.write();
+    if remote_tier.is_some() {
+      return Ok(false);
+    }
+
+    let tier = RedisTier::connect(&url, key_prefix.unwrap_or_default())
+      .map_err(|err| Error::from_reason(format!("Failed to connect to Redis at '{url}': {err}")))?;
+    let core = self.core.clone();
+    let writer = BackgroundTask::spawn(
+      "microservice-cache-redis-writer",
+      REMOTE_FLUSH_INTERVAL,
+      move || core.flush_remote(),
+    )
+    .map_err(|err| Error::from_reason(format!("Failed to start Redis writer: {err}")))?;
+    *remote_tier = Some(tier);
+    *self.remote_writer.lock() = Some(writer);
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn disable_redis_tier(&self) -> bool {
+    self.remote_writer.lock().take();
+    self.flush_remote();
+    self.remote_tier.write().take().is_some()
+  }
+
+  #[napi(
+    ts_args_type = "options?: ChainOptions | undefined | null, loader?: ((key: string) => Promise<string>) | undefined | null"
+  )]
+  pub fn create_chain(
+    &self,
+    options: Option<ChainOptions>,
+    loader: Option<KeyLoader>,
+  ) -> Result<CacheChain> {
+    let options = options.unwrap_or_default();
+    let second = match (options.redis_url, options.disk_path) {
+      (Some(_), Some(_)) => {
+        return Err(Error::from_reason(
+          "Invalid cache chain: choose either redisUrl or diskPath for L2",
+        ))
+      }
+      (Some(url), None) => Some(SecondLevel::Redis(
+        RedisTier::connect(&url, options.redis_prefix.unwrap_or_default()).map_err(|err| {
+          Error::from_reason(format!("Failed to connect to Redis at '{url}': {err}"))
+        })?,
+      )),
+      (None, Some(path)) => Some(SecondLevel::Disk(
+        DiskTier::open(Path::new(&path), self.key_hasher_name())
+          .map_err(|err| Error::from_reason(format!("Failed to open disk tier '{path}': {err}")))?,
+      )),
+      (None, None) => None,
+    };
+
+    let chain = Arc::new(Chain::new(
+      second,
+      loader,
+      options.demotion.unwrap_or_default(),
+      options.ttl_seconds.map(seconds_to_millis),
+      options.l1_ttl_seconds.map(seconds_to_millis),
+      options.promote_after_hits.unwrap_or(1),
+    ));
+    let mut chains = self.chains.write();
+    chains.retain(|chain| chain.strong_count() > 0);
+    chains.push(Arc::downgrade(&chain));
+
+    Ok(CacheChain {
+      core: self.core.clone(),
+      chain,
+    })
+  }
+
+  #[napi]
+  pub fn enable_invalidation(&self, url: String, channel: Option<String>) -> Result<bool> {
+    let mut invalidation = self.invalidation.lock();
+    if invalidation.is_some() {
+      return Ok(false);
+    }
+
+    let (core, linked) = (self.core.clone(), self.core.clone());
+    let channel = channel.unwrap_or_else(|| "microservice-cache:invalidations".to_string());
+    let bus = InvalidationBus::connect(
+      &url,
+      &channel,
+      move |invalidation| {
+        let removed = match invalidation {
+          Invalidation::Key { key } => core
+            .remove_entry(&core.hash_key(&key))
+            .into_iter()
+            .collect(),
+          Invalidation::Tag { tag } => core.remove_tagged(&tag),
+          Invalidation::Prefix { prefix } => core
+            .matching_keys(|key| key.starts_with(&prefix))
+            .into_iter()
+            .filter_map(|key| core.remove_entry(&core.hash_key(&key)))
+            .collect(),
+          Invalidation::Generation { namespace } => {
+            core.bump_namespace_generation(&namespace);
+            Vec::new()
+          }
+        };
+        for entry in &removed {
+          core.notify_removal(entry, EvictionReason::Delete);
+        }
+        let removed = removed.len() as u64;
+        core.increment_stat(Stat::InvalidationsReceived);
+        core.add_stat(Stat::InvalidatedEntries, removed);
+      },
+      move |event| {
+        linked.increment_stat(match event {
+          LinkEvent::Lost => Stat::InvalidationFailures,
+          LinkEvent::Restored => Stat::InvalidationReconnects,
+        });
+      },
+    )
+    .map_err(|err| {
+      Error::from_reason(format!(
+        "Failed to subscribe to invalidations at '{url}': {err}"
+      ))
+    })?;
+
+    *invalidation = Some(bus);
+    Ok(true)
+  }
+
+  #[napi]
+  pub fn disable_invalidation(&self) -> bool {
+    self.invalidation.lock().take().is_some()
+  }
+
+  #[napi]
+  pub fn flush(&self) -> u32 {
+    let count = self.clear_entries();
+    self.increment_stat(Stat::Flushes);
+    count
+  }
+
+  async fn run_loader(
+    &self,
+    key: String,
+    loader: Loader,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<String> {
+    let started = Instant::now();
+    let value = self
+      .with_retries(|| async { loader.call_async_catch(()).await?.await })
+      .await;
+    let elapsed = started.elapsed();
+    self.latencies.loader.record(elapsed);
+    self.record_load(&key, value.is_ok());
+    let value = match value {
+      Ok(value) => value,
+      Err(err) => return self.fall_back(&key, err).await,
+    };
+    self.increment_stat(Stat::Loads);
+    if self.is_draining() {
+      return Ok(value);
+    }
+    self
+      .text_entry(key.clone(), value.clone(), ttl_seconds, tags, None, None)
+      .and_then(|entry| self.store_persisted(entry))
+      .map_err(plain_error)?;
+    if let Some(mut entry) = self.storage.get_mut(&self.locate(&key)) {
+      entry.compute_ms = elapsed.as_millis() as u64;
+    }
+    Ok(value)
+  }
+}
+
+impl MicroserviceCache {
+  fn from_shared(shared: SharedCache) -> Self {
+    Self {
+      core: shared.core,
+      sweeper: Mutex::new(None),
+      autosave: Mutex::new(None),
+      compactor: Mutex::new(None),
+      log_writer: Mutex::new(None),
+      remote_writer: Mutex::new(None),
+      invalidation: shared.invalidation,
+      statsd: Mutex::new(None),
+      write_behind_flusher: Mutex::new(None),
+    }
+  }
+
+  fn publish(&self, token: String) -> bool {
+    shared::publish(
+      token,
+      SharedCache {
+        core: self.core.clone(),
+        invalidation: self.invalidation.clone(),
+      },
+    )
+  }
+
+  fn store_text(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+    tti_seconds: Option<u32>,
+    priority: Option<Priority>,
+  ) -> Result<i64, CacheErrorCode> {
+    let entry = self.text_entry(key, value, ttl_seconds, tags, tti_seconds, priority)?;
+    self.store_entry(entry)
+  }
+
+  fn text_entry(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+    tti_seconds: Option<u32>,
+    priority: Option<Priority>,
+  ) -> Result<CacheEntry, CacheErrorCode> {
+    Ok(
+      self
+        .new_entry(key, value.into(), ttl_seconds.map(seconds_to_millis), tags)?
+        .with_idle_timeout(
+          tti_seconds
+            .map(seconds_to_millis)
+            .or(self.limits().default_tti_ms),
+        )
+        .with_priority(priority),
+    )
+  }
+
+  fn store_versioned(
+    &self,
+    entry: CacheEntry,
+    expected_version: i64,
+  ) -> Result<i64, CacheErrorCode> {
+    self.ensure_writable().map_err(coded_error)?;
+    self.check_entry(&entry)?;
+    let key = entry.original_key.clone();
+    let key_hash = self.hash_key(&key);
+    self.read_through(&key, &key_hash);
+
+    let mut found = self.current_version(&key_hash);
+    let written = if found == expected_version {
+      self.make_room(&key_hash, entry.weight())?;
+      self.insert_entry_if(key_hash.clone(), entry, |live| {
+        found = live_version(live);
+        found == expected_version
+      })
+    } else {
+      None
+    };
+
+    match written {
+      Some(version) => {
+        self.increment_stat(Stat::Sets);
+        self.mirror(&key_hash);
+        Ok(version as i64)
+      }
+      None => {
+        self.increment_stat(Stat::CasConflicts);
+        Err(Error::new(
+          CacheErrorCode::VersionConflict,
+          format!("Version conflict for key '{key}': expected {expected_version}, found {found}"),
+        ))
+      }
+    }
+  }
+
+  async fn in_turn<T>(&self, key_hash: &str, work: impl Future<Output = T>) -> T {
+    let queue = self
+      .computing
+      .entry(key_hash.to_string())
+      .or_default()
+      .clone();
+    let turn = queue.lock().await;
+    let result = work.await;
+    drop(turn);
+    drop(queue);
+    self
+      .computing
+      .remove_if(key_hash, |_, queue| Arc::strong_count(queue) == 1);
+    result
+  }
+
+  async fn compute_queued(
+    &self,
+    key: &str,
+    key_hash: &str,
+    transform: &AsyncTransform,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<Option<String>, CacheErrorCode> {
+    for _ in 0..MAX_COMPUTE_ATTEMPTS {
+      let (current, version) = self.versioned_text(key);
+      let next = async { transform.call_async(current).await?.await }
+        .await
+        .map_err(coded_error)?;
+
+      let locks = self.storage.lock_keys([key_hash]);
+      if self.current_version(key_hash) != version {
+        continue;
+      }
+      let deleted = self.apply_computed(key, next.clone(), ttl_seconds, tags.clone())?;
+      drop(locks);
+      if deleted {
+        self.broadcast(Invalidation::Key {
+          key: key.to_string(),
+        });
+      }
+      return Ok(next);
+    }
+    Err(self.compute_conflict(key))
+  }
+
+  fn compute_conflict(&self, key: &str) -> Error<CacheErrorCode> {
+    self.increment_stat(Stat::CasConflicts);
+    Error::new(
+      CacheErrorCode::VersionConflict,
+      format!("Key '{key}' changed during {MAX_COMPUTE_ATTEMPTS} compute attempts"),
+    )
+  }
+
+  fn versioned_text(&self, key: &str) -> (Option<String>, i64) {
+    self
+      .lookup_versioned(key.to_string(), |value, version| {
+        Some((Some(value.to_text()?), version as i64))
+      })
+      .flatten()
+      .unwrap_or((None, 0))
+  }
+
+  fn apply_computed(
+    &self,
+    key: &str,
+    next: Option<String>,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<bool, CacheErrorCode> {
+    match next {
+      Some(value) => {
+        self.store_text(key.to_string(), value, ttl_seconds, tags, None, None)?;
+        Ok(false)
+      }
+      None => Ok(self.delete_key(key)),
+    }
+  }
+
+  fn relocate(
+    &self,
+    source: String,
+    target: String,
+    keep_source: bool,
+    ttl_seconds: Option<u32>,
+  ) -> Result<bool, CacheErrorCode> {
+    self.ensure_writable().map_err(coded_error)?;
+    self.ensure_direct_write(&target).map_err(coded_error)?;
+    if !keep_source {
+      self.ensure_direct_write(&source).map_err(coded_error)?;
+    }
+    if source == target {
+      return Err(Error::new(
+        CacheErrorCode::GenericFailure,
+        format!("Source and destination keys are both '{source}'"),
+      ));
+    }
+    let source_hash = self.locate(&source);
+    self.read_through(&source, &source_hash);
+    let target_hash = self.hash_key(&target);
+
+    let locks = self
+      .storage
+      .lock_keys([source_hash.as_str(), target_hash.as_str()]);
+    let Some(mut entry) = self
+      .storage
+      .get(&source_hash)
+      .filter(|entry| !entry.is_expired(self.now()))
+      .map(|entry| entry.clone())
+    else {
+      return Ok(false);
+    };
+    entry.original_key = target.clone();
+    entry.version = 0;
+    if let Some(ttl_seconds) = ttl_seconds {
+      entry.set_expiry(
+        Some(self.now() + seconds_to_millis(ttl_seconds)),
+        self.now(),
+      );
+    }
+    self.check_entry(&entry)?;
+    if keep_source {
+      self.make_room(&target_hash, entry.weight())?;
+    } else if let Some(removed) = self.remove_entry(&source_hash) {
+      self.invalidate_dependents(&source_hash);
+      self.notify_removal(&removed, EvictionReason::Delete);
+    }
+    self.insert_entry(target_hash.clone(), entry);
+    self.increment_stat(Stat::Sets);
+    drop(locks);
+
+    if !keep_source {
+      self.delete_through(&source);
+      self.broadcast(Invalidation::Key { key: source });
+    }
+    self.mirror(&target_hash);
+    self.broadcast(Invalidation::Key { key: target });
+    Ok(true)
+  }
+
+  async fn persist_change(
+    &self,
+    op: ChangeOperation,
+    key: &str,
+    value: Option<String>,
+  ) -> Result<()> {
+    let Some(persister) = self.persister_for(key) else {
+      return Ok(());
+    };
+
+    let record = ChangeRecord {
+      sequence: self.next_version() as i64,
+      op,
+      key: key.to_string(),
+      value,
+      timestamp: wall_millis() as i64,
+    };
+    let persisted = match persister.call_async_catch(record).await {
+      Ok(pending) => pending.await,
+      Err(err) => Err(err),
+    };
+    persisted.map_err(|err| {
+      self.increment_stat(Stat::WriteThroughFailures);
+      Error::from_reason(format!(
+        "Write-through persistence failed for key '{key}': {}",
+        err.reason
+      ))
+    })?;
+    self.increment_stat(Stat::WriteThroughWrites);
+    Ok(())
+  }
+
+  fn one_or_many(values: Either<String, Vec<String>>) -> Vec<String> {
+    match values {
+      Either::A(value) => vec![value],
+      Either::B(values) => values,
+    }
+  }
+
+  fn update_score(
+    &self,
+    key: String,
+    member: String,
+    ttl_seconds: Option<u32>,
+    score: impl Fn(Option<f64>) -> f64,
+  ) -> Result<(bool, f64)> {
+    self.ensure_direct_write(&key)?;
+    self.ensure_writable()?;
+    let place = |members: &mut ScoredMembers| {
+      let previous = members.score(&member);
+      let next = score(previous);
+      if !next.is_finite() {
+        return Err(Error::from_reason(format!(
+          "Invalid score for member '{member}' of key '{key}'"
+        )));
+      }
+      members.insert(member.clone(), next);
+      Ok((previous.is_none(), next))
+    };
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+
+    self
+      .make_room(&key_hash, (key.len() + member.len() + 8) as u64)
+      .map_err(plain_error)?;
+
+    let updated = loop {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      match live {
+        Some(mut entry) => {
+          let updated = self.edit_value(&key, &key_hash, &mut entry, |stored| {
+            place(CacheCore::sorted_set_of(&key, stored)?).map(Some)
+          })?;
+          break updated.unwrap_or_default();
+        }
+        None => {
+          let mut members = ScoredMembers::default();
+          let updated = place(&mut members)?;
+          let entry = self
+            .new_entry(
+              key.clone(),
+              CacheValue::Collection(Collection::SortedSet(members)),
+              ttl_seconds.map(seconds_to_millis),
+              None,
+            )
+            .map_err(plain_error)?;
+          if self
+            .insert_entry_if(key_hash.clone(), entry, |live| live.is_none())
+            .is_some()
+          {
+            break updated;
+          }
+        }
+      }
+    };
+
+    self.increment_stat(Stat::Sets);
+    self.mirror(&key_hash);
+    Ok(updated)
+  }
+
+  fn push_items(
+    &self,
+    key: String,
+    values: Either<String, Vec<String>>,
+    ttl_seconds: Option<u32>,
+    max_length: Option<u32>,
+    front: bool,
+  ) -> Result<u32> {
+    self.ensure_direct_write(&key)?;
+    self.ensure_writable()?;
+    let values = Self::one_or_many(values);
+    let push = |items: &mut ListItems| {
+      for value in &values {
+        items.push(value.clone(), front);
+      }
+      if let Some(max_length) = max_length {
+        while items.len() > max_length as usize {
+          items.pop(!front);
+        }
+      }
+      items.len() as u32
+    };
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+
+    let pushed_bytes: usize = values.iter().map(String::len).sum();
+    self
+      .make_room(&key_hash, (key.len() + pushed_bytes) as u64)
+      .map_err(plain_error)?;
+
+    let length = loop {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      match live {
+        Some(mut entry) => {
+          let length = self.edit_value(&key, &key_hash, &mut entry, |stored| {
+            Ok(Some(push(CacheCore::list_of(&key, stored)?)))
+          })?;
+          break length.unwrap_or_default();
+        }
+        None => {
+          let mut items = ListItems::default();
+          let length = push(&mut items);
+          let entry = self
+            .new_entry(
+              key.clone(),
+              CacheValue::Collection(Collection::List(items)),
+              ttl_seconds.map(seconds_to_millis),
+              None,
+            )
+            .map_err(plain_error)?;
+          if self
+            .insert_entry_if(key_hash.clone(), entry, |live| live.is_none())
+            .is_some()
+          {
+            break length;
+          }
+        }
+      }
+    };
+
+    self.increment_stat(Stat::Sets);
+    self.mirror(&key_hash);
+    Ok(length)
+  }
+
+  fn pop_item(&self, key: String, front: bool) -> Result<Option<String>> {
+    self.ensure_writable()?;
+    let key_hash = self.locate(&key);
+    self.read_through(&key, &key_hash);
+
+    let locks = self.storage.lock_keys([key_hash.as_str()]);
+    let popped = {
+      let live = self
+        .storage
+        .get_mut(&key_hash)
+        .filter(|entry| !entry.is_expired(self.now()));
+      let Some(mut entry) = live else {
+        return Ok(None);
+      };
+      self.edit_value(&key, &key_hash, &mut entry, |stored| {
+        let items = CacheCore::list_of(&key, stored)?;
+        Ok(items.pop(front).map(|item| (item, items.is_empty())))
+      })?
+    };
+    let Some((item, emptied)) = popped else {
+      return Ok(None);
+    };
+    if emptied {
+      self.delete_key(&key);
+    }
+    drop(locks);
+
+    if emptied {
+      self.broadcast(Invalidation::Key { key });
+    } else {
+      self.mirror(&key_hash);
+    }
+    Ok(Some(item))
+  }
+
+  fn http_variant_of(
+    &self,
+    url: &str,
+    request_headers: &HashMap<String, String>,
+  ) -> Option<String> {
+    let vary = self.inspect(&http_cache::vary_key(url), |entry| {
+      self.read_value(entry, CacheValue::to_text)
+    })?;
+    let vary: Vec<String> = vary
+      .split(',')
+      .filter(|name| !name.is_empty())
+      .map(str::to_string)
+      .collect();
+    Some(http_cache::variant_key(url, request_headers, &vary))
+  }
+
+  fn broadcast(&self, invalidation: Invalidation) {
+    self
+      .core
+      .publish_invalidation(&self.invalidation, invalidation);
+  }
+}
+
+#[napi]
+impl CacheChain {
+  #[napi]
+  pub async fn get(&self, key: String) -> Result<Option<String>> {
+    let chain = &self.chain;
+    if let Some(found) = self.core.lookup(key.clone(), CacheValue::to_text) {
+      bump(&chain.l1.hits);
+      return Ok(found);
+    }
+    bump(&chain.l1.misses);
+
+    if let Some((value, ttl_ms)) = self.core.fetch_from_chain(chain, &key) {
+      if chain.should_promote(&key) {
+        let stored = self
+          .core
+          .new_entry(
+            key.clone(),
+            value.clone().into(),
+            chain.l1_ttl(ttl_ms),
+            None,
+          )
+          .and_then(|entry| self.core.store_persisted(entry));
+        if stored.is_ok() {
+          chain.claim(&key);
+          bump(&chain.promotions);
+          bump(&chain.l1.writes);
+        }
+      }
+      return Ok(Some(value));
+    }
+
+    let Some(loader) = &chain.loader else {
+      bump(&chain.origin.misses);
+      return Ok(None);
+    };
+    let key_hash = self.core.hash_key(&key);
+    if let LoadSlot::Waiter(mut receiver) = self.core.claim_load(&key_hash) {
+      self.core.increment_stat(Stat::CoalescedLoads);
+      return match receiver.recv().await {
+        Ok(result) => result.map(Some).map_err(Error::from_reason),
+        Err(err) => Err(Error::from_reason(format!("In-flight load aborted: {err}"))),
+      };
+    }
+
+    let loaded = match loader.call_async_catch(key.clone()).await {
+      Ok(pending) => pending.await,
+      Err(err) => Err(err),
+    };
+    self.core.finish_load(&key_hash, &loaded);
+    let value = loaded.inspect_err(|_| bump(&chain.origin.errors))?;
+    bump(&chain.origin.hits);
+    let _ = self.write(key, value.clone(), chain.ttl_ms);
+    Ok(Some(value))
+  }
+
+  #[napi]
+  pub fn set(&self, key: String, value: String, ttl_seconds: Option<u32>) -> Result<i64> {
+    self.write(
+      key,
+      value,
+      ttl_seconds.map(seconds_to_millis).or(self.chain.ttl_ms),
+    )
+  }
+
+  #[napi]
+  pub fn delete(&self, key: String) -> bool {
+    self.chain.forget(&key);
+    let in_l2 = self.core.remove_from_chain(&self.chain, &key);
+    self.core.delete_key(&key) || in_l2
+  }
+
+  #[napi]
+  pub fn get_stats(&self) -> ChainStats {
+    let level = |counters: &chain::LevelCounters| ChainLevelStats {
+      hits: counters.hits.load(Ordering::Relaxed) as i64,
+      misses: counters.misses.load(Ordering::Relaxed) as i64,
+      writes: counters.writes.load(Ordering::Relaxed) as i64,
+      errors: counters.errors.load(Ordering::Relaxed) as i64,
+    };
+    ChainStats {
+      l1: level(&self.chain.l1),
+      l2: level(&self.chain.l2),
+      loader: level(&self.chain.origin),
+      promotions: self.chain.promotions.load(Ordering::Relaxed) as i64,
+      demotions: self.chain.demotions.load(Ordering::Relaxed) as i64,
+    }
+  }
+}
+
+impl CacheChain {
+  fn write(&self, key: String, value: String, ttl_ms: Option<u64>) -> Result<i64> {
+    let chain = &self.chain;
+    chain.forget(&key);
+    if chain.demotion == ChainDemotion::WriteThrough {
+      self.core.store_in_chain(chain, &key, &value, ttl_ms);
+    }
+    let entry = self
+      .core
+      .new_entry(key.clone(), value.into(), chain.l1_ttl(ttl_ms), None)
+      .map_err(plain_error)?;
+    let version = self
+      .core
+      .store_entry(entry)
+      .map_err(plain_error)
+      .inspect_err(|_| bump(&chain.l1.errors))?;
+    chain.claim(&key);
+    bump(&chain.l1.writes);
+    Ok(version)
+  }
+}
+
+#[napi]
+impl CacheNamespace {
+  #[napi(getter)]
+  pub fn name(&self) -> String {
+    self.namespace.name.clone()
+  }
+
+  #[napi]
+  pub fn set(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<i64, CacheErrorCode> {
+    let key = self.namespace.key(&key);
+    let ttl_ms = ttl_seconds
+      .map(seconds_to_millis)
+      .or(self.namespace.default_ttl_ms());
+    let entry = self.core.new_entry(key, value.into(), ttl_ms, tags)?;
+    self.core.store_entry(entry)
+  }
+
+  #[napi(ts_return_type = "string | CacheMiss | null")]
+  pub fn get(&self, key: String) -> Option<Either<SharedText, CacheMiss>> {
+    self
+      .core
+      .lookup(self.namespace.key(&key), CacheValue::shared_text)
+      .map(|value| match value {
+        Some(value) => Either::A(value),
+        None => Either::B(CacheMiss {}),
+      })
+  }
+
+  #[napi]
+  pub fn has(&self, key: String) -> bool {
+    self
+      .core
+      .inspect(&self.namespace.key(&key), |_| Some(()))
+      .is_some()
+  }
+
+  #[napi]
+  pub fn delete(&self, key: String) -> bool {
+    let key = self.namespace.key(&key);
+    let removed = self.core.delete_key(&key);
+    self
+      .core
+      .publish_invalidation(&self.invalidation, Invalidation::Key { key });
+    removed
+  }
+
+  #[napi]
+  pub fn keys(&self) -> Vec<String> {
+    self
+      .core
+      .storage
+      .iter()
+      .filter(|entry| {
+        !entry.is_expired(self.core.now()) && self.core.is_current(entry.key(), &entry.original_key)
+      })
+      .filter_map(|entry| {
+        self
+          .namespace
+          .strip(&entry.original_key)
+          .map(str::to_string)
+      })
+      .collect()
+  }
+
+  #[napi]
+  pub fn flush(&self) -> u32 {
+    let key_hashes: Vec<String> = self
+      .core
+      .storage
+      .iter()
+      .filter(|entry| self.namespace.strip(&entry.original_key).is_some())
+      .map(|entry| entry.key().to_string())
+      .collect();
+
+    let mut flushed = 0;
+    for key_hash in key_hashes {
+      if let Some(entry) = self.core.remove_entry(&key_hash) {
+        self.core.notify_removal(&entry, EvictionReason::Flush);
+        flushed += 1;
+      }
+    }
+    flushed
+  }
+
+  #[napi]
+  pub fn bump_generation(&self) -> i64 {
+    let generation = self.core.bump_namespace_generation(&self.namespace.name);
+    self.core.publish_invalidation(
+      &self.invalidation,
+      Invalidation::Generation {
+        namespace: self.namespace.name.clone(),
+      },
+    );
+    generation as i64
+  }
+
+  #[napi]
+  pub fn get_stats(&self) -> NamespaceStats {
+    let namespace = &self.namespace;
+    let hits = namespace.hits.load(Ordering::Relaxed) as i64;
+    let misses = namespace.misses.load(Ordering::Relaxed) as i64;
+    NamespaceStats {
+      entries: namespace.entries() as u32,
+      max_size: namespace.max_size().map(|max_size| max_size as u32),
+      hits,
+      misses,
+      hit_rate: if hits + misses > 0 {
+        hits as f64 / (hits + misses) as f64
+      } else {
+        0.0
+      },
+      sets: namespace.sets.load(Ordering::Relaxed) as i64,
+      deletes: namespace.deletes.load(Ordering::Relaxed) as i64,
+      evictions: namespace.evictions.load(Ordering::Relaxed) as i64,
+      bytes: namespace.bytes() as i64,
+    }
+  }
+}
+
+#[napi]
+impl CacheTransaction {
+  #[napi]
+  pub fn expect_version(&self, key: String, version: i64) {
+    self
+      .ops
+      .lock()
+      .push(TransactionOp::ExpectVersion(key, version));
+  }
+
+  #[napi]
+  pub fn watch(&self, keys: Vec<String>) {
+    let watched: Vec<TransactionOp> = keys
+      .into_iter()
+      .map(|key| {
+        let version = self.core.current_version(&self.core.hash_key(&key));
+        TransactionOp::ExpectVersion(key, version)
+      })
+      .collect();
+    self.ops.lock().extend(watched);
+  }
+
+  #[napi]
+  pub fn get(&self, key: String) {
+    self.ops.lock().push(TransactionOp::Get(key));
+  }
+
+  #[napi]
+  pub fn set(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<(), CacheErrorCode> {
+    let op = self.set_op(key, value, ttl_seconds, tags)?;
+    self.ops.lock().push(op);
+    Ok(())
+  }
+
+  #[napi]
+  pub fn delete(&self, key: String) {
+    self.ops.lock().push(TransactionOp::Delete(key));
+  }
+
+  #[napi]
+  pub fn exec_if_unchanged(
+    &self,
+    ops: Vec<TransactionOperation>,
+  ) -> Result<TransactionResult, CacheErrorCode> {
+    let mut queued = Vec::with_capacity(ops.len());
+    for op in ops {
+      queued.push(match op.op {
+        TransactionCommand::Get => TransactionOp::Get(op.key),
+        TransactionCommand::Delete => TransactionOp::Delete(op.key),
+        TransactionCommand::Set => {
+          let Some(value) = op.value else {
+            return Err(Error::new(
+              CacheErrorCode::GenericFailure,
+              format!("Transaction set for key '{}' requires a value", op.key),
+            ));
+          };
+          self.set_op(op.key, value, op.ttl_seconds, op.tags)?
+        }
+      });
+    }
+    self.ops.lock().extend(queued);
+    self.commit()
+  }
+
+  #[napi]
+  pub fn commit(&self) -> Result<TransactionResult, CacheErrorCode> {
+    let ops = std::mem::take(&mut *self.ops.lock());
+    let core = &self.core;
+    if ops
+      .iter()
+      .any(|op| matches!(op, TransactionOp::Set(_) | TransactionOp::Delete(_)))
+    {
+      core.ensure_writable().map_err(coded_error)?;
+    }
+    let key_hashes: Vec<String> = ops.iter().map(|op| core.hash_key(op.key())).collect();
+
+    let locks = core
+      .storage
+      .lock_keys(key_hashes.iter().map(String::as_str));
+    let conflicts: Vec<String> = ops
+      .iter()
+      .zip(&key_hashes)
+      .filter_map(|(op, key_hash)| match op {
+        TransactionOp::ExpectVersion(key, expected)
+          if core.current_version(key_hash) != *expected =>
+        {
+          Some(key.clone())
+        }
+        _ => None,
+      })
+      .collect();
+    if !conflicts.is_empty() {
+      drop(locks);
+      core.add_stat(Stat::CasConflicts, conflicts.len() as u64);
+      return Ok(TransactionResult {
+        committed: false,
+        conflicts,
+        values: Vec::new(),
+        versions: Vec::new(),
+      });
+    }
+    for (op, key_hash) in ops.iter().zip(&key_hashes) {
+      if let TransactionOp::Set(entry) = op {
+        core.make_room(key_hash, entry.weight())?;
+      }
+    }
+
+    let mut values = Vec::new();
+    let mut versions = Vec::new();
+    let mut written = Vec::new();
+    let mut deleted = Vec::new();
+    for (op, key_hash) in ops.into_iter().zip(key_hashes) {
+      match op {
+        TransactionOp::ExpectVersion(..) => {}
+        TransactionOp::Get(_) => values.push(
+          core
+            .storage
+            .get(&key_hash)
+            .filter(|entry| !entry.is_expired(core.now()))
+            .and_then(|entry| core.read_value(&entry, CacheValue::to_text)),
+        ),
+        TransactionOp::Set(entry) => {
+          versions.push(core.insert_entry(key_hash.clone(), *entry) as i64);
+          core.increment_stat(Stat::Sets);
+          written.push(key_hash);
+        }
+        TransactionOp::Delete(key) => {
+          core.delete_local(&key);
+          deleted.push(key);
+        }
+      }
+    }
+    drop(locks);
+
+    for key_hash in written {
+      core.mirror(&key_hash);
+    }
+    for key in deleted {
+      core.delete_through(&key);
+      core.publish_invalidation(&self.invalidation, Invalidation::Key { key });
+    }
+    Ok(TransactionResult {
+      committed: true,
+      conflicts: Vec::new(),
+      values,
+      versions,
+    })
+  }
+}
+
+impl CacheTransaction {
+  fn set_op(
+    &self,
+    key: String,
+    value: String,
+    ttl_seconds: Option<u32>,
+    tags: Option<Vec<String>>,
+  ) -> Result<TransactionOp, CacheErrorCode> {
+    let entry = self
+      .core
+      .new_entry(key, value.into(), ttl_seconds.map(seconds_to_millis), tags)?;
+    self.core.check_entry(&entry)?;
+    Ok(TransactionOp::Set(Box::new(entry)))
+  }
+}
+
+impl CacheCore {
+  fn lookup<T>(
+    &self,
+    key: String,
+    read: impl FnOnce(&CacheValue) -> Option<T>,
+  ) -> Option<Option<T>> {
+    self.lookup_versioned(key, |value, _| read(value))
+  }
+
+  fn lookup_versioned<T>(
+    &self,
+    key: String,
+    read: impl FnOnce(&CacheValue, u64) -> Option<T>,
+  ) -> Option<Option<T>> {
+    let started = Instant::now();
+    let span = self.trace("cache.get");
+    self.hot_keys.record(&key);
+    let found = self.resolve(key, read);
+    self.latencies.get.record(started.elapsed());
+    let outcome = match &found {
+      Some(Some(_)) => "hit",
+      Some(None) => "negative",
+      None => "miss",
+    };
+    self.record_operation(span, "get", outcome);
+    found
+  }
+
+  fn resolve<T>(
+    &self,
+    key: String,
+    read: impl FnOnce(&CacheValue, u64) -> Option<T>,
+  ) -> Option<Option<T>> {
+    let key_hash = self.locate(&key);
+    if self.known_missing(&key, &key_hash) {
+      self.increment_stat(Stat::BloomHits);
+      return Some(None);
+    }
+
+    if let Some(mut entry_ref) = self.storage.get_mut(&key_hash) {
+      if entry_ref.is_expired(self.now()) {
+        drop(entry_ref);
+        if let Some(entry) = self.remove_entry(&key_hash) {
+          self.groups.record(&key, &entry.tags, false);
+          self.notify_removal(&entry, EvictionReason::Ttl);
+        }
+        self.increment_stat(Stat::ExpiredHits);
+        return None;
+      }
+      if self.expires_early(&entry_ref) {
+        drop(entry_ref);
+        self.increment_stat(Stat::EarlyExpirations);
+        self.record_miss(&key);
+        return None;
+      }
+
+      self.touch_entry(&key_hash, &mut entry_ref);
+      let version = entry_ref.version;
+      let value = self.read_value(&entry_ref, |value| read(value, version));
+      if value.is_none() && !entry_ref.negative {
+        drop(entry_ref);
+        self.record_miss(&key);
+        return None;
+      }
+      self.reencrypt_stale_key(&mut entry_ref);
+      self.increment_stat(Stat::Hits);
+      self.groups.record(&key, &entry_ref.tags, true);
+      if let Some(namespace) = self.namespace_for(&key) {
+        namespace.hits.fetch_add(1, Ordering::Relaxed);
+      }
+      if entry_ref.negative {
+        self.increment_stat(Stat::NegativeHits);
+      }
+
+      if entry_ref.needs_refresh(self.now()) {
+        if entry_ref.is_stale(self.now()) {
+          self.increment_stat(Stat::StaleHits);
+        }
+        let (ttl_ms, tags, idle_timeout) = (
+          entry_ref.ttl_ms,
+          entry_ref.tags.clone(),
+          entry_ref.idle_timeout,
+        );
+        drop(entry_ref);
+        self.refresh_in_background(key, key_hash, ttl_ms, tags, idle_timeout);
+      }
+
+      Some(value)
+    } else {
+      self.record_miss(&key);
+      self.prefetch(&key, &key_hash);
+      None
+    }
+  }
+
+  fn record_miss(&self, key: &str) {
+    self.increment_stat(Stat::Misses);
+    self.groups.record(key, &[], false);
+    if let Some(namespace) = self.namespace_for(key) {
+      namespace.misses.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  fn expires_early(&self, entry: &CacheEntry) -> bool {
+    let beta = f64::from_bits(self.xfetch_beta.load(Ordering::Relaxed));
+    if beta == 0.0 || entry.compute_ms == 0 || entry.negative || entry.is_stale(self.now()) {
+      return false;
+    }
+    let Some(deadline) = entry.stale_at.or(entry.expires_at) else {
+      return false;
+    };
+    let headroom = entry.compute_ms as f64 * beta * -fastrand::f64().ln();
+    self.now() as f64 + headroom >= deadline as f64
+  }
+
+  fn publish_invalidation(&self, bus: &Mutex<Option<InvalidationBus>>, invalidation: Invalidation) {
+    if let Some(bus) = bus.lock().as_ref() {
+      self.increment_stat(if bus.publish(invalidation).is_ok() {
+        Stat::InvalidationsSent
+      } else {
+        Stat::InvalidationFailures
+      });
+    }
+  }
+
+  fn known_missing(&self, key: &str, key_hash: &str) -> bool {
+    let listed = self
+      .bloom
+      .read()
+      .as_ref()
+      .is_some_and(|bloom| bloom.contains(key));
+    listed && !self.storage.contains_key(key_hash)
+  }
+
+  async fn with_retries<F, Fut>(&self, load: F) -> Result<String>
+  where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<String>>,
+  {
+    let policy = *self.retry_policy.read();
+    let mut attempt = 0;
+    loop {
+      match (load().await, policy) {
+        (Err(_), Some(policy)) if attempt < policy.max_retries => {
+          self.increment_stat(Stat::LoaderRetries);
+          tokio::time::sleep(policy.delay(attempt)).await;
+          attempt += 1;
+        }
+        (result, _) => return result,
+      }
+    }
+  }
+
+  async fn fall_back(&self, key: &str, err: Error) -> Result<String> {
+    let Some(fallback) = self.fallback.read().clone() else {
+      return Err(err);
+    };
+    match fallback.call_async_catch(key.to_string()).await {
+      Ok(pending) => match pending.await {
+        Ok(value) => {
+          self.increment_stat(Stat::Fallbacks);
+          Ok(value)
+        }
+        Err(_) => Err(err),
+      },
+      Err(_) => Err(err),
+    }
+  }
+
+  fn admit_load(&self, key: &str) -> bool {
+    let admitted = self
+      .breaker
+      .read()
+      .as_ref()
+      .is_none_or(|breaker| breaker.admit(key, self.now()));
+    if !admitted {
+      self.increment_stat(Stat::CircuitRejections);
+    }
+    admitted
+  }
+
+  fn circuit_stale(&self, key: &str) -> Option<String> {
+    let open = self
+      .breaker
+      .read()
+      .as_ref()
+      .is_some_and(|breaker| breaker.is_open(key, self.now()));
+    if !open {
+      return None;
+    }
+    let entry = self.storage.get(&self.hash_key(key))?;
+    self.read_value(&entry, CacheValue::to_text)
+  }
+
+  fn record_load(&self, key: &str, succeeded: bool) {
+    let opened = self
+      .breaker
+      .read()
+      .as_ref()
+      .is_some_and(|breaker| breaker.record(key, succeeded, self.now()));
+    if opened {
+      self.increment_stat(Stat::CircuitsOpened);
+    }
+  }
+
+  fn fetch_from_chain(&self, chain: &Chain, key: &str) -> Option<(String, Option<u64>)> {
+    let fetched = match chain.second.as_ref()? {
+      SecondLevel::Redis(tier) => tier.fetch(key).map_err(drop).map(|found| {
+        found.map(|(value, ttl_ms)| (String::from_utf8_lossy(&value).into_owned(), ttl_ms))
+      }),
+      SecondLevel::Disk(tier) => Ok(
+        tier
+          .get(&self.hash_key(key))
+          .filter(|entry| !entry.is_expired(self.now()))
+          .and_then(|entry| {
+            let ttl_ms = entry
+              .expires_at
+              .map(|expires_at| expires_at.saturating_sub(self.now()));
+            self
+              .read_value(&entry, CacheValue::to_text)
+              .map(|value| (value, ttl_ms))
+          }),
+      ),
+    };
+
+    match fetched {
+      Ok(Some(found)) => {
+        bump(&chain.l2.hits);
+        Some(found)
+      }
+      Ok(None) => {
+        bump(&chain.l2.misses);
+        None
+      }
+      Err(()) => {
+        bump(&chain.l2.errors);
+        None
+      }
+    }
+  }
+
+  fn store_in_chain(&self, chain: &Chain, key: &str, value: &str, ttl_ms: Option<u64>) -> bool {
+    let stored = match chain.second.as_ref() {
+      Some(SecondLevel::Redis(tier)) => tier.store(key, value.as_bytes(), ttl_ms).is_ok(),
+      Some(SecondLevel::Disk(tier)) => self
+        .new_entry(key.to_string(), value.to_string().into(), ttl_ms, None)
+        .is_ok_and(|entry| tier.put(&self.hash_key(key), &entry).is_ok()),
+      None => return false,
+    };
+    bump(if stored {
+      &chain.l2.writes
+    } else {
+      &chain.l2.errors
+    });
+    stored
+  }
+
+  fn remove_from_chain(&self, chain: &Chain, key: &str) -> bool {
+    match chain.second.as_ref() {
+      Some(SecondLevel::Redis(tier)) => tier.remove(key).is_ok(),
+      Some(SecondLevel::Disk(tier)) => tier.take(&self.hash_key(key)).is_some(),
+      None => false,
+    }
+  }
+
+  fn demote(&self, entry: &CacheEntry, evicted: bool) {
+    let owners: Vec<Arc<Chain>> = {
+      let chains = self.chains.read();
+      if chains.is_empty() {
+        return;
+      }
+      chains
+        .iter()
+        .filter_map(Weak::upgrade)
+        .filter(|chain| chain.release(&entry.original_key))
+        .collect()
+    };
+    if !evicted || owners.is_empty() || entry.is_expired(self.now()) {
+      return;
+    }
+    let Some(value) = self.read_value(entry, CacheValue::to_text) else {
+      return;
+    };
+    let ttl_ms = entry
+      .expires_at
+      .map(|expires_at| expires_at.saturating_sub(self.now()));
+
+    let (core, key) = (self.clone(), entry.original_key.clone());
+    napi::bindgen_prelude::spawn_blocking(move || {
+      for chain in owners {
+        if core.store_in_chain(&chain, &key, &value, ttl_ms) {
+          bump(&chain.demotions);
+        }
+      }
+    });
+  }
+
+  fn claim_load(&self, key_hash: &str) -> LoadSlot {
+    match self.inflight.entry(key_hash.to_string()) {
+      Entry::Occupied(inflight) => LoadSlot::Waiter(inflight.get().subscribe()),
+      Entry::Vacant(slot) => {
+        slot.insert(broadcast::channel(1).0);
+        LoadSlot::Leader
+      }
+    }
+  }
+
+  fn finish_load(&self, key_hash: &str, result: &Result<String>) {
+    if let Some((_, sender)) = self.inflight.remove(key_hash) {
+      let _ = sender.send(result.as_ref().cloned().map_err(|err| err.reason.clone()));
+    }
+  }
+
+  fn refresh_in_background(
+    &self,
+    key: String,
+    key_hash: String,
+    ttl_ms: Option<u64>,
+    tags: Vec<String>,
+    idle_timeout: Option<u64>,
+  ) {
+    let Some(loader) = self.loader.read().clone() else {
+      return;
+    };
+    if !self.admit_load(&key) {
+      return;
+    }
+    if let LoadSlot::Waiter(_) = self.claim_load(&key_hash) {
+      return;
+    }
+
+    let core = self.clone();
+    napi::bindgen_prelude::spawn(async move {
+      let started = Instant::now();
+      let result = core
+        .with_retries(|| async { loader.call_async_catch(key.clone()).await?.await })
+        .await;
+      let elapsed = started.elapsed();
+      core.latencies.loader.record(elapsed);
+      core.record_load(&key, result.is_ok());
+
+      let result = result.and_then(|value| {
+        let entry = core
+          .new_entry(key, value.clone().into(), ttl_ms, Some(tags))
+          .map_err(plain_error)?
+          .with_idle_timeout(idle_timeout)
+          .with_compute_cost(elapsed);
+        core.store_persisted(entry).map_err(plain_error)?;
+        Ok(value)
+      });
+      core.increment_stat(if result.is_ok() {
+        Stat::Refreshes
+      } else {
+        Stat::RefreshFailures
+      });
+      core.finish_load(&key_hash, &result);
+    });
+  }
+
+  fn now(&self) -> u64 {
+    self.clock.now()
+  }
+
+  fn pressure_cap(&self) -> Option<PressureCap> {
+    let report = (*self.pressure.read())?;
+    let held_ms = self.now().saturating_sub(report.reported_at);
+    let level = report.level.relaxed(held_ms / PRESSURE_DECAY_MS);
+    if level == MemoryPressure::None {
+      return None;
+    }
+    let share = level.retained_share();
+    let limits = self.limits();
+    let max_bytes = limits.max_bytes.unwrap_or(report.baseline_bytes);
+    Some(PressureCap {
+      level,
+      max_entries: ((limits.max_size as f64 * share) as usize).max(MIN_PRESSURE_ENTRIES),
+      max_bytes: ((max_bytes as f64 * share) as u64).max(MIN_PRESSURE_BYTES),
+    })
+  }
+
+  fn hash_key(&self, key: &str) -> String {
+    let generation = self.generation_of(key);
+    match self.key_hasher {
+      Some(algorithm) => key_hash::digest(algorithm, generation, key),
+      None => match generation {
+        Some(generation) => format!("{key}\0{generation}"),
+        None => key.to_string(),
+      },
+    }
+  }
+
+  fn key_hasher_name(&self) -> &'static str {
+    match self.key_hasher {
+      Some(KeyHash::Fnv) => "fnv",
+      Some(KeyHash::Xxh64) => "xxh64",
+      Some(KeyHash::Xxh3) => "xxh3",
+      Some(KeyHash::Sha256) => "sha256",
+      None => "none",
+    }
+  }
+
+  fn generation_of(&self, key: &str) -> Option<u64> {
+    self
+      .namespace_for(key)
+      .map(|namespace| namespace.generation())
+      .filter(|generation| *generation > 0)
+  }
+
+  fn remote_key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+    match self.generation_of(key) {
+      Some(generation) => Cow::Owned(format!("{key}:g{generation}")),
+      None => Cow::Borrowed(key),
+    }
+  }
+
+  fn is_current(&self, key_hash: &str, original_key: &str) -> bool {
+    self.namespaces.is_empty() || self.hash_key(original_key) == key_hash
+  }
+
+  fn bump_namespace_generation(&self, name: &str) -> u64 {
+    let generation = self.register_namespace(name).bump_generation();
+    self.log_record(&Record::Generation(name, generation));
+    self.stale_generations.store(true, Ordering::Relaxed);
+    generation
+  }
+
+  fn restore_generation(&self, name: &str, generation: u64) {
+    if self.register_namespace(name).restore_generation(generation) {
+      self.stale_generations.store(true, Ordering::Relaxed);
+    }
+  }
+
+  fn generations(&self) -> Vec<(String, u64)> {
+    self
+      .namespaces
+      .iter()
+      .map(|namespace| (namespace.name.clone(), namespace.generation()))
+      .filter(|(_, generation)| *generation > 0)
+      .collect()
+  }
+
+  fn purge_stale_generations(&self) -> u32 {
+    let stale: Vec<String> = self
+      .storage
+      .iter()
+      .filter(|entry| !self.is_current(entry.key(), &entry.original_key))
+      .map(|entry| entry.key().to_string())
+      .collect();
+
+    let mut purged = 0;
+    for key_hash in stale {
+      if self.remove_entry(&key_hash).is_some() {
+        purged += 1;
+      }
+    }
+    self.add_stat(Stat::StaleGenerationEntries, purged as u64);
+    purged
+  }
+
+  fn new_entry(
+    &self,
+    key: String,
+    value: CacheValue,
+    ttl_ms: Option<u64>,
+    tags: Option<Vec<String>>,
+  ) -> Result<CacheEntry, CacheErrorCode> {
+    let limits = self.limits();
+    let effective_ttl_ms = ttl_ms
+      .or_else(|| self.tag_ttl_ms(tags.as_deref().unwrap_or_default()))
+      .or(limits.default_ttl_ms)
+      .map(|ttl| jitter(ttl, limits.ttl_jitter_percent));
+    let etag = value.etag().unwrap_or_default();
+    let mut entry = CacheEntry::new(
+      self.seal(self.compress(value))?,
+      effective_ttl_ms,
+      tags.unwrap_or_default(),
+      key,
+      self.now(),
+    )
+    .with_refresh_ahead(limits.refresh_ahead_percent)
+    .with_stale_window(limits.stale_window_ms)
+    .with_idle_timeout(limits.default_tti_ms);
+    entry.ttl_ms = ttl_ms;
+    entry.etag = etag;
+    Ok(entry)
+  }
+
+  fn tag_ttl_ms(&self, tags: &[String]) -> Option<u64> {
+    if self.tag_ttls.is_empty() {
+      return None;
+    }
+    tags
+      .iter()
+      .filter_map(|tag| self.tag_ttls.get(tag).map(|ttl_ms| *ttl_ms))
+      .min()
+  }
+
+  fn rewrite_value(
+    &self,
+    key_hash: &str,
+    entry: &mut CacheEntry,
+    value: CacheValue,
+  ) -> Result<(), CacheErrorCode> {
+    let etag = value.etag().unwrap_or_default();
+    let value = self.seal(self.compress(value))?;
+    entry.etag = etag;
+    self.account_bytes(value.len() as u64, entry.value.len() as u64);
+    entry.value = value;
+    self.commit_rewrite(key_hash, entry);
+    Ok(())
+  }
+
+  fn commit_rewrite(&self, key_hash: &str, entry: &mut CacheEntry) {
+    entry.negative = false;
+    entry.version = self.next_version();
+    self.touch_entry(key_hash, entry);
+    self.log_put(entry);
+    self.notify_write(entry);
+  }
+
+  fn edit_value<T>(
+    &self,
+    key: &str,
+    key_hash: &str,
+    entry: &mut CacheEntry,
+    edit: impl FnOnce(&mut CacheValue) -> Result<Option<T>>,
+  ) -> Result<Option<T>> {
+    let sealed = matches!(
+      entry.value,
+      CacheValue::Compressed { .. } | CacheValue::Encrypted { .. }
+    );
+    if sealed || self.keyring.is_some() {
+      let mut value = self
+        .try_reveal(&entry.value)
+        .and_then(|value| value.expanded().map(Cow::into_owned))
+        .ok_or_else(|| Error::from_reason(format!("Value at key '{key}' cannot be read")))?;
+      let edited = edit(&mut value)?;
+      if edited.is_some() && !value.is_empty_collection() {
+        self
+          .rewrite_value(key_hash, entry, value)
+          .map_err(plain_error)?;
+      }
+      return Ok(edited);
+    }
+
+    let stored = entry.value.len() as u64;
+    let edited = edit(&mut entry.value)?;
+    self.account_bytes(entry.value.len() as u64, stored);
+    if edited.is_none() || entry.value.is_empty_collection() {
+      return Ok(edited);
+    }
+    if self
+      .compression
+      .is_some_and(|(_, threshold)| entry.value.len() >= threshold)
+    {
+      let value = std::mem::replace(&mut entry.value, CacheValue::Bytes(Arc::from([])));
+      self.account_bytes(0, value.len() as u64);
+      self
+        .rewrite_value(key_hash, entry, value)
+        .map_err(plain_error)?;
+    } else {
+      entry.etag = entry.value.etag().unwrap_or_default();
+      self.commit_rewrite(key_hash, entry);
+    }
+    Ok(edited)
+  }
+
+  fn hash_of<'v>(key: &str, value: &'v mut CacheValue) -> Result<&'v mut HashFields> {
+    match value.collection_mut() {
+      Some(Collection::Hash(fields)) => Ok(fields),
+      _ => Err(Error::from_reason(format!(
+        "Value at key '{key}' is not a hash"
+      ))),
+    }
+  }
+
+  fn read_hash<T>(&self, key: &str, read: impl FnOnce(&HashFields) -> T) -> Result<Option<T>> {
+    self
+      .lookup(key.to_string(), |value| {
+        Some(
+          value
+            .with_collection(|collection| match collection {
+              Collection::Hash(fields) => Some(read(fields)),
+              _ => None,
+            })
+            .flatten()
+            .ok_or_else(|| Error::from_reason(format!("Value at key '{key}' is not a hash"))),
+        )
+      })
+      .flatten()
+      .transpose()
+  }
+
+  fn list_of<'v>(key: &str, value: &'v mut CacheValue) -> Result<&'v mut ListItems> {
+    match value.collection_mut() {
+      Some(Collection::List(items)) => Ok(items),
+      _ => Err(Error::from_reason(format!(
+        "Value at key '{key}' is not a list"
+      ))),
+    }
+  }
+
+  fn read_list<T>(&self, key: &str, read: impl FnOnce(&ListItems) -> T) -> Result<Option<T>> {
+    self
+      .lookup(key.to_string(), |value| {
+        Some(
+          value
+            .with_collection(|collection| match collection {
+              Collection::List(items) => Some(read(items)),
+              _ => None,
+            })
+            .flatten()
+            .ok_or_else(|| Error::from_reason(format!("Value at key '{key}' is not a list"))),
+        )
+      })
+      .flatten()
+      .transpose()
+  }
+
+  fn set_of<'v>(key: &str, value: &'v mut CacheValue) -> Result<&'v mut SetMembers> {
+    match value.collection_mut() {
+      Some(Collection::Set(members)) => Ok(members),
+      _ => Err(Error::from_reason(format!(
+        "Value at key '{key}' is not a set"
+      ))),
+    }
+  }
+
+  fn read_set<T>(&self, key: &str, read: impl FnOnce(&SetMembers) -> T) -> Result<Option<T>> {
+    self
+      .lookup(key.to_string(), |value| {
+        Some(
+          value
+            .with_collection(|collection| match collection {
+              Collection::Set(members) => Some(read(members)),
+              _ => None,
+            })
+            .flatten()
+            .ok_or_else(|| Error::from_reason(format!("Value at key '{key}' is not a set"))),
+        )
+      })
+      .flatten()
+      .transpose()
+  }
+
+  fn sorted_set_of<'v>(key: &str, value: &'v mut CacheValue) -> Result<&'v mut ScoredMembers> {
+    match value.collection_mut() {
+      Some(Collection::SortedSet(members)) => Ok(members),
+      _ => Err(Error::from_reason(format!(
+        "Value at key '{key}' is not a sorted set"
+      ))),
+    }
+  }
+
+  fn read_sorted_set<T>(
+    &self,
+    key: &str,
+    read: impl FnOnce(&ScoredMembers) -> T,
+  ) -> Result<Option<T>> {
+    self
+      .lookup(key.to_string(), |value| {
+        Some(
+          value
+            .with_collection(|collection| match collection {
+              Collection::SortedSet(members) => Some(read(members)),
+              _ => None,
+            })
+            .flatten()
+            .ok_or_else(|| Error::from_reason(format!("Value at key '{key}' is not a sorted set"))),
+        )
+      })
+      .flatten()
+      .transpose()
+  }
+
+  fn compress(&self, value: CacheValue) -> CacheValue {
+    let Some((codec, threshold)) = self.compression else {
+      return value;
+    };
+
+    let (value, sizes) = value.compress(codec, threshold);
+    if let Some((original, compressed)) = sizes {
+      self.increment_stat(Stat::CompressedValues);
+      self.add_stat(Stat::CompressionInputBytes, original as u64);
+      self.add_stat(Stat::CompressionOutputBytes, compressed as u64);
+    }
+    value
+  }
+
+  fn seal(&self, value: CacheValue) -> Result<CacheValue, CacheErrorCode> {
+    let Some(keyring) = &self.keyring else {
+      return Ok(value);
+    };
+
+    let (key_id, nonce, ciphertext) = keyring
+      .seal(&value.encode())
+      .map_err(|reason| Error::new(CacheErrorCode::GenericFailure, reason))?;
+    Ok(CacheValue::Encrypted {
+      key_id,
+      nonce,
+      ciphertext,
+    })
+  }
+
+  fn try_reveal<'a>(&self, value: &'a CacheValue) -> Option<Cow<'a, CacheValue>> {
+    let CacheValue::Encrypted {
+      key_id,
+      nonce,
+      ciphertext,
+    } = value
+    else {
+      return Some(Cow::Borrowed(value));
+    };
+
+    let plaintext = self.keyring.as_ref()?.open(*key_id, nonce, ciphertext)?;
+    CacheValue::decode(&plaintext).map(Cow::Owned)
+  }
+
+  fn read_value<T>(
+    &self,
+    entry: &CacheEntry,
+    read: impl FnOnce(&CacheValue) -> Option<T>,
+  ) -> Option<T> {
+    if entry.negative {
+      return None;
+    }
+    self.revealed(&entry.value, read)
+  }
+
+  fn revealed<T>(
+    &self,
+    value: &CacheValue,
+    read: impl FnOnce(&CacheValue) -> Option<T>,
+  ) -> Option<T> {
+    self.decoded(self.try_reveal(value).and_then(|value| read(&value)))
+  }
+
+  fn decoded<T>(&self, value: Option<T>) -> Option<T> {
+    if value.is_none() {
+      self.increment_stat(Stat::CorruptValues);
+    }
+    value
+  }
+
+  fn reencrypt_stale_key(&self, entry: &mut CacheEntry) {
+    let (Some(keyring), CacheValue::Encrypted { key_id, .. }) = (&self.keyring, &entry.value)
+    else {
+      return;
+    };
+    if *key_id == keyring.current_key_id() {
+      return;
+    }
+
+    let Some(value) = self.try_reveal(&entry.value).map(Cow::into_owned) else {
+      return;
+    };
+    let Ok(value) = self.seal(value) else {
+      return;
+    };
+    self.account_bytes(value.len() as u64, entry.value.len() as u64);
+    entry.value = value;
+    self.increment_stat(Stat::ReencryptedValues);
+  }
+
+  fn current_version(&self, key_hash: &str) -> i64 {
+    live_version(
+      self
+        .storage
+        .get(key_hash)
+        .filter(|entry| !entry.is_expired(self.now()))
+        .as_deref(),
+    )
+  }
+
+  fn limits(&self) -> Limits {
+    *self.limits.read()
+  }
+
+  fn is_draining(&self) -> bool {
+    self.draining.load(Ordering::Acquire)
+  }
+
+  fn ensure_writable(&self) -> Result<()> {
+    if self.is_draining() {
+      return Err(Error::from_reason(
+        "Cache is draining for shutdown; writes are rejected",
+      ));
+    }
+    Ok(())
+  }
+
+  fn store_entry(&self, entry: CacheEntry) -> Result<i64, CacheErrorCode> {
+    self
+      .ensure_direct_write(&entry.original_key)
+      .map_err(coded_error)?;
+    self.store_persisted(entry)
+  }
+
+  fn store_persisted(&self, entry: CacheEntry) -> Result<i64, CacheErrorCode> {
+    self.ensure_writable().map_err(coded_error)?;
+    self.check_limits(&entry)?;
+    let started = Instant::now();
+    let span = self.trace("cache.set");
+    self.hot_keys.record(&entry.original_key);
+    let key_hash = self.hash_key(&entry.original_key);
+    let namespace = self.namespace_for(&entry.original_key);
+    if let Some(namespace) = &namespace {
+      self.make_namespace_room(namespace, &key_hash);
+    }
+    self.make_room(&key_hash, entry.weight())?;
+
+    self.write_through(&entry);
+    if let Some(namespace) = namespace {
+      namespace.sets.fetch_add(1, Ordering::Relaxed);
+    }
+    let version = self.insert_entry(key_hash, entry);
+    self.increment_stat(Stat::Sets);
+    self.latencies.set.record(started.elapsed());
+    self.record_operation(span, "set", "stored");
+
+    Ok(version as i64)
+  }
+
+  fn delete_key(&self, key: &str) -> bool {
+    self.delete_through(key);
+    self.delete_local(key)
+  }
+
+  fn delete_local(&self, key: &str) -> bool {
+    let started = Instant::now();
+    let span = self.trace("cache.delete");
+    let key_hash = self.hash_key(key);
+    let removed = self.remove_entry(&key_hash);
+    self.invalidate_dependents(&key_hash);
+    if let Some(entry) = &removed {
+      self.increment_stat(Stat::Deletes);
+      if let Some(namespace) = self.namespace_for(key) {
+        namespace.deletes.fetch_add(1, Ordering::Relaxed);
+      }
+      self.notify_removal(entry, EvictionReason::Delete);
+    }
+    self.latencies.delete.record(started.elapsed());
+    let outcome = if removed.is_some() {
+      "removed"
+    } else {
+      "missing"
+    };
+    self.record_operation(span, "delete", outcome);
+    removed.is_some()
+  }
+
+  fn scan_page(
+    &self,
+    cursor: ScanCursor,
+    pattern: Option<&KeyPattern>,
+    limit: usize,
+  ) -> (Vec<String>, Option<ScanCursor>) {
+    let mut keys = Vec::with_capacity(limit);
+    let ScanCursor {
+      mut shard,
+      mut after,
+    } = cursor;
+    while shard < self.storage.shard_count() {
+      let wanted = limit - keys.len();
+      let mut page = BinaryHeap::with_capacity(wanted + 1);
+      self.storage.for_each_in_shard(shard, |key_hash, entry| {
+        let Some(position) = key_hash::position(key_hash, self.key_hasher.is_some()) else {
+          return;
+        };
+        if (after > 0 && position <= after)
+          || page.len() == wanted && page.peek().is_some_and(|(last, _)| position >= *last)
+          || entry.is_expired(self.now())
+          || !self.is_current(key_hash, &entry.original_key)
+          || !pattern.is_none_or(|pattern| pattern.matches(&entry.original_key))
+        {
+          return;
+        }
+        page.push((position, key_hash.to_string()));
+        if page.len() > wanted {
+          page.pop();
+        }
+      });
+
+      let filled = page.len() == wanted;
+      let page = page.into_sorted_vec();
+      let last = page.last().map(|(position, _)| *position);
+      keys.extend(page.into_iter().map(|(_, key_hash)| key_hash));
+      if let (true, Some(last)) = (filled, last) {
+        return (keys, Some(ScanCursor { shard, after: last }));
+      }
+      shard += 1;
+      after = 0;
+    }
+    (keys, None)
+  }
+
+  fn entry_record(&self, key_hash: &str) -> Option<EntryRecord> {
+    let entry = self
+      .storage
+      .get(key_hash)
+      .filter(|entry| !entry.is_expired(self.now()))?;
+    Some(self.record_of(&entry))
+  }
+
+  fn disk_record(&self, key_hash: &str) -> Option<EntryRecord> {
+    if self.storage.contains_key(key_hash) {
+      return None;
+    }
+    let entry = self.disk_tier.read().as_ref()?.get(key_hash)?;
+    (!entry.is_expired(self.now())).then(|| self.record_of(&entry))
+  }
+
+  fn record_of(&self, entry: &CacheEntry) -> EntryRecord {
+    EntryRecord {
+      key: entry.original_key.clone(),
+      value: self.read_value(entry, CacheValue::to_text),
+      metadata: entry.metadata(),
+    }
+  }
+
+  fn disk_scan(&self, pattern: Option<&KeyPattern>) -> VecDeque<String> {
+    self
+      .disk_keys(None)
+      .into_iter()
+      .filter(|(key_hash, key)| {
+        !self.storage.contains_key(key_hash)
+          && self.is_current(key_hash, key)
+          && pattern.is_none_or(|pattern| pattern.matches(key))
+      })
+      .map(|(key_hash, _)| key_hash)
+      .collect()
+  }
+
+  fn matching_keys(&self, predicate: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut keys = Vec::new();
+
+    for entry in self.storage.iter() {
+      if !entry.is_expired(self.now())
+        && self.is_current(entry.key(), &entry.original_key)
+        && predicate(&entry.original_key)
+      {
+        keys.push(entry.original_key.clone());
+      }
+    }
+    for (key_hash, key) in self.disk_keys(None) {
+      if self.is_current(&key_hash, &key) && predicate(&key) {
+        keys.push(key);
+      }
+    }
+
+    keys
+  }
+
+  fn persister_for(&self, key: &str) -> Option<Arc<Persister>> {
+    if self.write_through.is_empty() {
+      return None;
+    }
+    let name = groups::namespace_of(key)?;
+    self
+      .write_through
+      .get(name)
+      .map(|persister| persister.clone())
+  }
+
+  fn ensure_direct_write(&self, key: &str) -> Result<()> {
+    match groups::namespace_of(key).filter(|_| self.persister_for(key).is_some()) {
+      Some(namespace) => Err(Error::from_reason(format!(
+        "Key '{key}' belongs to write-through namespace '{namespace}'; use setWriteThrough"
+      ))),
+      None => Ok(()),
+    }
+  }
+
+  fn namespace_for(&self, key: &str) -> Option<Arc<Namespace>> {
+    if self.namespaces.is_empty() {
+      return None;
+    }
+    let name = groups::namespace_of(key)?;
+    self.namespaces.get(name).map(|namespace| namespace.clone())
+  }
+
+  fn register_namespace(&self, name: &str) -> Arc<Namespace> {
+    if let Some(namespace) = self.namespaces.get(name) {
+      return namespace.clone();
+    }
+    let namespace = Namespace::new(name.to_string());
+    for entry in self.storage.iter() {
+      if namespace.strip(&entry.original_key).is_some() {
+        namespace.on_insert(entry.key(), entry.weight(), None);
+      }
+    }
+    self
+      .namespaces
+      .entry(name.to_string())
+      .or_insert(Arc::new(namespace))
+      .clone()
+  }
+
+  fn tenant_for(&self, tenant: Option<&str>) -> Option<Arc<Tenant>> {
+    self.tenants.get(tenant?).map(|tenant| tenant.clone())
+  }
+
+  fn register_tenant(&self, name: &str) -> Arc<Tenant> {
+    if let Some(tenant) = self.tenants.get(name) {
+      return tenant.clone();
+    }
+    let tenant = Tenant::default();
+    for entry in self.storage.iter() {
+      if entry.tenant.as_deref() == Some(name) {
+        tenant.on_insert(entry.key(), entry.weight(), None);
+      }
+    }
+    self
+      .tenants
+      .entry(name.to_string())
+      .or_insert(Arc::new(tenant))
+      .clone()
+  }
+
+  fn make_tenant_room(
+    &self,
+    tenant: &Tenant,
+    key_hash: &str,
+    entry: &CacheEntry,
+  ) -> Result<(), CacheErrorCode> {
+    let quota = tenant.quota();
+    let incoming_bytes = entry.weight();
+    if let Some(max_bytes) = quota.max_bytes {
+      if incoming_bytes > max_bytes {
+        return Err(Error::new(
+          CacheErrorCode::CapacityExceeded,
+          format!(
+            "Entry of {incoming_bytes} bytes exceeds the quota of {max_bytes} bytes for tenant '{}'",
+            entry.tenant.as_deref().unwrap_or_default()
+          ),
+        ));
+      }
+    }
+
+    let replaced_bytes = self
+      .storage
+      .get(key_hash)
+      .filter(|existing| existing.tenant == entry.tenant)
+      .map(|existing| existing.weight());
+    let evict_until = |over_quota: &dyn Fn() -> bool| {
+      while over_quota()
+        && self.evict_next(|| tenant.victim(), |key_hash, _| tenant.track(key_hash))
+      {}
+    };
+
+    if let (Some(max_entries), None) = (quota.max_entries, replaced_bytes) {
+      evict_until(&|| tenant.entries() >= max_entries);
+    }
+    if let Some(max_bytes) = quota.max_bytes {
+      let budget = max_bytes + replaced_bytes.unwrap_or(0) - incoming_bytes;
+      evict_until(&|| tenant.bytes() > budget);
+    }
+    Ok(())
+  }
+
+  fn make_namespace_room(&self, namespace: &Namespace, key_hash: &str) {
+    let Some(max_size) = namespace.max_size() else {
+      return;
+    };
+    if self.storage.contains_key(key_hash) {
+      return;
+    }
+    while namespace.entries() >= max_size as u64
+      && self.evict_next(
+        || namespace.victim(),
+        |key_hash, _| namespace.track(key_hash),
+      )
+    {}
+  }
+
+  fn touch_entry(&self, key_hash: &str, entry: &mut CacheEntry) {
+    self.reschedule(key_hash, entry, |entry| entry.touch(self.now()));
+    self.eviction.on_access(key_hash, entry.priority.tier());
+    if let Some(namespace) = self.namespace_for(&entry.original_key) {
+      namespace.on_access(key_hash);
+    }
+    if let Some(tenant) = self.tenant_for(entry.tenant.as_deref()) {
+      tenant.on_access(key_hash);
+    }
+  }
+
+  fn reschedule(
+    &self,
+    key_hash: &str,
+    entry: &mut CacheEntry,
+    update: impl FnOnce(&mut CacheEntry),
+  ) {
+    let deadline = entry.expires_at;
+    update(entry);
+
+    if entry.expires_at != deadline {
+      self.expiry_index.cancel(deadline, key_hash);
+      self.expiry_index.schedule(entry.expires_at, key_hash);
+    }
+  }
+
+  fn update_expiry(&self, key: &str, expires_at: Option<u64>) -> bool {
+    let key_hash = self.hash_key(key);
+    match self.storage.get_mut(&key_hash) {
+      Some(mut entry) if !entry.is_expired(self.now()) => {
+        self.reschedule(&key_hash, &mut entry, |entry| {
+          entry.set_expiry(expires_at, self.now())
+        });
+        self.log_put(&entry);
+      }
+      Some(_) => return false,
+      None => {
+        return self.update_on_disk(&key_hash, |entry| entry.set_expiry(expires_at, self.now()))
+      }
+    }
+    self.mirror(&key_hash);
+    true
+  }
+
+  fn inspect<T>(&self, key: &str, read: impl FnOnce(&CacheEntry) -> Option<T>) -> Option<T> {
+    let key_hash = self.hash_key(key);
+    if let Some(entry) = self.storage.get(&key_hash) {
+      return if entry.is_expired(self.now()) {
+        None
+      } else {
+        read(&entry)
+      };
+    }
+    let entry = self.disk_tier.read().as_ref()?.get(&key_hash)?;
+    if entry.is_expired(self.now()) {
+      None
+    } else {
+      read(&entry)
+    }
+  }
+
+  fn update_on_disk(&self, key_hash: &str, update: impl FnOnce(&mut CacheEntry)) -> bool {
+    let disk_tier = self.disk_tier.read();
+    let Some(tier) = disk_tier.as_ref() else {
+      return false;
+    };
+    let Some(mut entry) = tier
+      .get(key_hash)
+      .filter(|entry| !entry.is_expired(self.now()))
+    else {
+      return false;
+    };
+    update(&mut entry);
+    if tier.put(key_hash, &entry).is_err() {
+      self.increment_stat(Stat::DiskFailures);
+      return false;
+    }
+    drop(disk_tier);
+    self.write_through(&entry);
+    true
+  }
+
+  fn insert_entry(&self, key_hash: String, entry: CacheEntry) -> u64 {
+    self
+      .insert_entry_if(key_hash, entry, |_| true)
+      .unwrap_or_default()
+  }
+
+  fn insert_entry_if(
+    &self,
+    key_hash: String,
+    mut entry: CacheEntry,
+    condition: impl FnOnce(Option<&CacheEntry>) -> bool,
+  ) -> Option<u64> {
+    let stripe = self.storage.lock_key(&key_hash);
+    let slot = self.storage.entry(&key_hash);
+    let live = match &slot {
+      Entry::Occupied(occupied) if !occupied.get().is_expired(self.now()) => Some(occupied.get()),
+      _ => None,
+    };
+    if !condition(live) {
+      return None;
+    }
+
+    if entry.version == 0 {
+      entry.version = self.next_version();
+    }
+    let version = entry.version;
+    let weight = entry.weight();
+    let expires_at = entry.expires_at;
+    let negative = entry.negative;
+    let priority = entry.priority;
+    let original_key = entry.original_key.clone();
+    let tenant_name = entry.tenant.clone();
+    let tags = entry.tags.clone();
+    let depends_on = entry.depends_on.clone();
+    let previous = match slot {
+      Entry::Occupied(mut occupied) => Some(occupied.insert(entry)),
+      Entry::Vacant(vacant) => {
+        vacant.insert(entry);
+        None
+      }
+    };
+
+    if let Some(bloom) = self.bloom.read().as_ref() {
+      if negative {
+        bloom.insert(&original_key);
+      } else {
+        bloom.remove(&original_key);
+      }
+    }
+    for tag in &tags {
+      self
+        .tag_index
+        .entry(tag.clone())
+        .or_default()
+        .insert(key_hash.clone());
+    }
+    for parent in &depends_on {
+      self
+        .dependents
+        .entry(parent.clone())
+        .or_default()
+        .insert(key_hash.clone());
+    }
+    self.eviction.on_insert(&key_hash, priority.tier());
+    self.account_bytes(weight, 0);
+
+    if let Some(namespace) = self.namespace_for(&original_key) {
+      namespace.on_insert(&key_hash, weight, previous.as_ref().map(CacheEntry::weight));
+    }
+    let tenant = self.tenant_for(tenant_name.as_deref());
+    let previous_tenant = previous
+      .as_ref()
+      .and_then(|previous| self.tenant_for(previous.tenant.as_deref()));
+    match (&previous_tenant, &tenant) {
+      (Some(before), Some(after)) if Arc::ptr_eq(before, after) => {
+        after.on_insert(&key_hash, weight, previous.as_ref().map(CacheEntry::weight));
+      }
+      _ => {
+        if let (Some(before), Some(previous)) = (&previous_tenant, &previous) {
+          before.on_remove(&key_hash, previous.weight());
+        }
+        if let Some(after) = &tenant {
+          after.on_insert(&key_hash, weight, None);
+        }
+      }
+    }
+    let replaced = previous.is_some();
+    if let Some(previous) = previous {
+      self.account_bytes(0, previous.weight());
+      self.expiry_index.cancel(previous.expires_at, &key_hash);
+      let stale_tags: Vec<&String> = previous
+        .tags
+        .iter()
+        .filter(|tag| !tags.contains(tag))
+        .collect();
+      self.unindex_tags(&key_hash, stale_tags);
+      let stale_parents: Vec<&String> = previous
+        .depends_on
+        .iter()
+        .filter(|parent| !depends_on.contains(parent))
+        .collect();
+      self.unindex_dependencies(&key_hash, stale_parents);
+    }
+    self.expiry_index.schedule(expires_at, &key_hash);
+
+    if let Some(stored) = self
+      .storage
+      .get(&key_hash)
+      .filter(|stored| stored.version == version)
+    {
+      self.log_put(&stored);
+      self.notify_write(&stored);
+    }
+    drop(stripe);
+
+    if let Some(tier) = self.disk_tier.read().as_ref() {
+      tier.remove(&key_hash);
+    }
+    if replaced {
+      self.invalidate_dependents(&key_hash);
+    }
+    Some(version)
+  }
+
+  fn next_version(&self) -> u64 {
+    self.version_counter.fetch_add(1, Ordering::Relaxed) + 1
+  }
+
+  fn remove_entry(&self, key_hash: &str) -> Option<CacheEntry> {
+    let removed = self.storage.remove_if(key_hash, |_, entry| {
+      self.log_record(&Record::Remove(&entry.original_key, entry.version));
+      true
+    });
+    if let Some((_, entry)) = removed {
+      self.forget_entry(key_hash, &entry);
+      return Some(entry);
+    }
+
+    let entry = self.disk_tier.read().as_ref()?.take(key_hash)?;
+    self.log_record(&Record::Remove(&entry.original_key, entry.version));
+    Some(entry)
+  }
+
+  fn remove_tagged(&self, tag: &str) -> Vec<CacheEntry> {
+    let mut removed = Vec::new();
+    for (key_hash, _) in self.disk_keys(Some(tag)) {
+      removed.extend(self.remove_entry(&key_hash));
+    }
+
+    let Some((_, key_hashes)) = self.tag_index.remove(tag) else {
+      return removed;
+    };
+
+    for key_hash in key_hashes {
+      let tagged = self
+        .storage
+        .get(&key_hash)
+        .is_some_and(|entry| entry.tags.iter().any(|entry_tag| entry_tag == tag));
+      if tagged {
+        removed.extend(self.remove_entry(&key_hash));
+      }
+    }
+    removed
+  }
+
+  fn enqueue_write_behind(&self, op: ChangeOperation, entry: &CacheEntry) {
+    let Some(queue) = self.write_behind.read().clone() else {
+      return;
+    };
+    if entry.negative {
+      return;
+    }
+
+    let value = match op {
+      ChangeOperation::Set => self.read_value(entry, CacheValue::to_text),
+      _ => None,
+    };
+    if queue.enqueue(op, &entry.original_key, value) {
+      self.increment_stat(Stat::WriteBehindQueued);
+      self.dispatch_write_behind(&queue, false);
+    } else {
+      self.increment_stat(Stat::WriteBehindDropped);
+    }
+  }
+
+  fn dispatch_write_behind(&self, queue: &Arc<WriteBehind>, force: bool) {
+    let Some(batch) = queue.take_batch(force) else {
+      return;
+    };
+
+    let core = self.clone();
+    let queue = queue.clone();
+    napi::bindgen_prelude::spawn(async move {
+      if core.write_batch(&queue, batch).await.is_ok() {
+        core.dispatch_write_behind(&queue, false);
+      }
+    });
+  }
+
+  async fn write_batch(&self, queue: &WriteBehind, batch: Vec<ChangeRecord>) -> Result<usize> {
+    let size = batch.len();
+    let written = match queue.writer.call_async_catch(batch.clone()).await {
+      Ok(pending) => pending.await,
+      Err(err) => Err(err),
+    };
+    match written {
+      Ok(()) => {
+        queue.finish(None);
+        self.increment_stat(Stat::WriteBehindBatches);
+        self.add_stat(Stat::WriteBehindFlushed, size as u64);
+        Ok(size)
+      }
+      Err(err) => {
+        let dropped = queue.finish(Some(batch));
+        self.increment_stat(Stat::WriteBehindFailures);
+        self.add_stat(Stat::WriteBehindDropped, dropped as u64);
+        Err(err)
+      }
+    }
+  }
+
+  fn notify_write(&self, entry: &CacheEntry) {
+    self.enqueue_write_behind(ChangeOperation::Set, entry);
+    self.events.emit(CacheEventType::Set, &entry.original_key);
+    if self.events.has_change_streams() {
+      let value = self.read_value(entry, CacheValue::to_text);
+      let dropped = self
+        .events
+        .emit_change(ChangeOperation::Set, &entry.original_key, value);
+      self.add_stat(Stat::ChangeStreamDropped, dropped);
+    }
+  }
+
+  fn notify_removal(&self, entry: &CacheEntry, reason: EvictionReason) {
+    let (event_type, operation, label) = match reason {
+      EvictionReason::Capacity => (
+        Some(CacheEventType::Evict),
+        ChangeOperation::Evict,
+        "capacity",
+      ),
+      EvictionReason::Ttl => (Some(CacheEventType::Expire), ChangeOperation::Expire, "ttl"),
+      EvictionReason::Delete => (
+        Some(CacheEventType::Delete),
+        ChangeOperation::Delete,
+        "delete",
+      ),
+      EvictionReason::Flush => (None, ChangeOperation::Flush, "flush"),
+    };
+    if let Some(telemetry) = self.telemetry.read().as_ref() {
+      telemetry.record_removal(label);
+    }
+    if let Some(event_type) = event_type {
+      self.events.emit(event_type, &entry.original_key);
+    }
+    if let EvictionReason::Delete = reason {
+      self.enqueue_write_behind(ChangeOperation::Delete, entry);
+    }
+    self.demote(entry, matches!(reason, EvictionReason::Capacity));
+    let dropped = self
+      .events
+      .emit_change(operation, &entry.original_key, None);
+    self.add_stat(Stat::ChangeStreamDropped, dropped);
+
+    if self.events.has_eviction_listener() {
+      self.events.emit_eviction(EvictedEntry {
+        key: entry.original_key.clone(),
+        value: self.read_value(entry, CacheValue::to_text),
+        reason,
+      });
+    }
+  }
+
+  fn trace(&self, name: &'static str) -> Option<OperationSpan> {
+    self
+      .telemetry
+      .read()
+      .as_ref()
+      .and_then(|telemetry| telemetry.span(name))
+  }
+
+  fn record_operation(
+    &self,
+    span: Option<OperationSpan>,
+    operation: &'static str,
+    outcome: &'static str,
+  ) {
+    if let Some(telemetry) = self.telemetry.read().as_ref() {
+      telemetry.record(operation, outcome);
+    }
+    if let Some(span) = span {
+      span.finish(outcome);
+    }
+  }
+
+  fn locate(&self, key: &str) -> String {
+    let key_hash = self.hash_key(key);
+    self.promote(&key_hash);
+    key_hash
+  }
+
+  fn promote(&self, key_hash: &str) {
+    if self.storage.contains_key(key_hash) {
+      return;
+    }
+    let Some(entry) = self
+      .disk_tier
+      .read()
+      .as_ref()
+      .and_then(|tier| tier.take(key_hash))
+    else {
+      return;
+    };
+
+    if !entry.is_expired(self.now()) && self.make_room(key_hash, entry.weight()).is_ok() {
+      self.insert_entry(key_hash.to_string(), entry);
+      self.increment_stat(Stat::DiskPromotions);
+    }
+  }
+
+  fn spill(&self, key_hash: &str, entry: &CacheEntry) -> bool {
+    if entry.is_expired(self.now()) {
+      return false;
+    }
+    let disk_tier = self.disk_tier.read();
+    let Some(tier) = disk_tier.as_ref() else {
+      return false;
+    };
+    let spilled = tier.put(key_hash, entry).is_ok();
+    self.increment_stat(if spilled {
+      Stat::DiskSpills
+    } else {
+      Stat::DiskFailures
+    });
+    spilled
+  }
+
+  fn read_through(&self, key: &str, key_hash: &str) {
+    if self.storage.contains_key(key_hash) {
+      return;
+    }
+    let fetched = match self.remote_tier.read().as_ref() {
+      Some(tier) => {
+        self.record_flush(tier.flush(self.now()));
+        tier.fetch(&self.remote_key(key))
+      }
+      None => return,
+    };
+
+    match fetched {
+      Ok(Some((payload, ttl_ms))) => {
+        let value = remote_tier::decode(payload)
+          .and_then(|value| self.try_reveal(&value).map(Cow::into_owned));
+        let Some(value) = self.decoded(value) else {
+          return;
+        };
+        let Ok(entry) = self.new_entry(key.to_string(), value, ttl_ms, None) else {
+          return;
+        };
+        if self.make_room(key_hash, entry.weight()).is_ok()
+          && self
+            .insert_entry_if(key_hash.to_string(), entry, |live| live.is_none())
+            .is_some()
+        {
+          self.increment_stat(Stat::RemoteHits);
+        }
+      }
+      Ok(None) => self.increment_stat(Stat::RemoteMisses),
+      Err(_) => self.increment_stat(Stat::RemoteFailures),
+    }
+  }
+
+  fn prefetch(&self, key: &str, key_hash: &str) -> Option<broadcast::Receiver<()>> {
+    if self.remote_tier.read().is_none() || self.storage.contains_key(key_hash) {
+      return None;
+    }
+    let receiver = match self.remote_fetches.entry(key_hash.to_string()) {
+      Entry::Occupied(fetch) => return Some(fetch.get().subscribe()),
+      Entry::Vacant(slot) => slot.insert(broadcast::channel(1).0).subscribe(),
+    };
+
+    let (core, key, key_hash) = (self.clone(), key.to_string(), key_hash.to_string());
+    napi::bindgen_prelude::spawn_blocking(move || {
+      core.read_through(&key, &key_hash);
+      if let Some((_, sender)) = core.remote_fetches.remove(&key_hash) {
+        let _ = sender.send(());
+      }
+    });
+    Some(receiver)
+  }
+
+  fn write_through(&self, entry: &CacheEntry) {
+    let remote_tier = self.remote_tier.read();
+    let Some(tier) = remote_tier.as_ref() else {
+      return;
+    };
+    if entry.negative {
+      return;
+    }
+
+    tier.enqueue(RemoteWrite::Store {
+      key: self.remote_key(&entry.original_key).into_owned(),
+      payload: remote_tier::encode(&entry.value),
+      expires_at: entry.expires_at,
+    });
+  }
+
+  fn mirror(&self, key_hash: &str) {
+    if self.remote_tier.read().is_none() {
+      return;
+    }
+    let entry = self.storage.get(key_hash).map(|entry| entry.clone());
+    if let Some(entry) = entry {
+      self.write_through(&entry);
+    }
+  }
+
+  fn delete_through(&self, key: &str) {
+    if let Some(tier) = self.remote_tier.read().as_ref() {
+      tier.enqueue(RemoteWrite::Remove(self.remote_key(key).into_owned()));
+    }
+  }
+
+  fn flush_remote(&self) {
+    if let Some(tier) = self.remote_tier.read().as_ref() {
+      self.record_flush(tier.flush(self.now()));
+    }
+  }
+
+  fn record_flush(&self, report: FlushReport) {
+    self.add_stat(Stat::RemoteWrites, report.writes);
+    self.add_stat(Stat::RemoteFailures, report.failures);
+  }
+
+  fn disk_keys(&self, tag: Option<&str>) -> Vec<(String, String)> {
+    let disk_tier = self.disk_tier.read();
+    match (disk_tier.as_ref(), tag) {
+      (Some(tier), Some(tag)) => tier.tagged(tag, self.now()),
+      (Some(tier), None) => tier.keys(self.now()),
+      (None, _) => Vec::new(),
+    }
+  }
+
+  fn forget_entry(&self, key_hash: &str, entry: &CacheEntry) {
+    self.account_bytes(0, entry.weight());
+    self.expiry_index.cancel(entry.expires_at, key_hash);
+    self.unindex_tags(key_hash, &entry.tags);
+    self.unindex_dependencies(key_hash, &entry.depends_on);
+    self.eviction.on_remove(key_hash, entry.priority.tier());
+    self.pinned.remove(key_hash);
+    if let Some(namespace) = self.namespace_for(&entry.original_key) {
+      namespace.on_remove(key_hash, entry.weight());
+    }
+    if let Some(tenant) = self.tenant_for(entry.tenant.as_deref()) {
+      tenant.on_remove(key_hash, entry.weight());
+    }
+  }
+
+  fn sweep_expired(&self) -> u32 {
+    let started = Instant::now();
+    let now = self.now();
+
+    let mut removed = 0;
+    for (expires_at, key_hash) in self.expiry_index.drain_due(now) {
+      let expired = self.storage.remove_if(&key_hash, |_, entry| {
+        entry.expires_at == Some(expires_at) && entry.is_expired(self.now())
+      });
+      if let Some((_, entry)) = expired {
+        self.forget_entry(&key_hash, &entry);
+        self.notify_removal(&entry, EvictionReason::Ttl);
+        removed += 1;
+      }
+    }
+
+    self.leases.purge_expired(now);
+    self.fill_leases.purge_expired(now);
+    self.rate_limiter.purge_full(now);
+    self.idempotency.purge_expired(now);
+    if self.stale_generations.swap(false, Ordering::Relaxed) {
+      self.purge_stale_generations();
+    }
+
+    self.increment_stat(Stat::Sweeps);
+    self.add_stat(Stat::SweptEntries, removed as u64);
+    self.set_stat(Stat::LastSweepAt, wall_millis() / 1000);
+    self.set_stat(
+      Stat::LastSweepDurationUs,
+      started.elapsed().as_micros() as u64,
+    );
+
+    removed
+  }
+
+  fn live_entries(&self) -> Vec<CacheEntry> {
+    self
+      .storage
+      .iter()
+      .filter(|entry| {
+        !entry.is_expired(self.now()) && self.is_current(entry.key(), &entry.original_key)
+      })
+      .map(|entry| entry.value().clone())
+      .collect()
+  }
+
+  fn restore_entry(&self, entry: CacheEntry) -> bool {
+    self
+      .version_counter
+      .fetch_max(entry.version, Ordering::Relaxed);
+    if entry.is_expired(self.now()) || self.decoded(self.try_reveal(&entry.value)).is_none() {
+      return false;
+    }
+
+    let key_hash = self.hash_key(&entry.original_key);
+    if self.make_room(&key_hash, entry.weight()).is_err() {
+      return false;
+    }
+    self.insert_entry(key_hash, entry);
+    true
+  }
+
+  fn clear_entries(&self) -> u32 {
+    let count = self.storage.len() as u32;
+    if self.events.has_eviction_listener() || self.events.has_change_streams() {
+      for entry in self.storage.iter() {
+        self.notify_removal(&entry, EvictionReason::Flush);
+      }
+    }
+    self.log_record(&Record::Clear);
+    self.storage.clear();
+    self.tag_index.clear();
+    self.pinned.clear();
+    self.expiry_index.clear();
+    self.eviction.clear();
+    for namespace in self.namespaces.iter() {
+      namespace.clear();
+    }
+    for tenant in self.tenants.iter() {
+      tenant.clear();
+    }
+    self.total_bytes.store(0, Ordering::Relaxed);
+    if let Some(tier) = self.disk_tier.read().as_ref() {
+      tier.clear();
+    }
+    count
+  }
+
+  fn log_put(&self, entry: &CacheEntry) {
+    self.log_record(&Record::Put(entry));
+  }
+
+  fn log_record(&self, record: &Record) {
+    if self.log_queue.push(record).is_err() {
+      self.increment_stat(Stat::AppendLogFailures);
+    }
+  }
+
+  fn flush_log(&self) -> std::io::Result<()> {
+    let mut log = self.append_log.lock();
+    let queued = self.log_queue.drain();
+    match log.as_mut() {
+      Some(log) => log.append(&queued),
+      None => Ok(()),
+    }
+  }
+
+  fn compact_log(&self) -> std::io::Result<u32> {
+    match self.append_log.lock().as_mut() {
+      Some(log) => {
+        log.append(&self.log_queue.drain())?;
+        log.begin_compaction();
+      }
+      None => return Ok(0),
+    }
+
+    let generations = self.generations();
+    let entries = self.live_entries();
+    if let Some(log) = self.append_log.lock().as_mut() {
+      log.append(&self.log_queue.drain())?;
+      log.finish_compaction(&generations, &entries)?;
+      self.increment_stat(Stat::AppendLogCompactions);
+    }
+    Ok(entries.len() as u32)
+  }
+
+  fn save_snapshot(&self, path: &Path) -> std::io::Result<u32> {
+    let generations = self.generations();
+    let entries = self.live_entries();
+
+    let size = snapshot::write(path, &generations, &entries)?;
+    self.increment_stat(Stat::Snapshots);
+    self.set_stat(Stat::LastSnapshotAt, wall_millis() / 1000);
+    self.set_stat(Stat::LastSnapshotBytes, size);
+    self.set_stat(Stat::LastSnapshotEntries, entries.len() as u64);
+    Ok(entries.len() as u32)
+  }
+
+  fn unindex_tags<'a>(&self, key_hash: &str, tags: impl IntoIterator<Item = &'a String>) {
+    for tag in tags {
+      self.tag_index.remove_if_mut(tag, |_, key_hashes| {
+        key_hashes.remove(key_hash);
+        key_hashes.is_empty()
+      });
+    }
+  }
+
+  fn unindex_dependencies<'a>(
+    &self,
+    key_hash: &str,
+    parents: impl IntoIterator<Item = &'a String>,
+  ) {
+    for parent in parents {
+      self.dependents.remove_if_mut(parent, |_, key_hashes| {
+        key_hashes.remove(key_hash);
+        key_hashes.is_empty()
+      });
+    }
+  }
+
+  fn invalidate_dependents(&self, key_hash: &str) -> u32 {
+    if self.dependents.is_empty() {
+      return 0;
+    }
+
+    let mut visited = HashSet::from([key_hash.to_string()]);
+    let mut pending = vec![key_hash.to_string()];
+    let mut invalidated = 0;
+    while let Some(parent) = pending.pop() {
+      let Some((_, key_hashes)) = self.dependents.remove(&parent) else {
+        continue;
+      };
+      for dependent in key_hashes {
+        if !visited.insert(dependent.clone()) {
+          continue;
+        }
+        if let Some(entry) = self.remove_entry(&dependent) {
+          self.notify_removal(&entry, EvictionReason::Delete);
+          invalidated += 1;
+        }
+        pending.push(dependent);
+      }
+    }
+    self.add_stat(Stat::DependencyInvalidations, invalidated as u64);
+    invalidated
+  }
+
+  fn statsd_sample(&self) -> StatsdSample {
+    let mut sample = StatsdSample {
+      counters: Vec::new(),
+      gauges: vec![
+        ("entries".to_string(), self.storage.len() as f64),
+        (
+          "bytes".to_string(),
+          self.total_bytes.load(Ordering::Relaxed) as f64,
+        ),
+      ],
+    };
+    for (stat, value) in self.stats.iter() {
+      if stat.name().starts_with("last_") {
+        sample.gauges.push((stat.name().to_string(), value as f64));
+      } else {
+        sample.counters.push((stat.name().to_string(), value));
+      }
+    }
+    sample
+  }
+
+  fn increment_stat(&self, stat: Stat) {
+    self.add_stat(stat, 1);
+  }
+
+  fn add_stat(&self, stat: Stat, amount: u64) {
+    self.stats.add(stat, amount);
+    self.rolling.record(stat, amount);
+  }
+
+  fn set_stat(&self, stat: Stat, value: u64) {
+    self.stats.set(stat, value);
+  }
+
+  fn live_count(&self) -> usize {
+    self
+      .storage
+      .len()
+      .saturating_sub(self.expiry_index.due_count(self.now()))
+  }
+
+  fn account_bytes(&self, added: u64, removed: u64) {
+    if added >= removed {
+      self
+        .total_bytes
+        .fetch_add(added - removed, Ordering::Relaxed);
+    } else {
+      self
+        .total_bytes
+        .fetch_sub(removed - added, Ordering::Relaxed);
+    }
+  }
+
+  fn check_entry(&self, entry: &CacheEntry) -> Result<(), CacheErrorCode> {
+    self.ensure_writable().map_err(coded_error)?;
+    self
+      .ensure_direct_write(&entry.original_key)
+      .map_err(coded_error)?;
+    self.check_limits(entry)
+  }
+
+  fn check_limits(&self, entry: &CacheEntry) -> Result<(), CacheErrorCode> {
+    let limits = self.limits();
+    let key = &entry.original_key;
+    if let Some(max_key_length) = limits.max_key_length {
+      if key.len() > max_key_length {
+        return Err(Error::new(
+          CacheErrorCode::KeyTooLong,
+          format!(
+            "Key of {} bytes exceeds maxKeyLength of {max_key_length}",
+            key.len()
+          ),
+        ));
+      }
+    }
+    if let Some(max_value_bytes) = limits.max_value_bytes {
+      let value_bytes = entry.value.len() as u64;
+      if value_bytes > max_value_bytes {
+        return Err(Error::new(
+          CacheErrorCode::ValueTooLarge,
+          format!("Value of {value_bytes} bytes for key '{key}' exceeds maxValueBytes of {max_value_bytes}"),
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  fn make_room(&self, key_hash: &str, incoming_bytes: u64) -> Result<(), CacheErrorCode> {
+    let limits = self.limits();
+    if let Some(max_bytes) = limits.max_bytes {
+      if incoming_bytes > max_bytes {
+        return Err(Error::new(
+          CacheErrorCode::CapacityExceeded,
+          format!("Entry of {incoming_bytes} bytes exceeds maxBytes of {max_bytes}"),
+        ));
+      }
+    }
+
+    let replaced_bytes = self.storage.get(key_hash).map(|entry| entry.weight());
+    let (max_size, max_bytes) = match self.pressure_cap() {
+      Some(cap) => (
+        limits.max_size.min(cap.max_entries),
+        Some(
+          limits
+            .max_bytes
+            .map_or(cap.max_bytes, |max| max.min(cap.max_bytes)),
+        ),
+      ),
+      None => (limits.max_size, limits.max_bytes),
+    };
+
+    if replaced_bytes.is_none() {
+      while self.storage.len() >= max_size && self.evict_one() {}
+    }
+
+    if let Some(max_bytes) = max_bytes {
+      let budget = (max_bytes + replaced_bytes.unwrap_or(0)).saturating_sub(incoming_bytes);
+      while self.used_bytes() > budget && self.evict_one() {}
+    }
+
+    Ok(())
+  }
+
+  fn make_byte_room(&self, incoming_bytes: u64) -> Result<(), CacheErrorCode> {
+    let Some(max_bytes) = self.limits().max_bytes else {
+      return Ok(());
+    };
+    if incoming_bytes > max_bytes {
+      return Err(Error::new(
+        CacheErrorCode::CapacityExceeded,
+        format!("Response of {incoming_bytes} bytes exceeds maxBytes of {max_bytes}"),
+      ));
+    }
+    while self.used_bytes() > max_bytes - incoming_bytes && self.evict_one() {}
+    Ok(())
+  }
+
+  fn used_bytes(&self) -> u64 {
+    self.total_bytes.load(Ordering::Relaxed) + self.idempotency.bytes()
+  }
+
+  fn evict_one(&self) -> bool {
+    self.evict_next(
+      || self.eviction.victim(),
+      |key_hash, entry| self.eviction.on_insert(key_hash, entry.priority.tier()),
+    )
+  }
+
+  fn evict_next(
+    &self,
+    victim: impl Fn() -> Option<String>,
+    requeue: impl Fn(&str, &CacheEntry),
+  ) -> bool {
+    let mut busy = Vec::new();
+    let evicted = loop {
+      let Some(key_hash) = victim() else {
+        break false;
+      };
+      match self.storage.try_lock_key(&key_hash) {
+        Some(_stripe) => {
+          if self.evict(&key_hash) {
+            break true;
+          }
+        }
+        None => busy.push(key_hash),
+      }
+    };
+    for key_hash in busy {
+      if let Some(entry) = self.storage.get(&key_hash) {
+        requeue(&key_hash, &entry);
+      }
+    }
+    evicted
+  }
+
+  fn evict(&self, key_hash: &str) -> bool {
+    if self.pinned.contains(key_hash) {
+      return false;
+    }
+    let Some(entry) = self.remove_entry(key_hash) else {
+      return false;
+    };
+    self.increment_stat(Stat::Evictions);
+    if let Some(namespace) = self.namespace_for(&entry.original_key) {
+      namespace.evictions.fetch_add(1, Ordering::Relaxed);
+    }
+    if let Some(tenant) = self.tenant_for(entry.tenant.as_deref()) {
+      tenant.evictions.fetch_add(1, Ordering::Relaxed);
+    }
+    if !self.spill(key_hash, &entry) {
+      self.notify_removal(&entry, EvictionReason::Capacity);
+    }
+    true
   }
 }
@@ -0,0 +1,191 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+const BUCKET_BOUNDS_US: [u64; 16] = [
+  5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000,
+  1_000_000,
+];
+
+const SUB_BUCKET_BITS: u32 = 5;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+const MAX_TRACKED_NS: u64 = (1 << 40) - 1;
+const FINE_BUCKETS: usize = fine_bucket(MAX_TRACKED_NS) + 1;
+
+const fn fine_bucket(nanos: u64) -> usize {
+  if nanos < SUB_BUCKETS {
+    return nanos as usize;
+  }
+  let shift = 63 - nanos.leading_zeros() - SUB_BUCKET_BITS;
+  ((shift as u64 + 1) * SUB_BUCKETS + (nanos >> shift) - SUB_BUCKETS) as usize
+}
+
+fn fine_bucket_ceiling(index: usize) -> u64 {
+  let index = index as u64;
+  if index < SUB_BUCKETS {
+    return index;
+  }
+  let shift = index / SUB_BUCKETS - 1;
+  let mantissa = SUB_BUCKETS + index % SUB_BUCKETS;
+  ((mantissa + 1) << shift) - 1
+}
+
+pub(crate) struct Histogram {
+  buckets: [AtomicU64; BUCKET_BOUNDS_US.len() + 1],
+  fine: Box<[AtomicU64]>,
+  sum_ns: AtomicU64,
+  max_ns: AtomicU64,
+  count: AtomicU64,
+}
+
+impl Default for Histogram {
+  fn default() -> Self {
+    Self {
+      buckets: Default::default(),
+      fine: (0..FINE_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+      sum_ns: AtomicU64::new(0),
+      max_ns: AtomicU64::new(0),
+      count: AtomicU64::new(0),
+    }
+  }
+}
+
+pub(crate) struct Percentiles {
+  pub(crate) count: u64,
+  pub(crate) mean: Duration,
+  pub(crate) p50: Duration,
+  pub(crate) p95: Duration,
+  pub(crate) p99: Duration,
+  pub(crate) max: Duration,
+}
+
+impl Histogram {
+  pub(crate) fn record(&self, elapsed: Duration) {
+    let micros = elapsed.as_micros() as u64;
+    let nanos = elapsed.as_nanos().min(MAX_TRACKED_NS as u128) as u64;
+    let bucket = BUCKET_BOUNDS_US.partition_point(|bound| *bound < micros);
+    self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    self.fine[fine_bucket(nanos)].fetch_add(1, Ordering::Relaxed);
+    self.sum_ns.fetch_add(nanos, Ordering::Relaxed);
+    self.max_ns.fetch_max(nanos, Ordering::Relaxed);
+    self.count.fetch_add(1, Ordering::Relaxed);
+  }
+
+  fn reset(&self) {
+    for bucket in self.buckets.iter().chain(self.fine.iter()) {
+      bucket.store(0, Ordering::Relaxed);
+    }
+    self.sum_ns.store(0, Ordering::Relaxed);
+    self.max_ns.store(0, Ordering::Relaxed);
+    self.count.store(0, Ordering::Relaxed);
+  }
+
+  pub(crate) fn percentiles(&self) -> Percentiles {
+    let counts: Vec<u64> = self
+      .fine
+      .iter()
+      .map(|bucket| bucket.load(Ordering::Relaxed))
+      .collect();
+    let count: u64 = counts.iter().sum();
+    let max_ns = self.max_ns.load(Ordering::Relaxed);
+    let quantile = |q: f64| {
+      let rank = ((q * count as f64).ceil() as u64).max(1);
+      let mut seen = 0;
+      for (index, bucket) in counts.iter().enumerate() {
+        seen += bucket;
+        if seen >= rank {
+          return Duration::from_nanos(fine_bucket_ceiling(index).min(max_ns));
+        }
+      }
+      Duration::ZERO
+    };
+
+    Percentiles {
+      count,
+      mean: Duration::from_nanos(
+        self
+          .sum_ns
+          .load(Ordering::Relaxed)
+          .checked_div(count)
+          .unwrap_or(0),
+      ),
+      p50: quantile(0.50),
+      p95: quantile(0.95),
+      p99: quantile(0.99),
+      max: Duration::from_nanos(max_ns),
+    }
+  }
+
+  fn write_prometheus(&self, out: &mut String, name: &str, operation: &str) {
+    let mut cumulative = 0;
+    for (bound, bucket) in BUCKET_BOUNDS_US.iter().zip(&self.buckets) {
+      cumulative += bucket.load(Ordering::Relaxed);
+      let le = *bound as f64 / 1_000_000.0;
+      let _ = writeln!(
+        out,
+        "{name}_bucket{{operation=\"{operation}\",le=\"{le}\"}} {cumulative}"
+      );
+    }
+    let count = self.count.load(Ordering::Relaxed);
+    let sum = self.sum_ns.load(Ordering::Relaxed) as f64 / 1_000_000_000.0;
+    let _ = writeln!(
+      out,
+      "{name}_bucket{{operation=\"{operation}\",le=\"+Inf\"}} {count}"
+    );
+    let _ = writeln!(out, "{name}_sum{{operation=\"{operation}\"}} {sum}");
+    let _ = writeln!(out, "{name}_count{{operation=\"{operation}\"}} {count}");
+  }
+}
+
+#[derive(Default)]
+pub(crate) struct Latencies {
+  pub(crate) get: Histogram,
+  pub(crate) set: Histogram,
+  pub(crate) delete: Histogram,
+  pub(crate) loader: Histogram,
+}
+
+impl Latencies {
+  pub(crate) fn reset(&self) {
+    for (_, histogram) in self.operations() {
+      histogram.reset();
+    }
+  }
+
+  fn operations(&self) -> [(&'static str, &Histogram); 4] {
+    [
+      ("get", &self.get),
+      ("set", &self.set),
+      ("delete", &self.delete),
+      ("loader", &self.loader),
+    ]
+  }
+}
+
+pub(crate) enum Metric<'a> {
+  Counter(&'a str, &'a str, u64),
+  Gauge(&'a str, &'a str, f64),
+}
+
+pub(crate) fn render_prometheus(metrics: &[Metric], latencies: &Latencies) -> String {
+  let mut out = String::new();
+  for metric in metrics {
+    let (name, help, kind, value) = match metric {
+      Metric::Counter(name, help, value) => {
+        (format!("{name}_total"), help, "counter", *value as f64)
+      }
+      Metric::Gauge(name, help, value) => (name.to_string(), help, "gauge", *value),
+    };
+    let _ = writeln!(out, "# HELP microservice_cache_{name} {help}");
+    let _ = writeln!(out, "# TYPE microservice_cache_{name} {kind}");
+    let _ = writeln!(out, "microservice_cache_{name} {value}");
+  }
+
+  let name = "microservice_cache_operation_duration_seconds";
+  let _ = writeln!(out, "# HELP {name} Latency of cache operations.");
+  let _ = writeln!(out, "# TYPE {name} histogram");
+  for (operation, histogram) in latencies.operations() {
+    histogram.write_prometheus(&mut out, name, operation);
+  }
+  out
+}
@@ -0,0 +1,89 @@
+use crate::groups::NAMESPACE_SEPARATOR;
+use crate::occupancy::Occupancy;
+use parking_lot::RwLock;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+struct Limits {
+  max_size: Option<usize>,
+  default_ttl_ms: Option<u64>,
+}
+
+pub(crate) struct Namespace {
+  pub(crate) name: String,
+  limits: RwLock<Limits>,
+  occupancy: Occupancy,
+  generation: AtomicU64,
+  pub(crate) hits: AtomicU64,
+  pub(crate) misses: AtomicU64,
+  pub(crate) sets: AtomicU64,
+  pub(crate) deletes: AtomicU64,
+  pub(crate) evictions: AtomicU64,
+}
+
+impl Deref for Namespace {
+  type Target = Occupancy;
+
+  fn deref(&self) -> &Occupancy {
+    &self.occupancy
+  }
+}
+
+impl Namespace {
+  pub(crate) fn new(name: String) -> Self {
+    Self {
+      name,
+      limits: RwLock::new(Limits {
+        max_size: None,
+        default_ttl_ms: None,
+      }),
+      occupancy: Occupancy::default(),
+      generation: AtomicU64::new(0),
+      hits: AtomicU64::new(0),
+      misses: AtomicU64::new(0),
+      sets: AtomicU64::new(0),
+      deletes: AtomicU64::new(0),
+      evictions: AtomicU64::new(0),
+    }
+  }
+
+  pub(crate) fn configure(&self, max_size: Option<usize>, default_ttl_ms: Option<u64>) {
+    let mut limits = self.limits.write();
+    if max_size.is_some() {
+      limits.max_size = max_size;
+    }
+    if default_ttl_ms.is_some() {
+      limits.default_ttl_ms = default_ttl_ms;
+    }
+  }
+
+  pub(crate) fn max_size(&self) -> Option<usize> {
+    self.limits.read().max_size
+  }
+
+  pub(crate) fn default_ttl_ms(&self) -> Option<u64> {
+    self.limits.read().default_ttl_ms
+  }
+
+  pub(crate) fn key(&self, key: &str) -> String {
+    format!("{}{NAMESPACE_SEPARATOR}{key}", self.name)
+  }
+
+  pub(crate) fn strip<'a>(&self, key: &'a str) -> Option<&'a str> {
+    key
+      .strip_prefix(self.name.as_str())?
+      .strip_prefix(NAMESPACE_SEPARATOR)
+  }
+
+  pub(crate) fn generation(&self) -> u64 {
+    self.generation.load(Ordering::Relaxed)
+  }
+
+  pub(crate) fn bump_generation(&self) -> u64 {
+    self.generation.fetch_add(1, Ordering::Relaxed) + 1
+  }
+
+  pub(crate) fn restore_generation(&self, generation: u64) -> bool {
+    self.generation.fetch_max(generation, Ordering::Relaxed) < generation
+  }
+}
@@ -0,0 +1,66 @@
+use crate::eviction::{Lru, Policy};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub(crate) struct Occupancy {
+  eviction: Lru,
+  entries: AtomicU64,
+  bytes: AtomicU64,
+}
+
+impl Default for Occupancy {
+  fn default() -> Self {
+    Self {
+      eviction: Lru::new(),
+      entries: AtomicU64::new(0),
+      bytes: AtomicU64::new(0),
+    }
+  }
+}
+
+impl Occupancy {
+  pub(crate) fn entries(&self) -> u64 {
+    self.entries.load(Ordering::Relaxed)
+  }
+
+  pub(crate) fn bytes(&self) -> u64 {
+    self.bytes.load(Ordering::Relaxed)
+  }
+
+  pub(crate) fn on_insert(&self, key_hash: &str, added_bytes: u64, replaced_bytes: Option<u64>) {
+    self.eviction.on_insert(key_hash);
+    match replaced_bytes {
+      Some(replaced) => {
+        self.bytes.fetch_add(added_bytes, Ordering::Relaxed);
+        self.bytes.fetch_sub(replaced, Ordering::Relaxed);
+      }
+      None => {
+        self.entries.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(added_bytes, Ordering::Relaxed);
+      }
+    }
+  }
+
+  pub(crate) fn track(&self, key_hash: &str) {
+    self.eviction.on_insert(key_hash);
+  }
+
+  pub(crate) fn on_access(&self, key_hash: &str) {
+    self.eviction.on_access(key_hash);
+  }
+
+  pub(crate) fn on_remove(&self, key_hash: &str, bytes: u64) {
+    self.eviction.on_remove(key_hash);
+    self.entries.fetch_sub(1, Ordering::Relaxed);
+    self.bytes.fetch_sub(bytes, Ordering::Relaxed);
+  }
+
+  pub(crate) fn victim(&self) -> Option<String> {
+    self.eviction.victim()
+  }
+
+  pub(crate) fn clear(&self) {
+    self.eviction.clear();
+    self.entries.store(0, Ordering::Relaxed);
+    self.bytes.store(0, Ordering::Relaxed);
+  }
+}
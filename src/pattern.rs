@@ -0,0 +1,68 @@
+#[derive(Clone)]
+pub(crate) struct KeyPattern {
+  tokens: Vec<Token>,
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+  Literal(char),
+  AnyChar,
+  AnySequence,
+}
+
+impl KeyPattern {
+  pub(crate) fn parse(pattern: &str) -> Self {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+      tokens.push(match c {
+        '*' => Token::AnySequence,
+        '?' => Token::AnyChar,
+        '\\' => Token::Literal(chars.next().unwrap_or('\\')),
+        c => Token::Literal(c),
+      });
+    }
+    tokens.dedup_by(|a, b| *a == Token::AnySequence && *b == Token::AnySequence);
+    Self { tokens }
+  }
+
+  pub(crate) fn matches(&self, key: &str) -> bool {
+    let key: Vec<char> = key.chars().collect();
+    let (mut t, mut k) = (0, 0);
+    let mut backtrack = None;
+
+    while k < key.len() {
+      match self.tokens.get(t) {
+        Some(Token::AnySequence) => {
+          backtrack = Some((t, k));
+          t += 1;
+          continue;
+        }
+        Some(Token::AnyChar) => {
+          t += 1;
+          k += 1;
+          continue;
+        }
+        Some(Token::Literal(c)) if *c == key[k] => {
+          t += 1;
+          k += 1;
+          continue;
+        }
+        _ => {}
+      }
+
+      match backtrack {
+        Some((star, matched)) => {
+          t = star + 1;
+          k = matched + 1;
+          backtrack = Some((star, matched + 1));
+        }
+        None => return false,
+      }
+    }
+
+    self.tokens[t..]
+      .iter()
+      .all(|token| *token == Token::AnySequence)
+  }
+}
@@ -0,0 +1,86 @@
+use dashmap::DashMap;
+
+struct Bucket {
+  tokens: f64,
+  capacity: f64,
+  refill_per_ms: f64,
+  updated_at: u64,
+}
+
+impl Bucket {
+  fn refill(&mut self, now: u64) {
+    let elapsed = now.saturating_sub(self.updated_at) as f64;
+    self.tokens = (self.tokens + elapsed * self.refill_per_ms).min(self.capacity);
+    self.updated_at = now;
+  }
+}
+
+pub(crate) struct Decision {
+  pub(crate) allowed: bool,
+  pub(crate) remaining: u32,
+  pub(crate) retry_after_ms: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct RateLimiter {
+  buckets: DashMap<String, Bucket>,
+}
+
+impl RateLimiter {
+  pub(crate) fn take(
+    &self,
+    key: String,
+    limit: u32,
+    window_ms: u64,
+    cost: u32,
+    max_buckets: usize,
+    now: u64,
+  ) -> Option<Decision> {
+    if self.buckets.len() >= max_buckets && !self.buckets.contains_key(&key) {
+      self.purge_full(now);
+      if self.buckets.len() >= max_buckets {
+        return None;
+      }
+    }
+    let capacity = limit as f64;
+    let refill_per_ms = capacity / window_ms.max(1) as f64;
+    let mut bucket = self.buckets.entry(key).or_insert_with(|| Bucket {
+      tokens: capacity,
+      capacity,
+      refill_per_ms,
+      updated_at: now,
+    });
+    bucket.capacity = capacity;
+    bucket.refill_per_ms = refill_per_ms;
+    bucket.refill(now);
+
+    let cost = cost as f64;
+    let allowed = bucket.tokens >= cost;
+    if allowed {
+      bucket.tokens -= cost;
+    }
+    let retry_after_ms = if allowed {
+      0
+    } else if cost > capacity {
+      u64::MAX
+    } else {
+      ((cost - bucket.tokens) / refill_per_ms).ceil() as u64
+    };
+    Some(Decision {
+      allowed,
+      remaining: bucket.tokens.floor() as u32,
+      retry_after_ms,
+    })
+  }
+
+  pub(crate) fn reset(&self, key: &str) -> bool {
+    self.buckets.remove(key).is_some()
+  }
+
+  pub(crate) fn purge_full(&self, now: u64) {
+    self.buckets.retain(|_, bucket| {
+      bucket.refill(now);
+      bucket.tokens < bucket.capacity
+    });
+  }
+}
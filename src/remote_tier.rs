@@ -0,0 +1,113 @@
+use crate::CacheValue;
+use parking_lot::Mutex;
+use redis::{Commands, Connection, RedisResult};
+use std::mem;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const MAGIC: &[u8; 4] = b"MSC1";
+
+pub(crate) fn encode(value: &CacheValue) -> Vec<u8> {
+  [MAGIC.as_slice(), &value.encode()].concat()
+}
+
+pub(crate) fn decode(payload: Vec<u8>) -> Option<CacheValue> {
+  match payload.strip_prefix(MAGIC) {
+    Some(encoded) => CacheValue::decode(encoded),
+    None => Some(match String::from_utf8(payload) {
+      Ok(text) => text.into(),
+      Err(err) => err.into_bytes().into(),
+    }),
+  }
+}
+
+pub(crate) enum RemoteWrite {
+  Store {
+    key: String,
+    payload: Vec<u8>,
+    expires_at: Option<u64>,
+  },
+  Remove(String),
+}
+
+#[derive(Default)]
+pub(crate) struct FlushReport {
+  pub(crate) writes: u64,
+  pub(crate) failures: u64,
+}
+
+pub(crate) struct RedisTier {
+  connection: Mutex<Connection>,
+  prefix: String,
+  pending: Mutex<Vec<RemoteWrite>>,
+  flushing: Mutex<()>,
+}
+
+impl RedisTier {
+  pub(crate) fn connect(url: &str, prefix: String) -> RedisResult<Self> {
+    let connection = redis::Client::open(url)?.get_connection_with_timeout(TIMEOUT)?;
+    connection.set_read_timeout(Some(TIMEOUT))?;
+    connection.set_write_timeout(Some(TIMEOUT))?;
+    Ok(Self {
+      connection: Mutex::new(connection),
+      prefix,
+      pending: Mutex::new(Vec::new()),
+      flushing: Mutex::new(()),
+    })
+  }
+
+  pub(crate) fn enqueue(&self, write: RemoteWrite) {
+    self.pending.lock().push(write);
+  }
+
+  pub(crate) fn flush(&self, now: u64) -> FlushReport {
+    let _flushing = self.flushing.lock();
+    let pending = mem::take(&mut *self.pending.lock());
+    let mut report = FlushReport::default();
+    for write in pending {
+      let result = match write {
+        RemoteWrite::Store {
+          key,
+          payload,
+          expires_at,
+        } => {
+          let ttl_ms = expires_at.map(|expires_at| expires_at.saturating_sub(now));
+          let stored = self.store(&key, &payload, ttl_ms);
+          report.writes += stored.is_ok() as u64;
+          stored
+        }
+        RemoteWrite::Remove(key) => self.remove(&key),
+      };
+      report.failures += result.is_err() as u64;
+    }
+    report
+  }
+
+  pub(crate) fn fetch(&self, key: &str) -> RedisResult<Option<(Vec<u8>, Option<u64>)>> {
+    let key = self.remote_key(key);
+    let (value, ttl_ms): (Option<Vec<u8>>, i64) = redis::pipe()
+      .get(&key)
+      .cmd("PTTL")
+      .arg(&key)
+      .query(&mut *self.connection.lock())?;
+    Ok(value.map(|value| (value, u64::try_from(ttl_ms).ok())))
+  }
+
+  pub(crate) fn store(&self, key: &str, value: &[u8], ttl_ms: Option<u64>) -> RedisResult<()> {
+    let key = self.remote_key(key);
+    let mut connection = self.connection.lock();
+    match ttl_ms {
+      Some(ttl_ms) => connection.pset_ex(key, value, ttl_ms.max(1)),
+      None => connection.set(key, value),
+    }
+  }
+
+  pub(crate) fn remove(&self, key: &str) -> RedisResult<()> {
+    let _: usize = self.connection.lock().del(self.remote_key(key))?;
+    Ok(())
+  }
+
+  fn remote_key(&self, key: &str) -> String {
+    format!("{}{key}", self.prefix)
+  }
+}
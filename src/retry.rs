@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+#[derive(Clone, Copy)]
+pub(crate) struct RetryPolicy {
+  pub(crate) max_retries: u32,
+  base_delay_ms: u64,
+  max_delay_ms: u64,
+}
+
+impl RetryPolicy {
+  pub(crate) fn new(max_retries: u32, base_delay_ms: u64, max_delay_ms: u64) -> Self {
+    Self {
+      max_retries,
+      base_delay_ms,
+      max_delay_ms: max_delay_ms.max(base_delay_ms),
+    }
+  }
+
+  pub(crate) fn delay(&self, attempt: u32) -> Duration {
+    let ceiling = self
+      .base_delay_ms
+      .saturating_mul(1 << attempt.min(32))
+      .min(self.max_delay_ms);
+    let floor = ceiling / 2;
+    Duration::from_millis(floor + fastrand::u64(0..=ceiling - floor))
+  }
+}
@@ -0,0 +1,101 @@
+use crate::counters::Stat;
+use crate::now_millis;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub(crate) const WINDOWS_SECONDS: [u64; 3] = [60, 300, 900];
+const SLOTS: u64 = WINDOWS_SECONDS[WINDOWS_SECONDS.len() - 1];
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct WindowCounts {
+  pub(crate) hits: u64,
+  pub(crate) misses: u64,
+  pub(crate) sets: u64,
+  pub(crate) deletes: u64,
+  pub(crate) evictions: u64,
+}
+
+impl WindowCounts {
+  fn add(&mut self, other: &WindowCounts) {
+    self.hits += other.hits;
+    self.misses += other.misses;
+    self.sets += other.sets;
+    self.deletes += other.deletes;
+    self.evictions += other.evictions;
+  }
+}
+
+#[derive(Default)]
+struct Slot {
+  second: u64,
+  counts: WindowCounts,
+}
+
+pub(crate) struct Window {
+  pub(crate) seconds: u64,
+  pub(crate) counts: WindowCounts,
+}
+
+pub(crate) struct RollingCounters {
+  slots: Box<[Mutex<Slot>]>,
+  since: AtomicU64,
+}
+
+impl Default for RollingCounters {
+  fn default() -> Self {
+    Self {
+      slots: (0..SLOTS).map(|_| Mutex::new(Slot::default())).collect(),
+      since: AtomicU64::new(now_millis() / 1000),
+    }
+  }
+}
+
+impl RollingCounters {
+  pub(crate) fn record(&self, stat: Stat, amount: u64) {
+    let counter: fn(&mut WindowCounts) -> &mut u64 = match stat {
+      Stat::Hits => |counts| &mut counts.hits,
+      Stat::Misses => |counts| &mut counts.misses,
+      Stat::Sets => |counts| &mut counts.sets,
+      Stat::Deletes => |counts| &mut counts.deletes,
+      Stat::Evictions => |counts| &mut counts.evictions,
+      _ => return,
+    };
+
+    let second = now_millis() / 1000;
+    let mut slot = self.slots[(second % SLOTS) as usize].lock();
+    if slot.second != second {
+      *slot = Slot {
+        second,
+        counts: WindowCounts::default(),
+      };
+    }
+    *counter(&mut slot.counts) += amount;
+  }
+
+  pub(crate) fn windows(&self) -> [Window; WINDOWS_SECONDS.len()] {
+    let now = now_millis() / 1000;
+    let uptime = now.saturating_sub(self.since.load(Ordering::Relaxed)) + 1;
+    let mut windows = WINDOWS_SECONDS.map(|seconds| Window {
+      seconds: seconds.min(uptime),
+      counts: WindowCounts::default(),
+    });
+
+    for slot in self.slots.iter() {
+      let slot = slot.lock();
+      let age = now.wrapping_sub(slot.second);
+      for (window, seconds) in windows.iter_mut().zip(WINDOWS_SECONDS) {
+        if age < seconds {
+          window.counts.add(&slot.counts);
+        }
+      }
+    }
+    windows
+  }
+
+  pub(crate) fn reset(&self) {
+    for slot in self.slots.iter() {
+      *slot.lock() = Slot::default();
+    }
+    self.since.store(now_millis() / 1000, Ordering::Relaxed);
+  }
+}
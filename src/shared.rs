@@ -0,0 +1,40 @@
+use crate::invalidation::InvalidationBus;
+use crate::CacheCore;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::sync::{Arc, LazyLock};
+
+static REGISTRY: LazyLock<DashMap<String, SharedCache>> = LazyLock::new(DashMap::new);
+
+#[derive(Clone)]
+pub(crate) struct SharedCache {
+  pub(crate) core: CacheCore,
+  pub(crate) invalidation: Arc<Mutex<Option<InvalidationBus>>>,
+}
+
+impl SharedCache {
+  fn is(&self, core: &CacheCore) -> bool {
+    Arc::ptr_eq(&self.core.storage, &core.storage)
+  }
+}
+
+pub(crate) fn publish(name: String, shared: SharedCache) -> bool {
+  match REGISTRY.entry(name) {
+    Entry::Occupied(existing) => existing.get().is(&shared.core),
+    Entry::Vacant(slot) => {
+      slot.insert(shared);
+      true
+    }
+  }
+}
+
+pub(crate) fn find(name: &str) -> Option<SharedCache> {
+  REGISTRY.get(name).map(|shared| shared.clone())
+}
+
+pub(crate) fn withdraw(name: &str, core: &CacheCore) -> bool {
+  REGISTRY
+    .remove_if(name, |_, shared| shared.is(core))
+    .is_some()
+}
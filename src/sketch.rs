@@ -0,0 +1,70 @@
+use fnv::FnvHasher;
+use std::hash::{Hash, Hasher};
+
+pub(crate) const DEPTH: usize = 4;
+const MAX_COUNT: u8 = 15;
+
+pub(crate) fn slot(row: usize, key: &str, mask: usize) -> usize {
+  let mut hasher = FnvHasher::default();
+  row.hash(&mut hasher);
+  key.hash(&mut hasher);
+  hasher.finish() as usize & mask
+}
+
+pub(crate) struct CountMinSketch {
+  rows: [Vec<u8>; DEPTH],
+  mask: usize,
+  additions: usize,
+  sample_size: usize,
+}
+
+impl CountMinSketch {
+  pub(crate) fn new(capacity: usize) -> Self {
+    let width = capacity.max(16).next_power_of_two();
+    Self {
+      rows: std::array::from_fn(|_| vec![0; width]),
+      mask: width - 1,
+      additions: 0,
+      sample_size: width * 10,
+    }
+  }
+
+  fn slot(&self, row: usize, key: &str) -> usize {
+    slot(row, key, self.mask)
+  }
+
+  pub(crate) fn increment(&mut self, key: &str) {
+    let mut incremented = false;
+    for row in 0..DEPTH {
+      let slot = self.slot(row, key);
+      let counter = &mut self.rows[row][slot];
+      if *counter < MAX_COUNT {
+        *counter += 1;
+        incremented = true;
+      }
+    }
+
+    if incremented {
+      self.additions += 1;
+      if self.additions >= self.sample_size {
+        self.halve();
+      }
+    }
+  }
+
+  pub(crate) fn estimate(&self, key: &str) -> u8 {
+    (0..DEPTH)
+      .map(|row| self.rows[row][self.slot(row, key)])
+      .min()
+      .unwrap_or(0)
+  }
+
+  fn halve(&mut self) {
+    for row in &mut self.rows {
+      for counter in row.iter_mut() {
+        *counter /= 2;
+      }
+    }
+    self.additions /= 2;
+  }
+}
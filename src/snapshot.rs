@@ -0,0 +1,56 @@
+use crate::CacheEntry;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"MSC2";
+const LEGACY_MAGIC: &[u8; 4] = b"MSC1";
+
+pub(crate) struct Snapshot {
+  pub(crate) generations: Vec<(String, u64)>,
+  pub(crate) entries: Vec<CacheEntry>,
+}
+
+pub(crate) fn write(
+  path: &Path,
+  generations: &[(String, u64)],
+  entries: &[CacheEntry],
+) -> io::Result<u64> {
+  let mut partial = path.as_os_str().to_owned();
+  partial.push(".tmp");
+  let partial = Path::new(&partial);
+
+  let mut writer = BufWriter::new(File::create(partial)?);
+  writer.write_all(MAGIC)?;
+  bincode::serialize_into(&mut writer, &(generations, entries)).map_err(io::Error::other)?;
+  let file = writer.into_inner()?;
+  file.sync_all()?;
+  let size = file.metadata()?.len();
+
+  fs::rename(partial, path)?;
+  Ok(size)
+}
+
+pub(crate) fn read(path: &Path) -> io::Result<Snapshot> {
+  let mut reader = BufReader::new(File::open(path)?);
+  let mut magic = [0; 4];
+  reader.read_exact(&mut magic)?;
+  let invalid = |err| io::Error::new(io::ErrorKind::InvalidData, err);
+  match &magic {
+    MAGIC => {
+      let (generations, entries) = bincode::deserialize_from(reader).map_err(invalid)?;
+      Ok(Snapshot {
+        generations,
+        entries,
+      })
+    }
+    LEGACY_MAGIC => Ok(Snapshot {
+      generations: Vec::new(),
+      entries: bincode::deserialize_from(reader).map_err(invalid)?,
+    }),
+    _ => Err(io::Error::new(
+      io::ErrorKind::InvalidData,
+      "not a cache snapshot file",
+    )),
+  }
+}
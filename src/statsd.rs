@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::UdpSocket;
+
+const MAX_PACKET_BYTES: usize = 1432;
+
+pub(crate) struct StatsdSample {
+  pub(crate) counters: Vec<(String, u64)>,
+  pub(crate) gauges: Vec<(String, f64)>,
+}
+
+pub(crate) struct StatsdEmitter {
+  socket: UdpSocket,
+  prefix: String,
+  tags: String,
+  reported: HashMap<String, u64>,
+}
+
+impl StatsdEmitter {
+  pub(crate) fn connect(address: &str, prefix: String, tags: &[String]) -> io::Result<Self> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect(address)?;
+
+    Ok(Self {
+      socket,
+      prefix,
+      tags: if tags.is_empty() {
+        String::new()
+      } else {
+        format!("|#{}", tags.join(","))
+      },
+      reported: HashMap::new(),
+    })
+  }
+
+  pub(crate) fn emit(&mut self, sample: &StatsdSample) {
+    let mut lines = Vec::with_capacity(sample.counters.len() + sample.gauges.len());
+    for (name, total) in &sample.counters {
+      let previous = self.reported.insert(name.clone(), *total).unwrap_or(0);
+      let delta = if *total < previous {
+        *total
+      } else {
+        total - previous
+      };
+      if delta > 0 {
+        lines.push(format!("{}{name}:{delta}|c{}", self.prefix, self.tags));
+      }
+    }
+    for (name, value) in &sample.gauges {
+      lines.push(format!("{}{name}:{value}|g{}", self.prefix, self.tags));
+    }
+
+    let mut packet = String::new();
+    for line in lines {
+      if !packet.is_empty() && packet.len() + line.len() + 1 > MAX_PACKET_BYTES {
+        let _ = self.socket.send(packet.as_bytes());
+        packet.clear();
+      }
+      if !packet.is_empty() {
+        packet.push('\n');
+      }
+      packet.push_str(&line);
+    }
+    if !packet.is_empty() {
+      let _ = self.socket.send(packet.as_bytes());
+    }
+  }
+}
@@ -0,0 +1,258 @@
+use compact_str::CompactString;
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::DashMap;
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::cell::RefCell;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+const STRIPES: usize = 256;
+const ACQUIRE_TIMEOUT: Duration = Duration::from_millis(1);
+
+thread_local! {
+  static HELD_STRIPES: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+#[repr(align(64))]
+#[derive(Default)]
+struct Stripe(RwLock<()>);
+
+pub(crate) struct Storage<V> {
+  map: DashMap<CompactString, V>,
+  stripes: Box<[Stripe]>,
+}
+
+pub(crate) struct Locked<'a, T> {
+  inner: T,
+  _stripe: Option<RwLockReadGuard<'a, ()>>,
+}
+
+pub(crate) struct TransactionLocks<'a> {
+  _guards: Vec<RwLockWriteGuard<'a, ()>>,
+  held: Vec<(usize, usize)>,
+}
+
+impl<V> Storage<V> {
+  pub(crate) fn new(map: DashMap<CompactString, V>) -> Self {
+    Self {
+      map,
+      stripes: (0..STRIPES).map(|_| Stripe::default()).collect(),
+    }
+  }
+
+  fn stripe_of(key: &str) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish() as usize % STRIPES
+  }
+
+  fn held(&self, stripe: usize) -> (usize, usize) {
+    (self.stripes.as_ptr() as usize, stripe)
+  }
+
+  fn holds(&self, stripe: usize) -> bool {
+    HELD_STRIPES.with_borrow(|held| held.contains(&self.held(stripe)))
+  }
+
+  pub(crate) fn lock_key(&self, key: &str) -> Option<RwLockReadGuard<'_, ()>> {
+    let stripe = Self::stripe_of(key);
+    if self.holds(stripe) {
+      return None;
+    }
+    Some(self.stripes[stripe].0.read_recursive())
+  }
+
+  pub(crate) fn try_lock_key(&self, key: &str) -> Option<RwLockReadGuard<'_, ()>> {
+    let stripe = Self::stripe_of(key);
+    if self.holds(stripe) {
+      return None;
+    }
+    self.stripes[stripe].0.try_read_recursive()
+  }
+
+  pub(crate) fn lock_keys<'k>(
+    &self,
+    keys: impl IntoIterator<Item = &'k str>,
+  ) -> TransactionLocks<'_> {
+    let mut order: Vec<usize> = keys
+      .into_iter()
+      .map(Self::stripe_of)
+      .filter(|&stripe| !self.holds(stripe))
+      .collect();
+    order.sort_unstable();
+    order.dedup();
+
+    'acquire: loop {
+      let mut guards = Vec::with_capacity(order.len());
+      for &stripe in &order {
+        let lock = &self.stripes[stripe].0;
+        let guard = if guards.is_empty() {
+          Some(lock.write())
+        } else {
+          lock.try_write_for(ACQUIRE_TIMEOUT)
+        };
+        match guard {
+          Some(guard) => guards.push(guard),
+          None => {
+            drop(guards);
+            std::thread::yield_now();
+            continue 'acquire;
+          }
+        }
+      }
+      let held: Vec<_> = order.iter().map(|&stripe| self.held(stripe)).collect();
+      HELD_STRIPES.with_borrow_mut(|stripes| stripes.extend_from_slice(&held));
+      return TransactionLocks {
+        _guards: guards,
+        held,
+      };
+    }
+  }
+
+  pub(crate) fn get(&self, key: &str) -> Option<Ref<'_, CompactString, V>> {
+    self.map.get(key)
+  }
+
+  pub(crate) fn contains_key(&self, key: &str) -> bool {
+    self.map.contains_key(key)
+  }
+
+  pub(crate) fn entry(&self, key: &str) -> Entry<'_, CompactString, V> {
+    self.map.entry(CompactString::new(key))
+  }
+
+  pub(crate) fn get_mut(&self, key: &str) -> Option<Locked<'_, RefMut<'_, CompactString, V>>> {
+    let stripe = self.lock_key(key);
+    Some(Locked {
+      inner: self.map.get_mut(key)?,
+      _stripe: stripe,
+    })
+  }
+
+  pub(crate) fn remove_if(
+    &self,
+    key: &str,
+    condition: impl FnOnce(&CompactString, &V) -> bool,
+  ) -> Option<(CompactString, V)> {
+    let _stripe = self.lock_key(key);
+    self.map.remove_if(key, condition)
+  }
+
+  pub(crate) fn shard_count(&self) -> usize {
+    self.map.shards().len()
+  }
+
+  pub(crate) fn for_each_in_shard(&self, shard: usize, mut visit: impl FnMut(&CompactString, &V)) {
+    let table = self.map.shards()[shard].read();
+    // SAFETY: the read guard keeps the table alive and unchanged while it is walked.
+    unsafe {
+      for bucket in table.iter() {
+        let (key, value) = bucket.as_ref();
+        visit(key, value.get());
+      }
+    }
+  }
+}
+
+impl<V> Deref for Storage<V> {
+  type Target = DashMap<CompactString, V>;
+
+  fn deref(&self) -> &DashMap<CompactString, V> {
+    &self.map
+  }
+}
+
+impl<T> Deref for Locked<'_, T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.inner
+  }
+}
+
+impl<T> DerefMut for Locked<'_, T> {
+  fn deref_mut(&mut self) -> &mut T {
+    &mut self.inner
+  }
+}
+
+impl Drop for TransactionLocks<'_> {
+  fn drop(&mut self) {
+    HELD_STRIPES.with_borrow_mut(|stripes| stripes.retain(|stripe| !self.held.contains(stripe)));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::{Arc, Barrier};
+  use std::thread;
+
+  fn storage() -> Arc<Storage<u64>> {
+    let map = DashMap::new();
+    for key in ["left", "right"] {
+      map.insert(CompactString::new(key), 0);
+    }
+    Arc::new(Storage::new(map))
+  }
+
+  #[test]
+  fn conflicting_transactions_serialize_nested_writes() {
+    let storage = storage();
+    let workers: Vec<_> = [["left", "right"], ["right", "left"]]
+      .into_iter()
+      .map(|keys| {
+        let storage = storage.clone();
+        thread::spawn(move || {
+          for _ in 0..1000 {
+            let _locks = storage.lock_keys(keys);
+            for key in keys {
+              let current = *storage.get(key).unwrap();
+              thread::yield_now();
+              **storage.get_mut(key).unwrap() = current + 1;
+            }
+          }
+        })
+      })
+      .collect();
+    for worker in workers {
+      worker.join().unwrap();
+    }
+    assert_eq!(*storage.get("left").unwrap(), 2000);
+    assert_eq!(*storage.get("right").unwrap(), 2000);
+  }
+
+  #[test]
+  fn transactions_only_skip_their_own_stripes() {
+    let storage = storage();
+    assert_ne!(
+      Storage::<u64>::stripe_of("left"),
+      Storage::<u64>::stripe_of("right")
+    );
+    let barrier = Arc::new(Barrier::new(2));
+    let workers: Vec<_> = [("left", "right"), ("right", "left")]
+      .into_iter()
+      .map(|(own, other)| {
+        let storage = storage.clone();
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+          let locks = storage.lock_keys([own]);
+          assert!(storage.lock_key(own).is_none());
+          assert!(storage.try_lock_key(own).is_none());
+          barrier.wait();
+          assert!(storage.try_lock_key(other).is_none());
+          assert!(storage.remove_if(own, |_, _| true).is_some());
+          barrier.wait();
+          drop(locks);
+          assert!(storage.lock_key(own).is_some());
+        })
+      })
+      .collect();
+    for worker in workers {
+      worker.join().unwrap();
+    }
+    assert!(storage.is_empty());
+  }
+}
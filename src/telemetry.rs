@@ -0,0 +1,209 @@
+use std::cell::Cell;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(not(feature = "otel"), allow(dead_code))]
+pub(crate) struct TraceParent {
+  trace_id: [u8; 16],
+  span_id: [u8; 8],
+  flags: u8,
+}
+
+impl TraceParent {
+  pub(crate) fn parse(header: &str) -> Option<Self> {
+    let mut parts = header.trim().split('-');
+    let (version, trace_id, span_id, flags) =
+      (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    let [version] = hex::<1>(version)?;
+    if version == 0xff || (version == 0 && parts.next().is_some()) {
+      return None;
+    }
+
+    let parent = Self {
+      trace_id: hex(trace_id)?,
+      span_id: hex(span_id)?,
+      flags: hex::<1>(flags)?[0],
+    };
+    let valid = parent.trace_id != [0; 16] && parent.span_id != [0; 8];
+    valid.then_some(parent)
+  }
+}
+
+fn hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+  if text.len() != N * 2 || !text.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+    return None;
+  }
+  let mut bytes = [0; N];
+  for (index, byte) in bytes.iter_mut().enumerate() {
+    *byte = u8::from_str_radix(&text[index * 2..index * 2 + 2], 16).ok()?;
+  }
+  Some(bytes)
+}
+
+thread_local! {
+  static TRACE_PARENT: Cell<Option<TraceParent>> = const { Cell::new(None) };
+}
+
+pub(crate) fn with_trace_parent<T>(parent: TraceParent, run: impl FnOnce() -> T) -> T {
+  let previous = TRACE_PARENT.replace(Some(parent));
+  let result = run();
+  TRACE_PARENT.set(previous);
+  result
+}
+
+#[cfg(feature = "otel")]
+mod exporter {
+  use super::TRACE_PARENT;
+  use opentelemetry::metrics::{Counter, MeterProvider};
+  use opentelemetry::trace::{
+    Span, SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState, Tracer,
+    TracerProvider,
+  };
+  use opentelemetry::{Context, KeyValue};
+  use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+  use opentelemetry_sdk::metrics::SdkMeterProvider;
+  use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+  use opentelemetry_sdk::Resource;
+
+  pub(crate) struct OperationSpan(opentelemetry_sdk::trace::Span);
+
+  impl OperationSpan {
+    pub(crate) fn finish(mut self, outcome: &'static str) {
+      self
+        .0
+        .set_attribute(KeyValue::new("cache.outcome", outcome));
+      self.0.end();
+    }
+  }
+
+  pub(crate) struct Telemetry {
+    meter_provider: SdkMeterProvider,
+    tracer_provider: Option<SdkTracerProvider>,
+    tracer: Option<SdkTracer>,
+    operations: Counter<u64>,
+    removals: Counter<u64>,
+  }
+
+  impl Telemetry {
+    pub(crate) fn start(endpoint: &str, service_name: &str, traces: bool) -> Result<Self, String> {
+      let endpoint = endpoint.trim_end_matches('/');
+      let resource = Resource::builder()
+        .with_service_name(service_name.to_string())
+        .build();
+
+      let metric_exporter = MetricExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{endpoint}/v1/metrics"))
+        .build()
+        .map_err(|err| err.to_string())?;
+      let meter_provider = SdkMeterProvider::builder()
+        .with_periodic_exporter(metric_exporter)
+        .with_resource(resource.clone())
+        .build();
+
+      let tracer_provider = if traces {
+        let span_exporter = SpanExporter::builder()
+          .with_http()
+          .with_endpoint(format!("{endpoint}/v1/traces"))
+          .build()
+          .map_err(|err| err.to_string())?;
+        Some(
+          SdkTracerProvider::builder()
+            .with_batch_exporter(span_exporter)
+            .with_resource(resource)
+            .build(),
+        )
+      } else {
+        None
+      };
+
+      let meter = meter_provider.meter("microservice-cache");
+      Ok(Self {
+        operations: meter
+          .u64_counter("cache.operations")
+          .with_description("Cache operations by outcome")
+          .build(),
+        removals: meter
+          .u64_counter("cache.removals")
+          .with_description("Entries removed from the cache by reason")
+          .build(),
+        tracer: tracer_provider
+          .as_ref()
+          .map(|provider| provider.tracer("microservice-cache")),
+        tracer_provider,
+        meter_provider,
+      })
+    }
+
+    pub(crate) fn record(&self, operation: &'static str, outcome: &'static str) {
+      self.operations.add(
+        1,
+        &[
+          KeyValue::new("operation", operation),
+          KeyValue::new("outcome", outcome),
+        ],
+      );
+    }
+
+    pub(crate) fn record_removal(&self, reason: &'static str) {
+      self.removals.add(1, &[KeyValue::new("reason", reason)]);
+    }
+
+    pub(crate) fn span(&self, name: &'static str) -> Option<OperationSpan> {
+      let tracer = self.tracer.as_ref()?;
+      let span = match TRACE_PARENT.get() {
+        Some(parent) => {
+          let parent = SpanContext::new(
+            TraceId::from_bytes(parent.trace_id),
+            SpanId::from_bytes(parent.span_id),
+            TraceFlags::new(parent.flags),
+            true,
+            TraceState::default(),
+          );
+          tracer.start_with_context(name, &Context::new().with_remote_span_context(parent))
+        }
+        None => tracer.start(name),
+      };
+      Some(OperationSpan(span))
+    }
+  }
+
+  impl Drop for Telemetry {
+    fn drop(&mut self) {
+      if let Some(provider) = &self.tracer_provider {
+        let _ = provider.shutdown();
+      }
+      let _ = self.meter_provider.shutdown();
+    }
+  }
+}
+
+#[cfg(not(feature = "otel"))]
+mod exporter {
+  pub(crate) struct OperationSpan;
+
+  impl OperationSpan {
+    pub(crate) fn finish(self, _outcome: &'static str) {}
+  }
+
+  pub(crate) struct Telemetry;
+
+  impl Telemetry {
+    pub(crate) fn start(
+      _endpoint: &str,
+      _service_name: &str,
+      _traces: bool,
+    ) -> Result<Self, String> {
+      Err("this build does not include the `otel` feature".to_string())
+    }
+
+    pub(crate) fn record(&self, _operation: &'static str, _outcome: &'static str) {}
+
+    pub(crate) fn record_removal(&self, _reason: &'static str) {}
+
+    pub(crate) fn span(&self, _name: &'static str) -> Option<OperationSpan> {
+      None
+    }
+  }
+}
+
+pub(crate) use exporter::{OperationSpan, Telemetry};
@@ -0,0 +1,35 @@
+use crate::occupancy::Occupancy;
+use parking_lot::RwLock;
+use std::ops::Deref;
+use std::sync::atomic::AtomicU64;
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Quota {
+  pub(crate) max_entries: Option<u64>,
+  pub(crate) max_bytes: Option<u64>,
+}
+
+#[derive(Default)]
+pub(crate) struct Tenant {
+  quota: RwLock<Quota>,
+  occupancy: Occupancy,
+  pub(crate) evictions: AtomicU64,
+}
+
+impl Deref for Tenant {
+  type Target = Occupancy;
+
+  fn deref(&self) -> &Occupancy {
+    &self.occupancy
+  }
+}
+
+impl Tenant {
+  pub(crate) fn quota(&self) -> Quota {
+    *self.quota.read()
+  }
+
+  pub(crate) fn set_quota(&self, quota: Quota) {
+    *self.quota.write() = quota;
+  }
+}
@@ -0,0 +1,333 @@
+use crate::collection::Collection;
+use crate::compression::Codec;
+use crate::encryption::NONCE_LEN;
+use fnv::FnvHasher;
+use napi::bindgen_prelude::ToNapiValue;
+use napi::sys;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::hash::Hasher;
+use std::ptr;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub(crate) enum CacheValue {
+  Text(Arc<str>),
+  Bytes(Arc<[u8]>),
+  Json {
+    document: Value,
+    encoded_len: usize,
+  },
+  Compressed {
+    codec: Codec,
+    kind: ValueKind,
+    data: Vec<u8>,
+  },
+  Encrypted {
+    key_id: u32,
+    nonce: [u8; NONCE_LEN],
+    ciphertext: Vec<u8>,
+  },
+  Collection(Collection),
+}
+
+pub struct SharedText(Arc<str>);
+
+impl ToNapiValue for SharedText {
+  unsafe fn to_napi_value(env: sys::napi_env, text: Self) -> napi::Result<sys::napi_value> {
+    unsafe { <&str>::to_napi_value(env, &text.0) }
+  }
+}
+
+pub struct SharedBytes(Arc<[u8]>);
+
+impl ToNapiValue for SharedBytes {
+  unsafe fn to_napi_value(env: sys::napi_env, bytes: Self) -> napi::Result<sys::napi_value> {
+    let mut buffer = ptr::null_mut();
+    let status = unsafe {
+      sys::napi_create_buffer_copy(
+        env,
+        bytes.0.len(),
+        bytes.0.as_ptr().cast(),
+        ptr::null_mut(),
+        &mut buffer,
+      )
+    };
+    napi::check_status!(status, "Failed to copy cached bytes into a Buffer")?;
+    Ok(buffer)
+  }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum ValueKind {
+  Text,
+  Bytes,
+  Json,
+  Collection = 5,
+}
+
+impl ValueKind {
+  fn from_tag(tag: u8) -> Option<Self> {
+    match tag {
+      0 => Some(ValueKind::Text),
+      1 => Some(ValueKind::Bytes),
+      2 => Some(ValueKind::Json),
+      5 => Some(ValueKind::Collection),
+      _ => None,
+    }
+  }
+}
+
+const COMPRESSED_TAG: u8 = 3;
+const ENCRYPTED_TAG: u8 = 4;
+
+impl CacheValue {
+  pub(crate) fn len(&self) -> usize {
+    match self {
+      CacheValue::Text(text) => text.len(),
+      CacheValue::Bytes(bytes) => bytes.len(),
+      CacheValue::Json { encoded_len, .. } => *encoded_len,
+      CacheValue::Compressed { data, .. } => data.len(),
+      CacheValue::Encrypted { ciphertext, .. } => NONCE_LEN + ciphertext.len(),
+      CacheValue::Collection(collection) => collection.len(),
+    }
+  }
+
+  pub(crate) fn is_empty_collection(&self) -> bool {
+    matches!(self, CacheValue::Collection(collection) if collection.is_empty())
+  }
+
+  pub(crate) fn to_text(&self) -> Option<String> {
+    Some(match self.expanded()?.as_ref() {
+      CacheValue::Text(text) => text.to_string(),
+      CacheValue::Bytes(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+      CacheValue::Json { document, .. } => document.to_string(),
+      CacheValue::Collection(collection) => collection.to_json_string(),
+      CacheValue::Compressed { .. } | CacheValue::Encrypted { .. } => return None,
+    })
+  }
+
+  pub(crate) fn to_bytes(&self) -> Option<Vec<u8>> {
+    Some(match self.expanded()?.as_ref() {
+      CacheValue::Text(text) => text.as_bytes().to_vec(),
+      CacheValue::Bytes(bytes) => bytes.to_vec(),
+      CacheValue::Json { document, .. } => document.to_string().into_bytes(),
+      CacheValue::Collection(collection) => collection.to_json_string().into_bytes(),
+      CacheValue::Compressed { .. } | CacheValue::Encrypted { .. } => return None,
+    })
+  }
+
+  pub(crate) fn shared_bytes(&self) -> Option<SharedBytes> {
+    Some(SharedBytes(match self.expanded()?.as_ref() {
+      CacheValue::Text(text) => text.clone().into(),
+      CacheValue::Bytes(bytes) => bytes.clone(),
+      CacheValue::Json { document, .. } => document.to_string().into_bytes().into(),
+      CacheValue::Collection(collection) => collection.to_json_string().into_bytes().into(),
+      CacheValue::Compressed { .. } | CacheValue::Encrypted { .. } => return None,
+    }))
+  }
+
+  pub(crate) fn shared_text(&self) -> Option<SharedText> {
+    Some(SharedText(match self.expanded()?.as_ref() {
+      CacheValue::Text(text) => text.clone(),
+      CacheValue::Bytes(bytes) => String::from_utf8_lossy(bytes).into(),
+      CacheValue::Json { document, .. } => document.to_string().into(),
+      CacheValue::Collection(collection) => collection.to_json_string().into(),
+      CacheValue::Compressed { .. } | CacheValue::Encrypted { .. } => return None,
+    }))
+  }
+
+  pub(crate) fn to_json(&self) -> Option<serde_json::Result<Value>> {
+    self.with_json(Value::clone)
+  }
+
+  pub(crate) fn etag(&self) -> Option<u64> {
+    let mut hasher = FnvHasher::default();
+    match self.expanded()?.as_ref() {
+      CacheValue::Text(text) => hasher.write(text.as_bytes()),
+      CacheValue::Bytes(bytes) => hasher.write(bytes),
+      CacheValue::Json { document, .. } => hasher.write(document.to_string().as_bytes()),
+      CacheValue::Collection(collection) => collection.digest(&mut hasher),
+      CacheValue::Compressed { .. } | CacheValue::Encrypted { .. } => return None,
+    }
+    Some(hasher.finish())
+  }
+
+  pub(crate) fn with_json<T>(
+    &self,
+    read: impl FnOnce(&Value) -> T,
+  ) -> Option<serde_json::Result<T>> {
+    Some(match self.expanded()?.as_ref() {
+      CacheValue::Text(text) => serde_json::from_str(text).map(|document| read(&document)),
+      CacheValue::Bytes(bytes) => serde_json::from_slice(bytes).map(|document| read(&document)),
+      CacheValue::Json { document, .. } => Ok(read(document)),
+      CacheValue::Collection(collection) => Ok(read(&collection.to_json())),
+      CacheValue::Compressed { .. } | CacheValue::Encrypted { .. } => return None,
+    })
+  }
+
+  pub(crate) fn with_collection<T>(&self, read: impl FnOnce(&Collection) -> T) -> Option<T> {
+    match self.expanded()?.as_ref() {
+      CacheValue::Collection(collection) => Some(read(collection)),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn collection_mut(&mut self) -> Option<&mut Collection> {
+    match self {
+      CacheValue::Collection(collection) => Some(collection),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn compress(self, codec: Codec, threshold: usize) -> (Self, Option<(usize, usize)>) {
+    if self.len() < threshold {
+      return (self, None);
+    }
+
+    let (kind, raw) = match &self {
+      CacheValue::Text(text) => (ValueKind::Text, Cow::Borrowed(text.as_bytes())),
+      CacheValue::Bytes(bytes) => (ValueKind::Bytes, Cow::Borrowed(bytes.as_ref())),
+      CacheValue::Json { document, .. } => (
+        ValueKind::Json,
+        Cow::Owned(document.to_string().into_bytes()),
+      ),
+      CacheValue::Collection(collection) => {
+        let mut raw = Vec::with_capacity(collection.len() + 1);
+        collection.encode(&mut raw);
+        (ValueKind::Collection, Cow::Owned(raw))
+      }
+      CacheValue::Compressed { .. } | CacheValue::Encrypted { .. } => return (self, None),
+    };
+
+    match codec.compress(&raw) {
+      Ok(data) if data.len() < raw.len() => {
+        let sizes = (raw.len(), data.len());
+        (CacheValue::Compressed { codec, kind, data }, Some(sizes))
+      }
+      _ => (self, None),
+    }
+  }
+
+  pub(crate) fn encode(&self) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(self.len() + 3);
+    match self {
+      CacheValue::Text(text) => {
+        encoded.push(ValueKind::Text as u8);
+        encoded.extend_from_slice(text.as_bytes());
+      }
+      CacheValue::Bytes(bytes) => {
+        encoded.push(ValueKind::Bytes as u8);
+        encoded.extend_from_slice(bytes);
+      }
+      CacheValue::Json { document, .. } => {
+        encoded.push(ValueKind::Json as u8);
+        encoded.extend_from_slice(document.to_string().as_bytes());
+      }
+      CacheValue::Collection(collection) => {
+        encoded.push(ValueKind::Collection as u8);
+        collection.encode(&mut encoded);
+      }
+      CacheValue::Compressed { codec, kind, data } => {
+        encoded.extend_from_slice(&[COMPRESSED_TAG, codec.tag(), *kind as u8]);
+        encoded.extend_from_slice(data);
+      }
+      CacheValue::Encrypted {
+        key_id,
+        nonce,
+        ciphertext,
+      } => {
+        encoded.push(ENCRYPTED_TAG);
+        encoded.extend_from_slice(&key_id.to_le_bytes());
+        encoded.extend_from_slice(nonce);
+        encoded.extend_from_slice(ciphertext);
+      }
+    }
+    encoded
+  }
+
+  pub(crate) fn decode(encoded: &[u8]) -> Option<Self> {
+    let (&tag, body) = encoded.split_first()?;
+    if tag == ENCRYPTED_TAG {
+      let (key_id, rest) = body.split_first_chunk::<4>()?;
+      let (nonce, ciphertext) = rest.split_first_chunk::<NONCE_LEN>()?;
+      return Some(CacheValue::Encrypted {
+        key_id: u32::from_le_bytes(*key_id),
+        nonce: *nonce,
+        ciphertext: ciphertext.to_vec(),
+      });
+    }
+    if tag == COMPRESSED_TAG {
+      let [codec, kind, data @ ..] = body else {
+        return None;
+      };
+      return Some(CacheValue::Compressed {
+        codec: Codec::from_tag(*codec)?,
+        kind: ValueKind::from_tag(*kind)?,
+        data: data.to_vec(),
+      });
+    }
+
+    Some(match ValueKind::from_tag(tag)? {
+      ValueKind::Text => CacheValue::Text(std::str::from_utf8(body).ok()?.into()),
+      ValueKind::Bytes => CacheValue::Bytes(body.into()),
+      ValueKind::Json => CacheValue::from(serde_json::from_slice::<Value>(body).ok()?),
+      ValueKind::Collection => CacheValue::Collection(Collection::decode(body)?),
+    })
+  }
+
+  pub(crate) fn expanded(&self) -> Option<Cow<'_, CacheValue>> {
+    let CacheValue::Compressed { codec, kind, data } = self else {
+      return Some(Cow::Borrowed(self));
+    };
+
+    let raw = codec.decompress(data).ok()?;
+    Some(Cow::Owned(match kind {
+      ValueKind::Text => CacheValue::Text(String::from_utf8_lossy(&raw).into()),
+      ValueKind::Bytes => CacheValue::Bytes(raw.into()),
+      ValueKind::Json => serde_json::from_slice::<Value>(&raw)
+        .map(CacheValue::from)
+        .unwrap_or(CacheValue::Bytes(raw.into())),
+      ValueKind::Collection => match Collection::decode(&raw) {
+        Some(collection) => CacheValue::Collection(collection),
+        None => CacheValue::Bytes(raw.into()),
+      },
+    }))
+  }
+}
+
+impl From<String> for CacheValue {
+  fn from(text: String) -> Self {
+    CacheValue::Text(text.into())
+  }
+}
+
+impl From<Vec<u8>> for CacheValue {
+  fn from(bytes: Vec<u8>) -> Self {
+    CacheValue::Bytes(bytes.into())
+  }
+}
+
+impl From<Value> for CacheValue {
+  fn from(document: Value) -> Self {
+    let encoded_len = document.to_string().len();
+    CacheValue::Json {
+      document,
+      encoded_len,
+    }
+  }
+}
+
+impl Serialize for CacheValue {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(&self.encode())
+  }
+}
+
+impl<'de> Deserialize<'de> for CacheValue {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let encoded = Vec::<u8>::deserialize(deserializer)?;
+    CacheValue::decode(&encoded).ok_or_else(|| serde::de::Error::custom("invalid cache value"))
+  }
+}
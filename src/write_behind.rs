@@ -0,0 +1,83 @@
+use crate::{wall_millis, ChangeOperation, ChangeRecord};
+use napi::bindgen_prelude::Promise;
+use napi::threadsafe_function::ThreadsafeFunction;
+use napi::Status;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+pub(crate) type BatchWriter =
+  ThreadsafeFunction<Vec<ChangeRecord>, Promise<()>, Vec<ChangeRecord>, Status, false, true>;
+
+pub(crate) struct WriteBehind {
+  pub(crate) writer: BatchWriter,
+  batch_size: usize,
+  max_queue: usize,
+  queue: Mutex<VecDeque<ChangeRecord>>,
+  in_flight: AtomicBool,
+  sequence: AtomicU64,
+}
+
+impl WriteBehind {
+  pub(crate) fn new(writer: BatchWriter, batch_size: usize, max_queue: usize) -> Self {
+    Self {
+      writer,
+      batch_size,
+      max_queue: max_queue.max(batch_size),
+      queue: Mutex::new(VecDeque::new()),
+      in_flight: AtomicBool::new(false),
+      sequence: AtomicU64::new(0),
+    }
+  }
+
+  pub(crate) fn enqueue(&self, op: ChangeOperation, key: &str, value: Option<String>) -> bool {
+    let mut queue = self.queue.lock();
+    if queue.len() >= self.max_queue {
+      return false;
+    }
+    queue.push_back(ChangeRecord {
+      sequence: (self.sequence.fetch_add(1, Ordering::Relaxed) + 1) as i64,
+      op,
+      key: key.to_string(),
+      value,
+      timestamp: wall_millis() as i64,
+    });
+    true
+  }
+
+  pub(crate) fn take_batch(&self, force: bool) -> Option<Vec<ChangeRecord>> {
+    let mut queue = self.queue.lock();
+    if queue.is_empty() || (!force && queue.len() < self.batch_size) {
+      return None;
+    }
+    if self.in_flight.swap(true, Ordering::AcqRel) {
+      return None;
+    }
+    let size = queue.len().min(self.batch_size);
+    Some(queue.drain(..size).collect())
+  }
+
+  pub(crate) fn finish(&self, failed: Option<Vec<ChangeRecord>>) -> usize {
+    let mut dropped = 0;
+    if let Some(batch) = failed {
+      let mut queue = self.queue.lock();
+      for record in batch.into_iter().rev() {
+        if queue.len() >= self.max_queue {
+          dropped += 1;
+        } else {
+          queue.push_front(record);
+        }
+      }
+    }
+    self.in_flight.store(false, Ordering::Release);
+    dropped
+  }
+
+  pub(crate) fn pending(&self) -> usize {
+    self.queue.lock().len()
+  }
+
+  pub(crate) fn is_idle(&self) -> bool {
+    !self.in_flight.load(Ordering::Acquire) && self.queue.lock().is_empty()
+  }
+}